@@ -1,9 +1,11 @@
-use std::mem::replace;
-
 use openmls::prelude::*;
 
+/// The number of key packages that are published for each identity.
+const KEY_PACKAGE_COUNT: usize = 5;
+
 pub struct Identity {
-    pub(crate) kpb: KeyPackageBundle,
+    pub(crate) ciphersuite: CiphersuiteName,
+    pub(crate) key_package_store: InMemoryKeyPackageStore,
     pub(crate) credential: CredentialBundle,
 }
 
@@ -11,22 +13,51 @@ impl Identity {
     pub(crate) fn new(ciphersuite: CiphersuiteName, id: &[u8]) -> Self {
         let credential_bundle =
             CredentialBundle::new(id.to_vec(), CredentialType::Basic, ciphersuite).unwrap();
-        let key_package_bundle =
-            KeyPackageBundle::new(&[ciphersuite], &credential_bundle, vec![]).unwrap();
+        let mut key_package_store = InMemoryKeyPackageStore::new();
+        key_package_store
+            .generate(
+                KEY_PACKAGE_COUNT,
+                &[ciphersuite],
+                &credential_bundle,
+                vec![],
+            )
+            .unwrap();
+        // The last resort key package is used when all others are consumed.
+        key_package_store
+            .replenish_last_resort(&[ciphersuite], &credential_bundle, vec![], &SystemClock)
+            .unwrap();
         Self {
-            kpb: key_package_bundle,
+            ciphersuite,
+            key_package_store,
             credential: credential_bundle,
         }
     }
 
-    /// Update the key package bundle in this identity.
-    /// The function returns the old `KeyPackageBundle`.
-    pub fn update(&mut self) -> KeyPackageBundle {
-        let ciphersuite = self.kpb.key_package().ciphersuite_name();
-        let key_package_bundle =
-            KeyPackageBundle::new(&[ciphersuite], &self.credential, vec![]).unwrap();
+    /// Generate fresh key packages after key packages were consumed by
+    /// `Welcome` messages, so that the store again contains
+    /// `KEY_PACKAGE_COUNT` key packages and a valid last resort key package.
+    pub(crate) fn replenish_key_packages(&mut self) {
+        let mut key_packages = self
+            .key_package_store
+            .replenish(
+                KEY_PACKAGE_COUNT,
+                &[self.ciphersuite],
+                &self.credential,
+                vec![],
+            )
+            .unwrap();
+        key_packages.extend(
+            self.key_package_store
+                .replenish_last_resort(&[self.ciphersuite], &self.credential, vec![], &SystemClock)
+                .unwrap(),
+        );
+        log::debug!("Generated {} new key packages", key_packages.len());
+    }
 
-        replace(&mut self.kpb, key_package_bundle)
+    /// Create a fresh `KeyPackageBundle` for this identity, e.g. to create a
+    /// new group.
+    pub fn new_key_package_bundle(&self) -> KeyPackageBundle {
+        KeyPackageBundle::new(&[self.ciphersuite], &self.credential, vec![]).unwrap()
     }
 
     /// Get the plain credential as byte vector.
@@ -56,10 +56,13 @@ impl User {
 
     /// Get the key packages fo this user.
     pub fn key_packages(&self) -> Vec<(Vec<u8>, KeyPackage)> {
-        vec![(
-            self.identity.borrow().kpb.key_package().hash(),
-            self.identity.borrow().kpb.key_package().clone(),
-        )]
+        self.identity
+            .borrow()
+            .key_package_store
+            .key_packages()
+            .into_iter()
+            .map(|key_package| (key_package.hash(), key_package))
+            .collect()
     }
 
     /// Get a list of clients in the group to send messages to.
@@ -145,6 +148,13 @@ impl User {
                             }
                         }
                         MLSMessage::Plaintext(msg) => msg,
+                        _ => {
+                            log::error!(
+                                "Unexpected {:?} message. Dropping message.",
+                                message.wire_format()
+                            );
+                            continue;
+                        }
                     };
                     let group = match self.groups.get_mut(&msg.group_id().as_slice()) {
                         Some(g) => g,
@@ -225,7 +235,7 @@ impl User {
         let group_id = name.as_bytes();
         let mut group_aad = group_id.to_vec();
         group_aad.extend(b" AAD");
-        let kpb = self.identity.borrow_mut().update();
+        let kpb = self.identity.borrow().new_key_package_bundle();
         let mut config = GroupConfig::default();
         config.add_ratchet_tree_extension = true;
         let mls_group = MlsGroup::new(group_id, CIPHERSUITE, kpb, config).unwrap();
@@ -266,11 +276,11 @@ impl User {
             None => return Err(format!("No group with name {} known.", group)),
         };
         let credentials = &self.identity.borrow().credential;
-        let add_proposal = group.mls_group.borrow().create_add_proposal(
-            &group.group_aad,
-            credentials,
-            key_package,
-        );
+        let add_proposal = group
+            .mls_group
+            .borrow()
+            .create_add_proposal(&group.group_aad, credentials, key_package)
+            .map_err(|e| format!("Error creating add proposal: {:?}", e))?;
         let proposals = vec![&add_proposal];
         let (commit, welcome_msg, _kpb) = group
             .mls_group
@@ -313,9 +323,22 @@ impl User {
     fn join_group(&mut self, welcome: Welcome) -> Result<(), String> {
         log::debug!("{} joining group ...", self.username);
 
-        let kpb = self.identity.borrow_mut().update();
+        let kpb = match self
+            .identity
+            .borrow()
+            .key_package_store
+            .bundle_for_welcome(&welcome)
+        {
+            Some(kpb) => kpb,
+            None => {
+                let s = "No key package found for the Welcome.".to_string();
+                log::info!("{}", s);
+                return Err(s);
+            }
+        };
         let mls_group = match MlsGroup::new_from_welcome(
-            welcome, None, /* no public tree here, has to be in the extension */
+            welcome.clone(),
+            None, /* no public tree here, has to be in the extension */
             kpb,
         ) {
             Ok(g) => g,
@@ -326,6 +349,12 @@ impl User {
             }
         };
 
+        // The key package is consumed now that the group was joined.
+        let mut identity = self.identity.borrow_mut();
+        identity.key_package_store.remove_for_welcome(&welcome);
+        identity.replenish_key_packages();
+        drop(identity);
+
         let group_id = mls_group.group_id();
         // XXX: Add application layer protocol for name etc.
         let group_name = String::from_utf8(group_id.as_slice()).unwrap();
@@ -51,27 +51,13 @@ impl ClientInfo {
 /// (see OpenMLS) for details.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
-    /// An `MLSMessage` is either an OpenMLS `MLSCiphertext` or `MLSPlaintext`.
+    /// An `MLSMessage`, e.g. an OpenMLS `MLSCiphertext` or `MLSPlaintext`.
     MLSMessage(MLSMessage),
 
     /// An OpenMLS `Welcome` message.
     Welcome(Welcome),
 }
 
-/// Enum defining encodings for the different message types/
-#[derive(Debug, Clone, Copy)]
-#[repr(u8)]
-pub enum MessageType {
-    /// An MLSCiphertext message.
-    MLSCiphertext = 0,
-
-    /// An MLSPlaintext message.
-    MLSPlaintext = 1,
-
-    /// A Welcome message.
-    Welcome = 2,
-}
-
 /// An MLS group message.
 /// This is an `MLSMessage` plus the list of recipients as a vector of client
 /// names.
@@ -91,13 +77,15 @@ impl GroupMessage {
         }
     }
 
-    /// Get the group ID as plain byte vector.
-    pub fn group_id(&self) -> Vec<u8> {
+    /// Get the group ID as plain byte vector, or `None` if the message isn't
+    /// bound to a group.
+    pub fn group_id(&self) -> Option<Vec<u8>> {
         self.msg.group_id()
     }
 
-    /// Get the epoch as plain u64.
-    pub fn epoch(&self) -> u64 {
+    /// Get the epoch as plain u64, or `None` if the message isn't bound to a
+    /// group.
+    pub fn epoch(&self) -> Option<u64> {
         self.msg.epoch()
     }
 
@@ -118,6 +106,8 @@ impl Codec for ClientKeyPackages {
     }
 }
 
+impl TlsSerialization for ClientKeyPackages {}
+
 impl Codec for ClientInfo {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         self.client_name.encode(buffer)?;
@@ -131,52 +121,20 @@ impl Codec for ClientInfo {
     }
 }
 
-impl Codec for MessageType {
-    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
-        (*self as u8).encode(buffer)
-    }
-    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
-        let value = u8::decode(cursor)?;
-        match value {
-            0 => Ok(Self::MLSCiphertext),
-            1 => Ok(Self::MLSPlaintext),
-            2 => Ok(Self::Welcome),
-            _ => Err(CodecError::DecodingError),
-        }
-    }
-}
+impl TlsSerialization for ClientInfo {}
 
 impl Codec for Message {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         match self {
-            Message::MLSMessage(m) => match m {
-                MLSMessage::Ciphertext(m) => {
-                    MessageType::MLSCiphertext.encode(buffer)?;
-                    m.encode(buffer)?;
-                }
-                MLSMessage::Plaintext(m) => {
-                    MessageType::MLSPlaintext.encode(buffer)?;
-                    m.encode(buffer)?;
-                }
-            },
-            Message::Welcome(m) => {
-                MessageType::Welcome.encode(buffer)?;
-                m.encode(buffer)?;
-            }
+            Message::MLSMessage(m) => m.encode(buffer),
+            Message::Welcome(m) => MLSMessage::Welcome(m.clone()).encode(buffer),
         }
-        Ok(())
     }
 
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
-        let msg_type = MessageType::decode(cursor)?;
-        let msg = match msg_type {
-            MessageType::MLSCiphertext => {
-                Message::MLSMessage(MLSMessage::Ciphertext(MLSCiphertext::decode(cursor)?))
-            }
-            MessageType::MLSPlaintext => {
-                Message::MLSMessage(MLSMessage::Plaintext(MLSPlaintext::decode(cursor)?))
-            }
-            MessageType::Welcome => Message::Welcome(Welcome::decode(cursor)?),
+        let msg = match MLSMessage::decode(cursor)? {
+            MLSMessage::Welcome(m) => Message::Welcome(m),
+            m => Message::MLSMessage(m),
         };
         Ok(msg)
     }
@@ -184,24 +142,13 @@ impl Codec for Message {
 
 impl Codec for GroupMessage {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
-        match &self.msg {
-            MLSMessage::Ciphertext(m) => {
-                MessageType::MLSCiphertext.encode(buffer)?;
-                m.encode(buffer)?;
-            }
-            MLSMessage::Plaintext(m) => {
-                MessageType::MLSPlaintext.encode(buffer)?;
-                m.encode(buffer)?;
-            }
-        }
+        self.msg.encode(buffer)?;
         self.recipients.encode(buffer)
     }
 
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
-        let msg_type = MessageType::decode(cursor)?;
-        let msg = match msg_type {
-            MessageType::MLSCiphertext => MLSMessage::Ciphertext(MLSCiphertext::decode(cursor)?),
-            MessageType::MLSPlaintext => MLSMessage::Plaintext(MLSPlaintext::decode(cursor)?),
+        let msg = match MLSMessage::decode(cursor)? {
+            m @ MLSMessage::Ciphertext(_) | m @ MLSMessage::Plaintext(_) => m,
             _ => return Err(CodecError::DecodingError),
         };
 
@@ -209,3 +156,5 @@ impl Codec for GroupMessage {
         Ok(Self { msg, recipients })
     }
 }
+
+impl TlsSerialization for GroupMessage {}
@@ -86,7 +86,7 @@ async fn register_client(mut body: Payload, data: web::Data<Mutex<DsData>>) -> i
     while let Some(item) = body.next().await {
         bytes.extend_from_slice(&unwrap_item!(item));
     }
-    let info = match ClientInfo::decode(&mut Cursor::new(&bytes)) {
+    let info = match ClientInfo::from_tls_bytes(&bytes) {
         Ok(i) => i,
         Err(_) => {
             log::error!("Invalid payload for /clients/register\n{:?}", bytes);
@@ -165,7 +165,7 @@ async fn send_welcome(mut body: Payload, data: web::Data<Mutex<DsData>>) -> impl
     while let Some(item) = body.next().await {
         bytes.extend_from_slice(&unwrap_item!(item));
     }
-    let welcome_msg = unwrap_data!(Welcome::decode(&mut Cursor::new(&bytes)));
+    let welcome_msg = unwrap_data!(Welcome::from_tls_bytes(&bytes));
     log::debug!("Storing welcome message: {:?}", welcome_msg);
 
     let mut data = unwrap_data!(data.lock());
@@ -194,7 +194,7 @@ async fn msg_send(mut body: Payload, data: web::Data<Mutex<DsData>>) -> impl Res
     while let Some(item) = body.next().await {
         bytes.extend_from_slice(&unwrap_item!(item));
     }
-    let group_msg = unwrap_data!(GroupMessage::decode(&mut Cursor::new(&bytes)));
+    let group_msg = unwrap_data!(GroupMessage::from_tls_bytes(&bytes));
     log::debug!("Storing group message: {:?}", group_msg);
 
     let mut data = unwrap_data!(data.lock());
@@ -205,8 +205,10 @@ async fn msg_send(mut body: Payload, data: web::Data<Mutex<DsData>>) -> impl Res
     //      painful to test in the current setting. This should get tested through
     //      the client and maybe later with the managed API.
     if group_msg.msg.is_handshake_message() {
-        let epoch = group_msg.epoch();
-        let group_id = group_msg.group_id();
+        let (group_id, epoch) = match (group_msg.group_id(), group_msg.epoch()) {
+            (Some(group_id), Some(epoch)) => (group_id, epoch),
+            _ => return actix_web::HttpResponse::BadRequest().finish(),
+        };
         if let Some(&group_epoch) = data.groups.get(&group_id) {
             if group_epoch > epoch {
                 return actix_web::HttpResponse::Conflict().finish();
@@ -188,8 +188,9 @@ async fn test_group() {
         client2_key_packages.remove(client2_key_package);
 
     // With the key package we can build a proposal.
-    let client2_add_proposal =
-        group.create_add_proposal(group_aad, &credentials[0], client2_key_package);
+    let client2_add_proposal = group
+        .create_add_proposal(group_aad, &credentials[0], client2_key_package)
+        .expect("Could not create proposal.");
     let epoch_proposals_ref = vec![&client2_add_proposal];
     let (commit, welcome_msg, _kpb) = group
         .create_commit(group_aad, &credentials[0], &epoch_proposals_ref, &[], false)
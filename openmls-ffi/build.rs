@@ -0,0 +1,22 @@
+//! Generates the C header from the FFI functions and writes it to
+//! `OUT_DIR/openmls.h`.
+//! The committed `include/openmls.h` is updated with the cbindgen CLI, see the
+//! Readme.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("Unable to read cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate the C header")
+        .write_to_file(PathBuf::from(env::var("OUT_DIR").unwrap()).join("openmls.h"));
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,332 @@
+/* This file is generated by cbindgen from openmls-ffi. Do not edit it manually. */
+
+#ifndef OPENMLS_H
+#define OPENMLS_H
+
+#include <stdint.h>
+#include <stddef.h>
+
+// The function succeeded.
+#define OPENMLS_OK 0
+
+// A required pointer argument was null.
+#define OPENMLS_ERROR_NULL_POINTER 9000
+
+// OpenMLS panicked. The handles passed to the function must not be used
+// anymore.
+#define OPENMLS_ERROR_PANIC 9001
+
+// None of the key packages in the store is referenced by the `Welcome`.
+#define OPENMLS_ERROR_KEY_PACKAGE_NOT_FOUND 9002
+
+// The group state couldn't be saved or loaded.
+#define OPENMLS_ERROR_SERIALIZATION 9003
+
+// Stable numeric error codes for FFI consumers.
+//
+// The values are grouped by module and never change. New codes are only
+// added, and codes of removed errors are not reused.
+enum ErrorCode {
+  ErrorCode_CodecEncodingError = 100,
+  ErrorCode_CodecDecodingError = 101,
+  ErrorCode_CodecTrailingData = 102,
+  ErrorCode_CodecOther = 103,
+  ErrorCode_ConfigInvalidConfig = 200,
+  ErrorCode_ConfigUnsupportedMlsVersion = 201,
+  ErrorCode_ConfigUnsupportedCiphersuite = 202,
+  ErrorCode_ConfigUnsupportedSignatureScheme = 203,
+  ErrorCode_CryptoLibraryError = 300,
+  ErrorCode_CryptoHpkeDecryptionError = 301,
+  ErrorCode_CryptoInvalidKey = 302,
+  ErrorCode_CredentialUnsupportedCredentialType = 400,
+  ErrorCode_CredentialUnsupportedVersion = 401,
+  ErrorCode_CredentialKeyMismatch = 402,
+  ErrorCode_KeyPackageUnsupportedCiphersuite = 500,
+  ErrorCode_KeyPackageUnknownConfigError = 501,
+  ErrorCode_KeyPackageMandatoryExtensionsMissing = 502,
+  ErrorCode_KeyPackageInvalidLifetimeExtension = 503,
+  ErrorCode_KeyPackageLifetimeTooLong = 504,
+  ErrorCode_KeyPackageInvalidSignature = 505,
+  ErrorCode_KeyPackageLibraryError = 506,
+  ErrorCode_KeyPackageDuplicateExtension = 507,
+  ErrorCode_KeyPackageNoCiphersuitesSupplied = 508,
+  ErrorCode_KeyPackageCiphersuiteMismatch = 509,
+  ErrorCode_KeyPackageUserIdMismatch = 510,
+  ErrorCode_KeyPackageSignatureSchemeMismatch = 511,
+  ErrorCode_KeyPackageCapabilitiesExtensionSupplied = 512,
+  ErrorCode_KeyPackageIdentityMismatch = 513,
+  ErrorCode_ExtensionInvalidExtensionType = 600,
+  ErrorCode_ExtensionInvalidLifetime = 601,
+  ErrorCode_ExtensionLifetimeExpired = 602,
+  ErrorCode_ExtensionInvalidCapabilities = 603,
+  ErrorCode_ExtensionCapabilitiesEmptyVersionsField = 604,
+  ErrorCode_ExtensionCapabilitiesUnsupportedCiphersuite = 605,
+  ErrorCode_ExtensionInvalidKeyPackageId = 606,
+  ErrorCode_ExtensionInvalidParentHash = 607,
+  ErrorCode_ExtensionInvalidRatchetTree = 608,
+  ErrorCode_ExtensionInvalidRoles = 609,
+  ErrorCode_ExtensionRolesDuplicateIdentity = 610,
+  ErrorCode_ExtensionInvalidUserId = 611,
+  ErrorCode_ExtensionInvalidLastResort = 612,
+  ErrorCode_ExtensionDuplicate = 613,
+  ErrorCode_TreeInvalidArguments = 700,
+  ErrorCode_TreeInvalidUpdatePath = 701,
+  ErrorCode_TreeInvalidTree = 702,
+  ErrorCode_TreePathSecretDecryptionError = 703,
+  ErrorCode_PlaintextNotAnApplicationMessage = 900,
+  ErrorCode_PlaintextInvalidMembershipTag = 901,
+  ErrorCode_CiphertextInvalidContentType = 1000,
+  ErrorCode_CiphertextInvalidSenderData = 1001,
+  ErrorCode_CiphertextGenerationOutOfBound = 1002,
+  ErrorCode_CiphertextUnknownSender = 1003,
+  ErrorCode_CiphertextInvalidPadding = 1004,
+  ErrorCode_CiphertextWrongEpoch = 1005,
+  ErrorCode_CiphertextReplay = 1006,
+  ErrorCode_CiphertextDecryptionError = 1007,
+  ErrorCode_CiphertextDecodingError = 1008,
+  ErrorCode_CiphertextInvalidSignature = 1009,
+  ErrorCode_CiphertextSecretReuse = 1010,
+  ErrorCode_WelcomeCiphersuiteMismatch = 1100,
+  ErrorCode_WelcomeJoinerSecretNotFound = 1101,
+  ErrorCode_WelcomeMissingRatchetTree = 1102,
+  ErrorCode_WelcomeTreeHashMismatch = 1103,
+  ErrorCode_WelcomeConfirmationTagMismatch = 1104,
+  ErrorCode_WelcomeInvalidGroupInfoSignature = 1105,
+  ErrorCode_WelcomeGroupInfoDecryptionFailure = 1106,
+  ErrorCode_WelcomeDuplicateRatchetTreeExtension = 1107,
+  ErrorCode_WelcomeUnsupportedMlsVersion = 1108,
+  ErrorCode_WelcomeUnknownError = 1109,
+  ErrorCode_WelcomeGroupSecretsDecryptionFailure = 1110,
+  ErrorCode_ApplyCommitEpochMismatch = 1200,
+  ErrorCode_ApplyCommitWrongPlaintextContentType = 1201,
+  ErrorCode_ApplyCommitSelfRemoved = 1202,
+  ErrorCode_ApplyCommitPathKeyPackageVerificationFailure = 1203,
+  ErrorCode_ApplyCommitProposalKeyPackageVerificationFailure = 1204,
+  ErrorCode_ApplyCommitCredentialIdentityMismatch = 1205,
+  ErrorCode_ApplyCommitNoParentHashExtension = 1206,
+  ErrorCode_ApplyCommitParentHashMismatch = 1207,
+  ErrorCode_ApplyCommitPlaintextSignatureFailure = 1208,
+  ErrorCode_ApplyCommitInvalidMembershipTag = 1209,
+  ErrorCode_ApplyCommitRequiredPathNotFound = 1210,
+  ErrorCode_ApplyCommitConfirmationTagMismatch = 1211,
+  ErrorCode_ApplyCommitMissingOwnKeyPackage = 1212,
+  ErrorCode_ApplyCommitMissingProposal = 1213,
+  ErrorCode_ApplyCommitOwnKeyNotFound = 1214,
+  ErrorCode_CreateCommitCannotRemoveSelf = 1300,
+  ErrorCode_CreateCommitOwnKeyNotFound = 1301,
+  ErrorCode_ExporterKeyLengthTooLong = 1400,
+  ErrorCode_ExporterKeyLengthZero = 1401,
+  ErrorCode_ExporterUnknownEpoch = 1402,
+  ErrorCode_GroupSecretTypeError = 1500,
+  ErrorCode_GroupProposalQueueError = 1501,
+  ErrorCode_StorageInvalidKeyLength = 1600,
+  ErrorCode_StorageInvalidHeader = 1601,
+  ErrorCode_StorageUnsupportedVersion = 1602,
+  ErrorCode_StorageEncryptionError = 1603,
+  ErrorCode_StorageDecryptionError = 1604,
+  ErrorCode_StorageGroupIdMismatch = 1605,
+  ErrorCode_StorageEpochRollback = 1606,
+  ErrorCode_StorageStateTooOld = 1607,
+  ErrorCode_ManagedGroupLibraryError = 2000,
+  ErrorCode_ManagedGroupUseAfterEviction = 2001,
+  ErrorCode_ManagedGroupPendingProposalsExist = 2002,
+  ErrorCode_ManagedGroupUnsupportedPreSharedKeys = 2003,
+  ErrorCode_MembershipUnknownIdentity = 2100,
+  ErrorCode_MembershipUnknownUser = 2101,
+  ErrorCode_MembershipNoKeyPackages = 2102,
+  ErrorCode_MembershipUserMismatch = 2103,
+  ErrorCode_MembershipNoMatchingKeyPackage = 2104,
+  ErrorCode_MembershipUserAlreadyMember = 2105,
+  ErrorCode_RolePolicyNotEnabled = 2200,
+  ErrorCode_RolePolicyInsufficientRole = 2201,
+  ErrorCode_RolePolicyUnknownMember = 2202,
+  ErrorCode_RolePolicyNoOwnerLeft = 2203,
+  ErrorCode_RolePolicyInvalidRoles = 2204,
+  ErrorCode_CredentialRotationIdentityMismatch = 2300,
+  ErrorCode_CredentialRotationCredentialMismatch = 2301,
+  ErrorCode_CredentialRotationNotCommitted = 2302,
+  ErrorCode_InvalidMessageUnexpectedWireFormat = 2400,
+  ErrorCode_InvalidMessageInvalidMembershipTag = 2401,
+  ErrorCode_InvalidMessageInvalidCiphertext = 2402,
+  ErrorCode_InvalidMessageCommitWithInvalidProposals = 2403,
+};
+typedef uint16_t ErrorCode;
+
+// A credential together with its signature private key.
+typedef struct OpenMlsCredential OpenMlsCredential;
+
+// A store of the key package bundles of a client.
+typedef struct OpenMlsKeyPackageStore OpenMlsKeyPackageStore;
+
+// A group of the client.
+typedef struct OpenMlsGroup OpenMlsGroup;
+
+// A byte buffer that is owned by the caller.
+//
+// Buffers returned by OpenMLS must be released with [`openmls_buffer_free()`].
+// An empty buffer has a null `data` pointer.
+typedef struct OpenMlsBuffer {
+  uint8_t *data;
+  uintptr_t len;
+} OpenMlsBuffer;
+
+// Release a buffer returned by OpenMLS.
+void openmls_buffer_free(OpenMlsBuffer buffer);
+
+// Create a new basic credential for the `identity` with a fresh signature
+// key of the `ciphersuite`.
+uint16_t openmls_credential_new(const uint8_t *identity,
+                                uintptr_t identity_len,
+                                uint16_t ciphersuite,
+                                OpenMlsCredential **credential);
+
+// Load a credential serialized with [`openmls_credential_to_bytes()`].
+uint16_t openmls_credential_from_bytes(const uint8_t *bytes,
+                                       uintptr_t bytes_len,
+                                       OpenMlsCredential **credential);
+
+// Serialize the credential including its private key.
+uint16_t openmls_credential_to_bytes(const OpenMlsCredential *credential, OpenMlsBuffer *bytes);
+
+// Release a credential. Groups using it keep their own reference.
+void openmls_credential_free(OpenMlsCredential *credential);
+
+// Create a new, empty key package store.
+uint16_t openmls_key_package_store_new(OpenMlsKeyPackageStore **store);
+
+// Generate `count` key packages of the `ciphersuite` for the `credential`
+// and keep their private keys in the `store`.
+//
+// The key packages are returned as `KeyPackage key_packages<0..2^32-1>`.
+uint16_t openmls_key_package_store_generate(OpenMlsKeyPackageStore *store,
+                                            const OpenMlsCredential *credential,
+                                            uint16_t ciphersuite,
+                                            uintptr_t count,
+                                            OpenMlsBuffer *key_packages);
+
+// Persist the key package bundles in the `store`, including their private
+// keys.
+uint16_t openmls_key_package_store_save(const OpenMlsKeyPackageStore *store, OpenMlsBuffer *state);
+
+// Load a key package store persisted with
+// [`openmls_key_package_store_save()`].
+uint16_t openmls_key_package_store_load(const uint8_t *state,
+                                        uintptr_t state_len,
+                                        OpenMlsKeyPackageStore **store);
+
+// Release a key package store.
+void openmls_key_package_store_free(OpenMlsKeyPackageStore *store);
+
+// Create a new group with the `group_id` and the `credential` as its only
+// member.
+uint16_t openmls_group_new(const OpenMlsCredential *credential,
+                           const uint8_t *group_id,
+                           uintptr_t group_id_len,
+                           uint16_t ciphersuite,
+                           OpenMlsGroup **group);
+
+// Join a group with a `Welcome` message. The key package the `Welcome` was
+// created for is taken from the `store` and removed from it once the group
+// was joined, unless it is a last resort key package. The `ratchet_tree` is
+// the `optional<Node> ratchet_tree<0..2^32-1>` exported by a member with
+// [`openmls_group_export_ratchet_tree()`].
+uint16_t openmls_group_new_from_welcome(const OpenMlsCredential *credential,
+                                        OpenMlsKeyPackageStore *store,
+                                        const uint8_t *welcome,
+                                        uintptr_t welcome_len,
+                                        const uint8_t *ratchet_tree,
+                                        uintptr_t ratchet_tree_len,
+                                        OpenMlsGroup **group);
+
+// Export the ratchet tree of the group as
+// `optional<Node> ratchet_tree<0..2^32-1>`. New members need it to join.
+uint16_t openmls_group_export_ratchet_tree(const OpenMlsGroup *group, OpenMlsBuffer *ratchet_tree);
+
+// Return the members of the group as a list of their leaf index and
+// identity, encoded as `Member members<0..2^32-1>` with
+// `struct { uint32 index; opaque identity<0..2^32-1>; } Member`.
+uint16_t openmls_group_members(const OpenMlsGroup *group, OpenMlsBuffer *members);
+
+// Add members to the group. The `key_packages` are encoded as
+// `KeyPackage key_packages<0..2^32-1>`.
+//
+// Returns the commit as `MLSMessage messages<0..2^32-1>` that has to be sent
+// to the group and passed to [`openmls_group_process_messages()`], and the
+// `Welcome` for the new members.
+uint16_t openmls_group_add_members(OpenMlsGroup *group,
+                                   const uint8_t *key_packages,
+                                   uintptr_t key_packages_len,
+                                   OpenMlsBuffer *messages,
+                                   OpenMlsBuffer *welcome);
+
+// Remove the members at the leaf `indices` from the group.
+//
+// Returns the commit as `MLSMessage messages<0..2^32-1>` that has to be sent
+// to the group and passed to [`openmls_group_process_messages()`]. If the
+// commit also adds members, a `Welcome` is returned, otherwise the `welcome`
+// buffer is empty.
+uint16_t openmls_group_remove_members(OpenMlsGroup *group,
+                                      const uint32_t *indices,
+                                      uintptr_t indices_len,
+                                      OpenMlsBuffer *messages,
+                                      OpenMlsBuffer *welcome);
+
+// Encrypt an application `message` for the group and return the
+// `MLSMessage`.
+uint16_t openmls_group_create_message(OpenMlsGroup *group,
+                                      const uint8_t *message,
+                                      uintptr_t message_len,
+                                      OpenMlsBuffer *mls_message);
+
+// Process the `messages`, encoded as `MLSMessage messages<0..2^32-1>`.
+//
+// Returns the events that occurred as `Event events<0..2^32-1>`. See the
+// `events` module for their encoding.
+uint16_t openmls_group_process_messages(OpenMlsGroup *group,
+                                        const uint8_t *messages,
+                                        uintptr_t messages_len,
+                                        OpenMlsBuffer *events);
+
+// Persist the state of the group.
+uint16_t openmls_group_save(const OpenMlsGroup *group, OpenMlsBuffer *state);
+
+// Load a group persisted with [`openmls_group_save()`]. The `credential`
+// must be the one the group was created or joined with.
+uint16_t openmls_group_load(const OpenMlsCredential *credential,
+                            const uint8_t *state,
+                            uintptr_t state_len,
+                            OpenMlsGroup **group);
+
+// Return the current epoch of the group. Pass the epoch of the last saved
+// state as `min_epoch` to [`openmls_group_load_encrypted()`] to detect a
+// rollback.
+uint16_t openmls_group_epoch(const OpenMlsGroup *group, uint64_t *epoch);
+
+// Persist the state of the group sealed with the `storage_key` of
+// `STORAGE_KEY_LENGTH` (32) bytes. The group ID and the epoch are
+// authenticated along with the state.
+uint16_t openmls_group_save_encrypted(const OpenMlsGroup *group,
+                                      const uint8_t *storage_key,
+                                      uintptr_t storage_key_len,
+                                      OpenMlsBuffer *state);
+
+// Load a group persisted with [`openmls_group_save_encrypted()`]. The
+// `credential` must be the one the group was created or joined with.
+//
+// The state must belong to the group with the `group_id` and must not be
+// older than `min_epoch`, otherwise the `StorageGroupIdMismatch` or
+// `StorageEpochRollback` error code is returned.
+uint16_t openmls_group_load_encrypted(const OpenMlsCredential *credential,
+                                      const uint8_t *storage_key,
+                                      uintptr_t storage_key_len,
+                                      const uint8_t *group_id,
+                                      uintptr_t group_id_len,
+                                      uint64_t min_epoch,
+                                      const uint8_t *state,
+                                      uintptr_t state_len,
+                                      OpenMlsGroup **group);
+
+// Release a group.
+void openmls_group_free(OpenMlsGroup *group);
+
+#endif /* OPENMLS_H */
@@ -0,0 +1,272 @@
+//! # Group events
+//!
+//! The callbacks of a `ManagedGroup` are plain function pointers. The FFI
+//! registers callbacks that record the events in a thread local queue, and
+//! [`openmls_group_process_messages()`](crate::openmls_group_process_messages())
+//! returns the recorded events to the caller, encoded as
+//! `Event events<0..2^32-1>`.
+//!
+//! Every event starts with its type (`uint8`), followed by the fields of the
+//! event. Identities, user IDs and messages are encoded as
+//! `opaque<0..2^32-1>`, error codes as `uint16`.
+//!
+//! | Type | Event                | Fields                                     |
+//! |------|----------------------|--------------------------------------------|
+//! | 1    | Member added         | sender, added member                       |
+//! | 2    | Member removed       | removal type (`uint8`), leaver, remover    |
+//! | 3    | Member updated       | old identity, new identity                 |
+//! | 4    | User added           | sender, user ID                            |
+//! | 5    | User removed         | sender, user ID                            |
+//! | 6    | Application message  | sender, AAD, message                       |
+//! | 7    | Invalid message      | error code                                 |
+//! | 8    | Error                | error code                                 |
+//!
+//! The removal types are `0` (we left), `1` (they left), `2` (we were removed)
+//! and `3` (they were removed). The leaver or remover is empty if it's the own
+//! client.
+
+use std::cell::RefCell;
+
+use openmls::prelude::*;
+
+/// An event of a `ManagedGroup`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    MemberAdded {
+        sender: Vec<u8>,
+        added_member: Vec<u8>,
+    },
+    MemberRemoved {
+        removal_type: u8,
+        leaver: Vec<u8>,
+        remover: Vec<u8>,
+    },
+    MemberUpdated {
+        old_identity: Vec<u8>,
+        new_identity: Vec<u8>,
+    },
+    UserAdded {
+        sender: Vec<u8>,
+        user_id: Vec<u8>,
+    },
+    UserRemoved {
+        sender: Vec<u8>,
+        user_id: Vec<u8>,
+    },
+    ApplicationMessage {
+        sender: Vec<u8>,
+        aad: Vec<u8>,
+        message: Vec<u8>,
+    },
+    InvalidMessage {
+        error_code: u16,
+    },
+    Error {
+        error_code: u16,
+    },
+}
+
+impl Codec for Event {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self {
+            Event::MemberAdded {
+                sender,
+                added_member,
+            } => {
+                1u8.encode(buffer)?;
+                sender.encode(buffer)?;
+                added_member.encode(buffer)
+            }
+            Event::MemberRemoved {
+                removal_type,
+                leaver,
+                remover,
+            } => {
+                2u8.encode(buffer)?;
+                removal_type.encode(buffer)?;
+                leaver.encode(buffer)?;
+                remover.encode(buffer)
+            }
+            Event::MemberUpdated {
+                old_identity,
+                new_identity,
+            } => {
+                3u8.encode(buffer)?;
+                old_identity.encode(buffer)?;
+                new_identity.encode(buffer)
+            }
+            Event::UserAdded { sender, user_id } => {
+                4u8.encode(buffer)?;
+                sender.encode(buffer)?;
+                user_id.encode(buffer)
+            }
+            Event::UserRemoved { sender, user_id } => {
+                5u8.encode(buffer)?;
+                sender.encode(buffer)?;
+                user_id.encode(buffer)
+            }
+            Event::ApplicationMessage {
+                sender,
+                aad,
+                message,
+            } => {
+                6u8.encode(buffer)?;
+                sender.encode(buffer)?;
+                aad.encode(buffer)?;
+                message.encode(buffer)
+            }
+            Event::InvalidMessage { error_code } => {
+                7u8.encode(buffer)?;
+                error_code.encode(buffer)
+            }
+            Event::Error { error_code } => {
+                8u8.encode(buffer)?;
+                error_code.encode(buffer)
+            }
+        }
+    }
+
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        let event = match u8::decode(cursor)? {
+            1 => Event::MemberAdded {
+                sender: Vec::decode(cursor)?,
+                added_member: Vec::decode(cursor)?,
+            },
+            2 => Event::MemberRemoved {
+                removal_type: u8::decode(cursor)?,
+                leaver: Vec::decode(cursor)?,
+                remover: Vec::decode(cursor)?,
+            },
+            3 => Event::MemberUpdated {
+                old_identity: Vec::decode(cursor)?,
+                new_identity: Vec::decode(cursor)?,
+            },
+            4 => Event::UserAdded {
+                sender: Vec::decode(cursor)?,
+                user_id: Vec::decode(cursor)?,
+            },
+            5 => Event::UserRemoved {
+                sender: Vec::decode(cursor)?,
+                user_id: Vec::decode(cursor)?,
+            },
+            6 => Event::ApplicationMessage {
+                sender: Vec::decode(cursor)?,
+                aad: Vec::decode(cursor)?,
+                message: Vec::decode(cursor)?,
+            },
+            7 => Event::InvalidMessage {
+                error_code: u16::decode(cursor)?,
+            },
+            8 => Event::Error {
+                error_code: u16::decode(cursor)?,
+            },
+            _ => return Err(CodecError::DecodingError),
+        };
+        Ok(event)
+    }
+}
+
+thread_local! {
+    static EVENTS: RefCell<Vec<Event>> = RefCell::new(Vec::new());
+}
+
+fn push_event(event: Event) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+/// Remove all recorded events of the current thread and return them.
+pub(crate) fn take_events() -> Vec<Event> {
+    EVENTS.with(|events| events.replace(Vec::new()))
+}
+
+/// The callbacks that record the events of a `ManagedGroup`.
+pub(crate) fn callbacks() -> ManagedGroupCallbacks {
+    ManagedGroupCallbacks::new()
+        .with_member_added(member_added)
+        .with_member_removed(member_removed)
+        .with_member_updated(member_updated)
+        .with_user_added(user_added)
+        .with_user_removed(user_removed)
+        .with_app_message_received(app_message_received)
+        .with_invalid_message_received(invalid_message_received)
+        .with_error_occured(error_occured)
+}
+
+fn member_added(
+    _managed_group: &ManagedGroup,
+    _aad: &[u8],
+    sender: &Credential,
+    added_member: &Credential,
+) {
+    push_event(Event::MemberAdded {
+        sender: sender.identity().clone(),
+        added_member: added_member.identity().clone(),
+    });
+}
+
+fn member_removed(_managed_group: &ManagedGroup, _aad: &[u8], removal: &Removal) {
+    let (removal_type, leaver, remover) = match removal {
+        Removal::WeLeft => (0, vec![], vec![]),
+        Removal::TheyLeft(leaver) => (1, leaver.identity().clone(), vec![]),
+        Removal::WeWereRemovedBy(remover) => (2, vec![], remover.identity().clone()),
+        Removal::TheyWereRemovedBy(leaver, remover) => {
+            (3, leaver.identity().clone(), remover.identity().clone())
+        }
+    };
+    push_event(Event::MemberRemoved {
+        removal_type,
+        leaver,
+        remover,
+    });
+}
+
+fn member_updated(
+    _managed_group: &ManagedGroup,
+    _aad: &[u8],
+    old_credential: &Credential,
+    new_credential: &Credential,
+) {
+    push_event(Event::MemberUpdated {
+        old_identity: old_credential.identity().clone(),
+        new_identity: new_credential.identity().clone(),
+    });
+}
+
+fn user_added(_managed_group: &ManagedGroup, _aad: &[u8], sender: &Credential, user_id: &[u8]) {
+    push_event(Event::UserAdded {
+        sender: sender.identity().clone(),
+        user_id: user_id.to_vec(),
+    });
+}
+
+fn user_removed(_managed_group: &ManagedGroup, _aad: &[u8], sender: &Credential, user_id: &[u8]) {
+    push_event(Event::UserRemoved {
+        sender: sender.identity().clone(),
+        user_id: user_id.to_vec(),
+    });
+}
+
+fn app_message_received(
+    _managed_group: &ManagedGroup,
+    aad: &[u8],
+    sender: &Credential,
+    message: &[u8],
+) {
+    push_event(Event::ApplicationMessage {
+        sender: sender.identity().clone(),
+        aad: aad.to_vec(),
+        message: message.to_vec(),
+    });
+}
+
+fn invalid_message_received(_managed_group: &ManagedGroup, error: InvalidMessageError) {
+    push_event(Event::InvalidMessage {
+        error_code: error.code().into(),
+    });
+}
+
+fn error_occured(_managed_group: &ManagedGroup, error: ManagedGroupError) {
+    push_event(Event::Error {
+        error_code: error.code().into(),
+    });
+}
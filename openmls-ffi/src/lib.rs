@@ -0,0 +1,689 @@
+//! # OpenMLS FFI
+//!
+//! C bindings for the `ManagedGroup` API of OpenMLS. The header
+//! `include/openmls.h` is generated with cbindgen, see the Readme.
+//!
+//! ## Handles
+//! Credentials, key package stores and groups are opaque handles that are
+//! created by `openmls_*_new()` functions and must be released with the
+//! matching `openmls_*_free()` function. A group keeps its own reference to
+//! the credential it was created or loaded with, so handles can be released
+//! in any order.
+//!
+//! ## Buffers
+//! Structs are passed in as byte buffers in the TLS encoding of the MLS
+//! specification. Inputs must contain exactly one encoded value. Outputs are
+//! returned as [`OpenMlsBuffer`]s that are owned by the caller and must be
+//! released with [`openmls_buffer_free()`].
+//!
+//! ## Errors
+//! All functions return a `uint16_t` status. [`OPENMLS_OK`] signals success,
+//! values below `9000` are the `ErrorCode` of the OpenMLS error that occurred
+//! and the `OPENMLS_ERROR_*` constants are errors of the bindings themselves.
+//! Output parameters are only written on success.
+//!
+//! ## Safety
+//! All pointers passed to the functions must either be null or valid for the
+//! given length, and handles must have been created by OpenMLS and not yet
+//! been released. Handles must not be used from multiple threads at the same
+//! time.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::convert::TryFrom;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::{ptr, slice};
+
+use openmls::prelude::*;
+use openmls::tree::node::Node;
+
+pub mod events;
+
+/// The function succeeded.
+pub const OPENMLS_OK: u16 = 0;
+/// A required pointer argument was null.
+pub const OPENMLS_ERROR_NULL_POINTER: u16 = 9000;
+/// OpenMLS panicked. The handles passed to the function must not be used
+/// anymore.
+pub const OPENMLS_ERROR_PANIC: u16 = 9001;
+/// None of the key packages in the store is referenced by the `Welcome`.
+pub const OPENMLS_ERROR_KEY_PACKAGE_NOT_FOUND: u16 = 9002;
+/// The group state couldn't be saved or loaded.
+pub const OPENMLS_ERROR_SERIALIZATION: u16 = 9003;
+
+/// Error of an FFI function, carrying the status that is returned.
+struct FfiError(u16);
+
+macro_rules! implement_ffi_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for FfiError {
+                fn from(e: $error) -> Self {
+                    FfiError(e.code().into())
+                }
+            }
+        )*
+    };
+}
+
+implement_ffi_error!(
+    CodecError,
+    CredentialError,
+    KeyPackageError,
+    GroupError,
+    ManagedGroupError,
+    StorageError
+);
+
+impl From<std::io::Error> for FfiError {
+    fn from(e: std::io::Error) -> Self {
+        // Errors of sealed states are wrapped in the `io::Error`
+        match e
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<StorageError>())
+        {
+            Some(storage_error) => storage_error.clone().into(),
+            None => FfiError(OPENMLS_ERROR_SERIALIZATION),
+        }
+    }
+}
+
+/// Run `f` and turn its result into a status. Panics don't unwind into the
+/// caller.
+fn ffi_call<F: FnOnce() -> Result<(), FfiError>>(f: F) -> u16 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => OPENMLS_OK,
+        Ok(Err(FfiError(status))) => status,
+        Err(_) => OPENMLS_ERROR_PANIC,
+    }
+}
+
+/// Returns an error if any of the `pointers` is null.
+fn check_not_null(pointers: &[bool]) -> Result<(), FfiError> {
+    if pointers.iter().any(|&is_null| is_null) {
+        return Err(FfiError(OPENMLS_ERROR_NULL_POINTER));
+    }
+    Ok(())
+}
+
+/// Borrow `len` bytes at `data`. A null `data` is only allowed if `len` is 0.
+unsafe fn input_slice<'a, T>(data: *const T, len: usize) -> Result<&'a [T], FfiError> {
+    if data.is_null() {
+        if len == 0 {
+            return Ok(&[]);
+        }
+        return Err(FfiError(OPENMLS_ERROR_NULL_POINTER));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Decode exactly one value from `bytes`.
+fn decode_input<T: Codec>(bytes: &[u8]) -> Result<T, FfiError> {
+    let mut cursor = Cursor::new(bytes);
+    let value = T::decode(&mut cursor)?;
+    if cursor.has_more() {
+        return Err(CodecError::TrailingData.into());
+    }
+    Ok(value)
+}
+
+/// A byte buffer that is owned by the caller.
+///
+/// Buffers returned by OpenMLS must be released with [`openmls_buffer_free()`].
+/// An empty buffer has a null `data` pointer.
+#[repr(C)]
+pub struct OpenMlsBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl OpenMlsBuffer {
+    fn empty() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+impl From<Vec<u8>> for OpenMlsBuffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        if bytes.is_empty() {
+            return Self::empty();
+        }
+        let bytes = bytes.into_boxed_slice();
+        let len = bytes.len();
+        Self {
+            data: Box::into_raw(bytes) as *mut u8,
+            len,
+        }
+    }
+}
+
+/// Release a buffer returned by OpenMLS.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_buffer_free(buffer: OpenMlsBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+// === Credentials ===
+
+/// A credential together with its signature private key.
+pub struct OpenMlsCredential(Arc<CredentialBundle>);
+
+/// Create a new basic credential for the `identity` with a fresh signature
+/// key of the `ciphersuite`.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_credential_new(
+    identity: *const u8,
+    identity_len: usize,
+    ciphersuite: u16,
+    credential: *mut *mut OpenMlsCredential,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null()])?;
+        let identity = input_slice(identity, identity_len)?;
+        let ciphersuite_name = CiphersuiteName::try_from(ciphersuite)?;
+        let credential_bundle =
+            CredentialBundle::new(identity.to_vec(), CredentialType::Basic, ciphersuite_name)?;
+        *credential = Box::into_raw(Box::new(OpenMlsCredential(Arc::new(credential_bundle))));
+        Ok(())
+    })
+}
+
+/// Load a credential serialized with [`openmls_credential_to_bytes()`].
+#[no_mangle]
+pub unsafe extern "C" fn openmls_credential_from_bytes(
+    bytes: *const u8,
+    bytes_len: usize,
+    credential: *mut *mut OpenMlsCredential,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null()])?;
+        let credential_bundle = CredentialBundle::from_bytes(input_slice(bytes, bytes_len)?)?;
+        *credential = Box::into_raw(Box::new(OpenMlsCredential(Arc::new(credential_bundle))));
+        Ok(())
+    })
+}
+
+/// Serialize the credential including its private key.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_credential_to_bytes(
+    credential: *const OpenMlsCredential,
+    bytes: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null(), bytes.is_null()])?;
+        *bytes = (*credential).0.to_bytes()?.into();
+        Ok(())
+    })
+}
+
+/// Release a credential. Groups using it keep their own reference.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_credential_free(credential: *mut OpenMlsCredential) {
+    if !credential.is_null() {
+        drop(Box::from_raw(credential));
+    }
+}
+
+// === Key packages ===
+
+/// A store of the key package bundles of a client.
+pub struct OpenMlsKeyPackageStore(InMemoryKeyPackageStore);
+
+/// Create a new, empty key package store.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_key_package_store_new(
+    store: *mut *mut OpenMlsKeyPackageStore,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[store.is_null()])?;
+        *store = Box::into_raw(Box::new(OpenMlsKeyPackageStore(
+            InMemoryKeyPackageStore::new(),
+        )));
+        Ok(())
+    })
+}
+
+/// Generate `count` key packages of the `ciphersuite` for the `credential`
+/// and keep their private keys in the `store`.
+///
+/// The key packages are returned as `KeyPackage key_packages<0..2^32-1>`.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_key_package_store_generate(
+    store: *mut OpenMlsKeyPackageStore,
+    credential: *const OpenMlsCredential,
+    ciphersuite: u16,
+    count: usize,
+    key_packages: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[
+            store.is_null(),
+            credential.is_null(),
+            key_packages.is_null(),
+        ])?;
+        let ciphersuite_name = CiphersuiteName::try_from(ciphersuite)?;
+        let generated =
+            (*store)
+                .0
+                .generate(count, &[ciphersuite_name], &(*credential).0, vec![])?;
+        *key_packages = generated.encode_detached()?.into();
+        Ok(())
+    })
+}
+
+/// Persist the key package bundles in the `store`, including their private
+/// keys.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_key_package_store_save(
+    store: *const OpenMlsKeyPackageStore,
+    state: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[store.is_null(), state.is_null()])?;
+        let mut serialized = vec![];
+        (*store).0.save(&mut serialized)?;
+        *state = serialized.into();
+        Ok(())
+    })
+}
+
+/// Load a key package store persisted with
+/// [`openmls_key_package_store_save()`].
+#[no_mangle]
+pub unsafe extern "C" fn openmls_key_package_store_load(
+    state: *const u8,
+    state_len: usize,
+    store: *mut *mut OpenMlsKeyPackageStore,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[store.is_null()])?;
+        let key_package_store = InMemoryKeyPackageStore::load(input_slice(state, state_len)?)?;
+        *store = Box::into_raw(Box::new(OpenMlsKeyPackageStore(key_package_store)));
+        Ok(())
+    })
+}
+
+/// Release a key package store.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_key_package_store_free(store: *mut OpenMlsKeyPackageStore) {
+    if !store.is_null() {
+        drop(Box::from_raw(store));
+    }
+}
+
+// === Groups ===
+
+/// A group of the client.
+pub struct OpenMlsGroup {
+    // Borrows the credential bundle below. It is declared first, so it is
+    // dropped before the credential bundle is released.
+    group: ManagedGroup<'static>,
+    _credential_bundle: Arc<CredentialBundle>,
+}
+
+/// The configuration of groups created through the FFI. The callbacks record
+/// the events returned by [`openmls_group_process_messages()`].
+fn group_config() -> ManagedGroupConfig {
+    ManagedGroupConfig::new(
+        HandshakeMessageFormat::Plaintext,
+        UpdatePolicy::default(),
+        events::callbacks(),
+    )
+}
+
+/// Create a group handle from a `ManagedGroup` that uses the
+/// `credential_bundle`. The handle owns a reference to the credential bundle,
+/// which keeps it alive as long as the group.
+fn new_group_handle<F>(
+    credential_bundle: &Arc<CredentialBundle>,
+    f: F,
+) -> Result<*mut OpenMlsGroup, FfiError>
+where
+    F: FnOnce(&'static CredentialBundle) -> Result<ManagedGroup<'static>, FfiError>,
+{
+    let credential_bundle = Arc::clone(credential_bundle);
+    // Safety: The credential bundle lives on the heap as long as the `Arc`
+    // stored next to the group, and the group is dropped first.
+    let credential_bundle_ref: &'static CredentialBundle =
+        unsafe { &*Arc::as_ptr(&credential_bundle) };
+    let group = f(credential_bundle_ref)?;
+    Ok(Box::into_raw(Box::new(OpenMlsGroup {
+        group,
+        _credential_bundle: credential_bundle,
+    })))
+}
+
+/// Create a new group with the `group_id` and the `credential` as its only
+/// member.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_new(
+    credential: *const OpenMlsCredential,
+    group_id: *const u8,
+    group_id_len: usize,
+    ciphersuite: u16,
+    group: *mut *mut OpenMlsGroup,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null(), group.is_null()])?;
+        let group_id = GroupId::from_slice(input_slice(group_id, group_id_len)?);
+        let ciphersuite_name = CiphersuiteName::try_from(ciphersuite)?;
+        *group = new_group_handle(&(*credential).0, |credential_bundle| {
+            let key_package_bundle =
+                KeyPackageBundle::new(&[ciphersuite_name], credential_bundle, vec![])?;
+            Ok(ManagedGroup::new(
+                credential_bundle,
+                &group_config(),
+                group_id,
+                key_package_bundle,
+            )?)
+        })?;
+        Ok(())
+    })
+}
+
+/// Join a group with a `Welcome` message. The key package the `Welcome` was
+/// created for is taken from the `store` and removed from it once the group
+/// was joined, unless it is a last resort key package. The `ratchet_tree` is
+/// the `optional<Node> ratchet_tree<0..2^32-1>` exported by a member with
+/// [`openmls_group_export_ratchet_tree()`].
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_new_from_welcome(
+    credential: *const OpenMlsCredential,
+    store: *mut OpenMlsKeyPackageStore,
+    welcome: *const u8,
+    welcome_len: usize,
+    ratchet_tree: *const u8,
+    ratchet_tree_len: usize,
+    group: *mut *mut OpenMlsGroup,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null(), store.is_null(), group.is_null()])?;
+        let welcome = Welcome::from_tls_bytes(input_slice(welcome, welcome_len)?)?;
+        let ratchet_tree: Vec<Option<Node>> =
+            decode_input(input_slice(ratchet_tree, ratchet_tree_len)?)?;
+        let key_package_bundle = (*store)
+            .0
+            .bundle_for_welcome(&welcome)
+            .ok_or(FfiError(OPENMLS_ERROR_KEY_PACKAGE_NOT_FOUND))?;
+        let new_group = new_group_handle(&(*credential).0, |credential_bundle| {
+            Ok(ManagedGroup::new_from_welcome(
+                credential_bundle,
+                &group_config(),
+                welcome.clone(),
+                Some(ratchet_tree),
+                key_package_bundle,
+            )?)
+        })?;
+        (*store).0.remove_for_welcome(&welcome);
+        *group = new_group;
+        Ok(())
+    })
+}
+
+/// Export the ratchet tree of the group as
+/// `optional<Node> ratchet_tree<0..2^32-1>`. New members need it to join.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_export_ratchet_tree(
+    group: *const OpenMlsGroup,
+    ratchet_tree: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), ratchet_tree.is_null()])?;
+        *ratchet_tree = (*group)
+            .group
+            .export_ratchet_tree()
+            .encode_detached()?
+            .into();
+        Ok(())
+    })
+}
+
+/// Return the members of the group as a list of their leaf index and
+/// identity, encoded as `Member members<0..2^32-1>` with
+/// `struct { uint32 index; opaque identity<0..2^32-1>; } Member`.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_members(
+    group: *const OpenMlsGroup,
+    members: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), members.is_null()])?;
+        let group_members: Vec<(u32, Vec<u8>)> = (*group)
+            .group
+            .members()
+            .into_iter()
+            .map(|(index, credential)| (index.as_u32(), credential.identity().clone()))
+            .collect();
+        *members = group_members.encode_detached()?.into();
+        Ok(())
+    })
+}
+
+/// Add members to the group. The `key_packages` are encoded as
+/// `KeyPackage key_packages<0..2^32-1>`.
+///
+/// Returns the commit as `MLSMessage messages<0..2^32-1>` that has to be sent
+/// to the group and passed to [`openmls_group_process_messages()`], and the
+/// `Welcome` for the new members.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_add_members(
+    group: *mut OpenMlsGroup,
+    key_packages: *const u8,
+    key_packages_len: usize,
+    messages: *mut OpenMlsBuffer,
+    welcome: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), messages.is_null(), welcome.is_null()])?;
+        let key_packages: Vec<KeyPackage> =
+            decode_input(input_slice(key_packages, key_packages_len)?)?;
+        let (mls_messages, new_welcome) = (*group).group.add_members(&key_packages)?;
+        let encoded_messages = mls_messages.encode_detached()?;
+        let encoded_welcome = new_welcome.to_tls_bytes()?;
+        *messages = encoded_messages.into();
+        *welcome = encoded_welcome.into();
+        Ok(())
+    })
+}
+
+/// Remove the members at the leaf `indices` from the group.
+///
+/// Returns the commit as `MLSMessage messages<0..2^32-1>` that has to be sent
+/// to the group and passed to [`openmls_group_process_messages()`]. If the
+/// commit also adds members, a `Welcome` is returned, otherwise the `welcome`
+/// buffer is empty.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_remove_members(
+    group: *mut OpenMlsGroup,
+    indices: *const u32,
+    indices_len: usize,
+    messages: *mut OpenMlsBuffer,
+    welcome: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), messages.is_null(), welcome.is_null()])?;
+        let members: Vec<usize> = input_slice(indices, indices_len)?
+            .iter()
+            .map(|&index| index as usize)
+            .collect();
+        let (mls_messages, new_welcome) = (*group).group.remove_members(&members)?;
+        let encoded_messages = mls_messages.encode_detached()?;
+        let encoded_welcome = match new_welcome {
+            Some(new_welcome) => new_welcome.to_tls_bytes()?,
+            None => vec![],
+        };
+        *messages = encoded_messages.into();
+        *welcome = encoded_welcome.into();
+        Ok(())
+    })
+}
+
+/// Encrypt an application `message` for the group and return the
+/// `MLSMessage`.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_create_message(
+    group: *mut OpenMlsGroup,
+    message: *const u8,
+    message_len: usize,
+    mls_message: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), mls_message.is_null()])?;
+        let message = input_slice(message, message_len)?;
+        *mls_message = (*group)
+            .group
+            .create_message(message)?
+            .to_tls_bytes()?
+            .into();
+        Ok(())
+    })
+}
+
+/// Process the `messages`, encoded as `MLSMessage messages<0..2^32-1>`.
+///
+/// Returns the events that occurred as `Event events<0..2^32-1>`. See the
+/// `events` module for their encoding.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_process_messages(
+    group: *mut OpenMlsGroup,
+    messages: *const u8,
+    messages_len: usize,
+    events: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), events.is_null()])?;
+        let mls_messages: Vec<MLSMessage> = decode_input(input_slice(messages, messages_len)?)?;
+        // Drop events left over from an earlier call that failed.
+        events::take_events();
+        let result = (*group).group.process_messages(mls_messages);
+        let group_events = events::take_events();
+        result?;
+        *events = group_events.encode_detached()?.into();
+        Ok(())
+    })
+}
+
+/// Persist the state of the group.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_save(
+    group: *const OpenMlsGroup,
+    state: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), state.is_null()])?;
+        let mut serialized = vec![];
+        (*group).group.save(&mut serialized)?;
+        *state = serialized.into();
+        Ok(())
+    })
+}
+
+/// Load a group persisted with [`openmls_group_save()`]. The `credential`
+/// must be the one the group was created or joined with.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_load(
+    credential: *const OpenMlsCredential,
+    state: *const u8,
+    state_len: usize,
+    group: *mut *mut OpenMlsGroup,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null(), group.is_null()])?;
+        let state = input_slice(state, state_len)?;
+        *group = new_group_handle(&(*credential).0, |credential_bundle| {
+            Ok(ManagedGroup::load(
+                state,
+                credential_bundle,
+                group_config().callbacks(),
+            )?)
+        })?;
+        Ok(())
+    })
+}
+
+/// Return the current epoch of the group. Pass the epoch of the last saved
+/// state as `min_epoch` to [`openmls_group_load_encrypted()`] to detect a
+/// rollback.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_epoch(group: *const OpenMlsGroup, epoch: *mut u64) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), epoch.is_null()])?;
+        *epoch = (*group).group.epoch();
+        Ok(())
+    })
+}
+
+/// Persist the state of the group sealed with the `storage_key` of
+/// `STORAGE_KEY_LENGTH` (32) bytes. The group ID and the epoch are
+/// authenticated along with the state.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_save_encrypted(
+    group: *const OpenMlsGroup,
+    storage_key: *const u8,
+    storage_key_len: usize,
+    state: *mut OpenMlsBuffer,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[group.is_null(), state.is_null()])?;
+        let storage_key = StorageKey::from_slice(input_slice(storage_key, storage_key_len)?)?;
+        let mut sealed = vec![];
+        (*group).group.save_encrypted(&mut sealed, &storage_key)?;
+        *state = sealed.into();
+        Ok(())
+    })
+}
+
+/// Load a group persisted with [`openmls_group_save_encrypted()`]. The
+/// `credential` must be the one the group was created or joined with.
+///
+/// The state must belong to the group with the `group_id` and must not be
+/// older than `min_epoch`, otherwise the `StorageGroupIdMismatch` or
+/// `StorageEpochRollback` error code is returned.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn openmls_group_load_encrypted(
+    credential: *const OpenMlsCredential,
+    storage_key: *const u8,
+    storage_key_len: usize,
+    group_id: *const u8,
+    group_id_len: usize,
+    min_epoch: u64,
+    state: *const u8,
+    state_len: usize,
+    group: *mut *mut OpenMlsGroup,
+) -> u16 {
+    ffi_call(|| {
+        check_not_null(&[credential.is_null(), group.is_null()])?;
+        let storage_key = StorageKey::from_slice(input_slice(storage_key, storage_key_len)?)?;
+        let group_id = GroupId::from_slice(input_slice(group_id, group_id_len)?);
+        let state = input_slice(state, state_len)?;
+        *group = new_group_handle(&(*credential).0, |credential_bundle| {
+            Ok(ManagedGroup::load_encrypted(
+                state,
+                &storage_key,
+                &group_id,
+                min_epoch,
+                credential_bundle,
+                group_config().callbacks(),
+            )?)
+        })?;
+        Ok(())
+    })
+}
+
+/// Release a group.
+#[no_mangle]
+pub unsafe extern "C" fn openmls_group_free(group: *mut OpenMlsGroup) {
+    if !group.is_null() {
+        drop(Box::from_raw(group));
+    }
+}
@@ -0,0 +1,417 @@
+use openmls::prelude::*;
+use openmls_ffi::events::Event;
+use openmls_ffi::*;
+
+use std::ptr;
+
+const CIPHERSUITE: u16 = CiphersuiteName::MLS10_128_DHKEMX25519_AES128GCM_SHA256_Ed25519 as u16;
+
+fn empty_buffer() -> OpenMlsBuffer {
+    OpenMlsBuffer {
+        data: ptr::null_mut(),
+        len: 0,
+    }
+}
+
+fn decode<T: Codec>(bytes: &[u8]) -> T {
+    let mut cursor = Cursor::new(bytes);
+    let value = T::decode(&mut cursor).unwrap();
+    assert!(!cursor.has_more());
+    value
+}
+
+/// Copy the content of the `buffer` and release it.
+fn take_buffer(buffer: OpenMlsBuffer) -> Vec<u8> {
+    let bytes = if buffer.data.is_null() {
+        vec![]
+    } else {
+        unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) }.to_vec()
+    };
+    unsafe { openmls_buffer_free(buffer) };
+    bytes
+}
+
+fn new_credential(identity: &[u8]) -> *mut OpenMlsCredential {
+    let mut credential = ptr::null_mut();
+    let status = unsafe {
+        openmls_credential_new(
+            identity.as_ptr(),
+            identity.len(),
+            CIPHERSUITE,
+            &mut credential,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    credential
+}
+
+fn process_messages(group: *mut OpenMlsGroup, messages: &[u8]) -> Vec<Event> {
+    let mut events = empty_buffer();
+    let status = unsafe {
+        openmls_group_process_messages(group, messages.as_ptr(), messages.len(), &mut events)
+    };
+    assert_eq!(status, OPENMLS_OK);
+    decode(&take_buffer(events))
+}
+
+fn members(group: *const OpenMlsGroup) -> Vec<(u32, Vec<u8>)> {
+    let mut members = empty_buffer();
+    assert_eq!(
+        unsafe { openmls_group_members(group, &mut members) },
+        OPENMLS_OK
+    );
+    decode(&take_buffer(members))
+}
+
+/// Alice creates a group, adds Bob and sends him a message through the C API.
+#[test]
+fn ffi_managed_group() {
+    let alice_credential = new_credential(b"Alice");
+    let bob_credential = new_credential(b"Bob");
+
+    // === Bob generates key packages ===
+    let mut bob_store = ptr::null_mut();
+    assert_eq!(
+        unsafe { openmls_key_package_store_new(&mut bob_store) },
+        OPENMLS_OK
+    );
+    let mut key_packages = empty_buffer();
+    let status = unsafe {
+        openmls_key_package_store_generate(
+            bob_store,
+            bob_credential,
+            CIPHERSUITE,
+            2,
+            &mut key_packages,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let key_packages = take_buffer(key_packages);
+    let bob_key_packages: Vec<KeyPackage> = decode(&key_packages);
+    assert_eq!(bob_key_packages.len(), 2);
+    let bob_key_package = vec![bob_key_packages[0].clone()].encode_detached().unwrap();
+
+    // === Alice creates a group and adds Bob ===
+    let group_id = b"Test group";
+    let mut alice_group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_new(
+            alice_credential,
+            group_id.as_ptr(),
+            group_id.len(),
+            CIPHERSUITE,
+            &mut alice_group,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+
+    let mut messages = empty_buffer();
+    let mut welcome = empty_buffer();
+    let status = unsafe {
+        openmls_group_add_members(
+            alice_group,
+            bob_key_package.as_ptr(),
+            bob_key_package.len(),
+            &mut messages,
+            &mut welcome,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let messages = take_buffer(messages);
+    let welcome = take_buffer(welcome);
+
+    let events = process_messages(alice_group, &messages);
+    assert!(events.contains(&Event::MemberAdded {
+        sender: b"Alice".to_vec(),
+        added_member: b"Bob".to_vec(),
+    }));
+
+    // === Bob joins the group ===
+    let mut ratchet_tree = empty_buffer();
+    assert_eq!(
+        unsafe { openmls_group_export_ratchet_tree(alice_group, &mut ratchet_tree) },
+        OPENMLS_OK
+    );
+    let ratchet_tree = take_buffer(ratchet_tree);
+    let mut bob_group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_new_from_welcome(
+            bob_credential,
+            bob_store,
+            welcome.as_ptr(),
+            welcome.len(),
+            ratchet_tree.as_ptr(),
+            ratchet_tree.len(),
+            &mut bob_group,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    assert_eq!(members(alice_group), members(bob_group));
+    assert_eq!(
+        members(bob_group),
+        vec![(0, b"Alice".to_vec()), (1, b"Bob".to_vec())]
+    );
+
+    // The key package was taken from the store, so the Welcome can't be used
+    // a second time.
+    let mut unused_group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_new_from_welcome(
+            bob_credential,
+            bob_store,
+            welcome.as_ptr(),
+            welcome.len(),
+            ratchet_tree.as_ptr(),
+            ratchet_tree.len(),
+            &mut unused_group,
+        )
+    };
+    assert_eq!(status, OPENMLS_ERROR_KEY_PACKAGE_NOT_FOUND);
+    assert!(unused_group.is_null());
+
+    // === Alice sends a message to Bob ===
+    let message = b"Hi, I'm Alice!";
+    let mut mls_message = empty_buffer();
+    let status = unsafe {
+        openmls_group_create_message(
+            alice_group,
+            message.as_ptr(),
+            message.len(),
+            &mut mls_message,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let mls_message = MLSMessage::from_tls_bytes(&take_buffer(mls_message)).unwrap();
+    let mls_messages = vec![mls_message].encode_detached().unwrap();
+
+    // === Bob saves and loads his group before processing the message ===
+    let mut state = empty_buffer();
+    assert_eq!(
+        unsafe { openmls_group_save(bob_group, &mut state) },
+        OPENMLS_OK
+    );
+    let state = take_buffer(state);
+    unsafe { openmls_group_free(bob_group) };
+    let mut bob_group = ptr::null_mut();
+    let status =
+        unsafe { openmls_group_load(bob_credential, state.as_ptr(), state.len(), &mut bob_group) };
+    assert_eq!(status, OPENMLS_OK);
+
+    let events = process_messages(bob_group, &mls_messages);
+    assert_eq!(
+        events,
+        vec![Event::ApplicationMessage {
+            sender: b"Alice".to_vec(),
+            aad: vec![],
+            message: message.to_vec(),
+        }]
+    );
+
+    // === Alice removes Bob ===
+    let indices = [1u32];
+    let mut messages = empty_buffer();
+    let mut welcome = empty_buffer();
+    let status = unsafe {
+        openmls_group_remove_members(
+            alice_group,
+            indices.as_ptr(),
+            indices.len(),
+            &mut messages,
+            &mut welcome,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let messages = take_buffer(messages);
+    assert!(take_buffer(welcome).is_empty());
+
+    let events = process_messages(bob_group, &messages);
+    assert!(events.contains(&Event::MemberRemoved {
+        removal_type: 2,
+        leaver: vec![],
+        remover: b"Alice".to_vec(),
+    }));
+
+    unsafe {
+        openmls_group_free(alice_group);
+        openmls_group_free(bob_group);
+        openmls_key_package_store_free(bob_store);
+        openmls_credential_free(alice_credential);
+        openmls_credential_free(bob_credential);
+    }
+}
+
+/// Invalid arguments are reported through the returned status.
+#[test]
+fn ffi_errors() {
+    // Null pointers
+    let mut credential = ptr::null_mut();
+    let status = unsafe { openmls_credential_new(ptr::null(), 5, CIPHERSUITE, &mut credential) };
+    assert_eq!(status, OPENMLS_ERROR_NULL_POINTER);
+    let status =
+        unsafe { openmls_credential_new(b"Alice".as_ptr(), 5, CIPHERSUITE, ptr::null_mut()) };
+    assert_eq!(status, OPENMLS_ERROR_NULL_POINTER);
+
+    // Unknown ciphersuite
+    let status = unsafe { openmls_credential_new(b"Alice".as_ptr(), 5, 0xffff, &mut credential) };
+    assert_eq!(status, u16::from(ErrorCode::CodecDecodingError));
+    assert!(credential.is_null());
+
+    // Credentials round trip
+    let credential = new_credential(b"Alice");
+    let mut bytes = empty_buffer();
+    assert_eq!(
+        unsafe { openmls_credential_to_bytes(credential, &mut bytes) },
+        OPENMLS_OK
+    );
+    let mut bytes = take_buffer(bytes);
+    let mut loaded_credential = ptr::null_mut();
+    let status = unsafe {
+        openmls_credential_from_bytes(bytes.as_ptr(), bytes.len(), &mut loaded_credential)
+    };
+    assert_eq!(status, OPENMLS_OK);
+    unsafe { openmls_credential_free(loaded_credential) };
+
+    // Trailing bytes
+    bytes.push(0);
+    let mut loaded_credential = ptr::null_mut();
+    let status = unsafe {
+        openmls_credential_from_bytes(bytes.as_ptr(), bytes.len(), &mut loaded_credential)
+    };
+    assert_ne!(status, OPENMLS_OK);
+    assert!(loaded_credential.is_null());
+
+    // Malformed messages
+    let group_id = b"Test group";
+    let mut group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_new(
+            credential,
+            group_id.as_ptr(),
+            group_id.len(),
+            CIPHERSUITE,
+            &mut group,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let mut events = empty_buffer();
+    let status = unsafe { openmls_group_process_messages(group, [1u8].as_ptr(), 1, &mut events) };
+    assert_eq!(status, u16::from(ErrorCode::CodecDecodingError));
+    assert!(events.data.is_null());
+
+    unsafe {
+        openmls_group_free(group);
+        openmls_credential_free(credential);
+    }
+}
+
+/// Key package stores and groups are persisted, groups optionally sealed with
+/// a storage key, and groups keep their credential alive.
+#[test]
+fn ffi_persistence() {
+    let credential = new_credential(b"Alice");
+
+    // === Key package stores ===
+    let mut store = ptr::null_mut();
+    assert_eq!(
+        unsafe { openmls_key_package_store_new(&mut store) },
+        OPENMLS_OK
+    );
+    let mut key_packages = empty_buffer();
+    let status = unsafe {
+        openmls_key_package_store_generate(store, credential, CIPHERSUITE, 1, &mut key_packages)
+    };
+    assert_eq!(status, OPENMLS_OK);
+    take_buffer(key_packages);
+    let mut state = empty_buffer();
+    assert_eq!(
+        unsafe { openmls_key_package_store_save(store, &mut state) },
+        OPENMLS_OK
+    );
+    let state = take_buffer(state);
+    unsafe { openmls_key_package_store_free(store) };
+
+    let mut store = ptr::null_mut();
+    let status = unsafe { openmls_key_package_store_load(state.as_ptr(), state.len(), &mut store) };
+    assert_eq!(status, OPENMLS_OK);
+    let mut reloaded_state = empty_buffer();
+    assert_eq!(
+        unsafe { openmls_key_package_store_save(store, &mut reloaded_state) },
+        OPENMLS_OK
+    );
+    assert_eq!(take_buffer(reloaded_state), state);
+
+    // === Sealed group states ===
+    let group_id = b"Test group";
+    let mut group = ptr::null_mut();
+    let status = unsafe {
+        openmls_group_new(
+            credential,
+            group_id.as_ptr(),
+            group_id.len(),
+            CIPHERSUITE,
+            &mut group,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let mut epoch = u64::MAX;
+    assert_eq!(
+        unsafe { openmls_group_epoch(group, &mut epoch) },
+        OPENMLS_OK
+    );
+    assert_eq!(epoch, 0);
+
+    let storage_key = [7u8; STORAGE_KEY_LENGTH];
+    let mut sealed_state = empty_buffer();
+    let status = unsafe {
+        openmls_group_save_encrypted(
+            group,
+            storage_key.as_ptr(),
+            storage_key.len(),
+            &mut sealed_state,
+        )
+    };
+    assert_eq!(status, OPENMLS_OK);
+    let sealed_state = take_buffer(sealed_state);
+    unsafe { openmls_group_free(group) };
+
+    let load_encrypted = |storage_key: &[u8], group_id: &[u8], min_epoch: u64| {
+        let mut group = ptr::null_mut();
+        let status = unsafe {
+            openmls_group_load_encrypted(
+                credential,
+                storage_key.as_ptr(),
+                storage_key.len(),
+                group_id.as_ptr(),
+                group_id.len(),
+                min_epoch,
+                sealed_state.as_ptr(),
+                sealed_state.len(),
+                &mut group,
+            )
+        };
+        (status, group)
+    };
+    let (status, _) = load_encrypted(&storage_key[1..], group_id, epoch);
+    assert_eq!(status, u16::from(ErrorCode::StorageInvalidKeyLength));
+    let (status, _) = load_encrypted(&storage_key, b"Other group", epoch);
+    assert_eq!(status, u16::from(ErrorCode::StorageGroupIdMismatch));
+    let (status, _) = load_encrypted(&storage_key, group_id, epoch + 1);
+    assert_eq!(status, u16::from(ErrorCode::StorageEpochRollback));
+    let (status, group) = load_encrypted(&storage_key, group_id, epoch);
+    assert_eq!(status, OPENMLS_OK);
+
+    // === The group keeps its credential ===
+    unsafe { openmls_credential_free(credential) };
+    let message = b"Hi, I'm Alice!";
+    let mut mls_message = empty_buffer();
+    let status = unsafe {
+        openmls_group_create_message(group, message.as_ptr(), message.len(), &mut mls_message)
+    };
+    assert_eq!(status, OPENMLS_OK);
+    take_buffer(mls_message);
+
+    unsafe {
+        openmls_group_free(group);
+        openmls_key_package_store_free(store);
+    }
+}
@@ -12,5 +12,6 @@ implement_error! {
     pub enum CryptoError {
         CryptoLibraryError = "Unrecoverable error in the crypto library.",
         HpkeDecryptionError = "Error while decrypting an HPKE ciphertext",
+        InvalidKey = "The key is invalid or not supported by the ciphersuite.",
     }
 }
@@ -12,6 +12,7 @@ pub(crate) use serde::{
     ser::{SerializeStruct, Serializer},
     Deserialize, Deserializer, Serialize,
 };
+use zeroize::Zeroize;
 
 // re-export for other parts of the library when we can use it
 pub(crate) use hpke::{HPKEKeyPair, HPKEPrivateKey, HPKEPublicKey};
@@ -19,13 +20,14 @@ pub(crate) use hpke::{HPKEKeyPair, HPKEPrivateKey, HPKEPublicKey};
 mod ciphersuites;
 mod codec;
 mod errors;
+mod pkcs8;
 pub(crate) mod signable;
 use ciphersuites::*;
 pub(crate) use errors::*;
 mod ser;
 
 use crate::config::{Config, ConfigError};
-use crate::group::GroupContext;
+use crate::group::ExporterError;
 use crate::schedule::ExporterSecret;
 use crate::schedule::SenderDataSecret;
 use crate::schedule::WelcomeSecret;
@@ -144,6 +146,12 @@ impl Secret {
     }
 }
 
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
 impl Default for Secret {
     fn default() -> Self {
         Secret { value: vec![] }
@@ -175,20 +183,38 @@ impl From<&[u8]> for Secret {
 impl ExporterSecret {
     /// Derive a `Secret` from the exporter secret. We return `Vec<u8>` here, so
     /// it can be used outside of OpenMLS. This function is made available for
-    /// use from the outside through [`crate::group::mls_group::export_secret`].
+    /// use from the outside through
+    /// [`MlsGroup::export_secret()`](`crate::group::MlsGroup::export_secret()`).
+    ///
+    /// ```text
+    /// MLS-Exporter(Label, Context, key_length) =
+    ///        ExpandWithLabel(Derive-Secret(exporter_secret, Label),
+    ///                          "exporter", Hash(Context), key_length)
+    /// ```
+    ///
+    /// Returns an error if the `key_length` is `0` or longer than what the
+    /// KDF of the ciphersuite can expand to.
     pub(crate) fn derive_exported_secret(
         &self,
         ciphersuite: &Ciphersuite,
         label: &str,
-        group_context: &GroupContext,
+        context: &[u8],
         key_length: usize,
-    ) -> Vec<u8> {
-        let context = &group_context.serialize();
+    ) -> Result<Vec<u8>, ExporterError> {
+        if key_length == 0 {
+            return Err(ExporterError::KeyLengthZero);
+        }
+        // The length is encoded as uint16 in the KDF label and HKDF can't
+        // expand to more than 255 blocks.
+        if key_length > u16::MAX.into() || key_length > 255 * ciphersuite.hash_length() {
+            return Err(ExporterError::KeyLengthTooLong);
+        }
         let context_hash = &ciphersuite.hash(context);
-        self.secret()
+        Ok(self
+            .secret()
             .derive_secret(ciphersuite, label)
-            .kdf_expand_label(ciphersuite, label, context_hash, key_length)
-            .value
+            .kdf_expand_label(ciphersuite, "exporter", context_hash, key_length)
+            .to_vec())
     }
 }
 
@@ -199,6 +225,12 @@ pub struct AeadKey {
     value: Vec<u8>,
 }
 
+impl Drop for AeadKey {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ReuseGuard {
@@ -298,6 +330,16 @@ impl Ciphersuite {
         self.name
     }
 
+    /// Returns true if this ciphersuite uses the same signature scheme as
+    /// `other`.
+    pub(crate) fn has_same_signature_scheme(&self, other: &Ciphersuite) -> bool {
+        matches!(
+            (self.signature, other.signature),
+            (SignatureMode::Ed25519, SignatureMode::Ed25519)
+                | (SignatureMode::P256, SignatureMode::P256)
+        )
+    }
+
     /// Get the AEAD mode
     #[cfg(test)]
     pub(crate) fn aead(&self) -> AeadMode {
@@ -326,6 +368,51 @@ impl Ciphersuite {
         })
     }
 
+    /// Create a signature key pair from the raw bytes of an existing
+    /// `private_key` and return it. The public key is derived from the
+    /// private key.
+    ///
+    /// Returns a `CryptoError::InvalidKey` error if the `private_key` isn't a
+    /// valid key of the ciphersuite's signature scheme.
+    pub(crate) fn signature_keypair_from_private_key(
+        &'static self,
+        private_key: &[u8],
+    ) -> Result<SignatureKeypair, CryptoError> {
+        let public_key = match self.signature {
+            SignatureMode::Ed25519 => {
+                let private_key =
+                    <[u8; 32]>::try_from(private_key).map_err(|_| CryptoError::InvalidKey)?;
+                eddsa_sk2pk(&private_key).to_vec()
+            }
+            SignatureMode::P256 => match ecdh_derive_base(EcdhMode::P256, private_key) {
+                Ok(public_key) => public_key,
+                Err(_) => return Err(CryptoError::InvalidKey),
+            },
+        };
+        Ok(SignatureKeypair {
+            ciphersuite: self,
+            private_key: SignaturePrivateKey {
+                value: private_key.to_vec(),
+                ciphersuite: self,
+            },
+            public_key: SignaturePublicKey {
+                value: public_key,
+                ciphersuite: self,
+            },
+        })
+    }
+
+    /// Create a signature key pair from a private key in PKCS#8 encoding and
+    /// return it. See [`Ciphersuite::signature_keypair_from_private_key()`]
+    /// for details.
+    pub(crate) fn signature_keypair_from_pkcs8(
+        &'static self,
+        pkcs8: &[u8],
+    ) -> Result<SignatureKeypair, CryptoError> {
+        let private_key = pkcs8::decode(self.signature, pkcs8)?;
+        self.signature_keypair_from_private_key(&private_key)
+    }
+
     /// Hash `payload` and return the digest.
     pub(crate) fn hash(&self, payload: &[u8]) -> Vec<u8> {
         hash(self.hash, payload)
@@ -336,6 +423,11 @@ impl Ciphersuite {
         get_digest_size(self.hash)
     }
 
+    /// Compute the HMAC of `payload` with `key` and return the tag.
+    pub(crate) fn mac(&self, key: &Secret, payload: &[u8]) -> Vec<u8> {
+        hmac(self.hmac, key.value.as_slice(), payload, None)
+    }
+
     /// HKDF extract.
     pub(crate) fn hkdf_extract(&self, salt_option: Option<&Secret>, ikm: &Secret) -> Secret {
         let salt = salt_option.unwrap_or_default();
@@ -433,7 +525,7 @@ impl AeadKey {
     pub(crate) fn from_secret(ciphersuite: &Ciphersuite, secret: Secret) -> Self {
         AeadKey {
             aead_mode: ciphersuite.aead,
-            value: secret.value,
+            value: secret.to_vec(),
         }
     }
 
@@ -451,7 +543,7 @@ impl AeadKey {
         );
         AeadKey {
             aead_mode: ciphersuite.aead,
-            value: key.value,
+            value: key.to_vec(),
         }
     }
 
@@ -469,7 +561,16 @@ impl AeadKey {
             .unwrap();
         AeadKey {
             aead_mode: ciphersuite.aead,
-            value: aead_secret.value,
+            value: aead_secret.to_vec(),
+        }
+    }
+
+    /// Create an `AeadKey` for the given AEAD mode from a byte slice. The
+    /// length of the slice has to be checked by the caller.
+    pub(crate) fn from_slice(aead_mode: AeadMode, value: &[u8]) -> Self {
+        AeadKey {
+            aead_mode,
+            value: value.to_vec(),
         }
     }
 
@@ -580,8 +681,12 @@ impl AeadNonce {
         AeadNonce { value: nonce }
     }
 
+    /// Create an `AeadNonce` from a byte array.
+    pub(crate) fn from_bytes(value: [u8; NONCE_BYTES]) -> Self {
+        AeadNonce { value }
+    }
+
     /// Get a slice to the nonce value.
-    #[cfg(test)]
     pub(crate) fn as_slice(&self) -> &[u8] {
         &self.value
     }
@@ -598,6 +703,16 @@ impl Signature {
     pub(crate) fn new_empty() -> Signature {
         Signature { value: vec![] }
     }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Signature {
+        Signature {
+            value: bytes.to_vec(),
+        }
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
 }
 
 impl SignatureKeypair {
@@ -648,6 +763,16 @@ impl SignaturePublicKey {
 }
 
 impl SignaturePrivateKey {
+    /// Get the raw bytes of the private key.
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Get the private key in PKCS#8 encoding.
+    pub(crate) fn to_pkcs8(&self) -> Vec<u8> {
+        pkcs8::encode(self.ciphersuite.signature, &self.value)
+    }
+
     /// Sign the `payload` byte slice with this signature key.
     /// Returns a `Result` with a `Signature` or a `SignatureError`.
     pub fn sign(&self, payload: &[u8]) -> Result<Signature, SignatureError> {
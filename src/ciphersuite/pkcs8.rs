@@ -0,0 +1,158 @@
+//! # PKCS#8 encoding of signature private keys
+//!
+//! A minimal DER encoding and decoding of the PKCS#8 `PrivateKeyInfo`
+//! structure (RFC 5208) for the signature schemes supported by the
+//! ciphersuites: Ed25519 keys as defined in RFC 8410 and P-256 keys as defined
+//! in RFC 5915.
+//!
+//! Only version 1 structures without attributes are written. When decoding,
+//! version 2 structures (RFC 5958) are accepted as well, and attributes as
+//! well as any embedded public key are ignored. The public key is always
+//! derived from the private key.
+
+use super::*;
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+
+/// Content of the DER encoded `AlgorithmIdentifier` for Ed25519 keys
+/// (OID 1.3.101.112).
+const ED25519_ALGORITHM: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// Content of the DER encoded `AlgorithmIdentifier` for EC keys
+/// (OID 1.2.840.10045.2.1) on the P-256 curve (OID 1.2.840.10045.3.1.7).
+const P256_ALGORITHM: &[u8] = &[
+    0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d,
+    0x03, 0x01, 0x07,
+];
+
+fn algorithm(signature_mode: SignatureMode) -> &'static [u8] {
+    match signature_mode {
+        SignatureMode::Ed25519 => ED25519_ALGORITHM,
+        SignatureMode::P256 => P256_ALGORITHM,
+    }
+}
+
+/// Append the DER element with the `tag` and `content` to the `buffer`.
+/// All elements written here are shorter than 128 bytes, so the short form
+/// of the length is sufficient.
+fn encode_element(tag: u8, content: &[u8], buffer: &mut Vec<u8>) {
+    debug_assert!(content.len() < 0x80);
+    buffer.push(tag);
+    buffer.push(content.len() as u8);
+    buffer.extend_from_slice(content);
+}
+
+/// Encode the raw `private_key` of the `signature_mode` as PKCS#8
+/// `PrivateKeyInfo`.
+pub(crate) fn encode(signature_mode: SignatureMode, private_key: &[u8]) -> Vec<u8> {
+    let mut key = vec![];
+    match signature_mode {
+        // CurvePrivateKey ::= OCTET STRING
+        SignatureMode::Ed25519 => encode_element(OCTET_STRING, private_key, &mut key),
+        // ECPrivateKey ::= SEQUENCE { version INTEGER (1), privateKey OCTET STRING, .. }
+        SignatureMode::P256 => {
+            let mut ec_private_key = vec![];
+            encode_element(INTEGER, &[1], &mut ec_private_key);
+            encode_element(OCTET_STRING, private_key, &mut ec_private_key);
+            encode_element(SEQUENCE, &ec_private_key, &mut key);
+        }
+    }
+
+    let mut private_key_info = vec![];
+    encode_element(INTEGER, &[0], &mut private_key_info);
+    encode_element(SEQUENCE, algorithm(signature_mode), &mut private_key_info);
+    encode_element(OCTET_STRING, &key, &mut private_key_info);
+
+    let mut buffer = vec![];
+    encode_element(SEQUENCE, &private_key_info, &mut buffer);
+    buffer
+}
+
+/// Decode the raw private key of the `signature_mode` from the PKCS#8
+/// `PrivateKeyInfo` in `bytes`.
+///
+/// Returns a `CryptoError::InvalidKey` error if `bytes` isn't a valid
+/// encoding or contains a key of a different signature scheme.
+pub(crate) fn decode(signature_mode: SignatureMode, bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let mut reader = DerReader::new(bytes);
+    let private_key_info = reader.read(SEQUENCE)?;
+    reader.finish()?;
+
+    let mut reader = DerReader::new(private_key_info);
+    let version = reader.read(INTEGER)?;
+    if version != [0] && version != [1] {
+        return Err(CryptoError::InvalidKey);
+    }
+    if reader.read(SEQUENCE)? != algorithm(signature_mode) {
+        return Err(CryptoError::InvalidKey);
+    }
+    let key = reader.read(OCTET_STRING)?;
+    // Attributes and the public key of version 2 structures are ignored.
+
+    let mut reader = DerReader::new(key);
+    let private_key = match signature_mode {
+        SignatureMode::Ed25519 => reader.read(OCTET_STRING)?,
+        SignatureMode::P256 => {
+            let mut ec_reader = DerReader::new(reader.read(SEQUENCE)?);
+            if ec_reader.read(INTEGER)? != [1] {
+                return Err(CryptoError::InvalidKey);
+            }
+            // The optional curve parameters and public key are ignored.
+            ec_reader.read(OCTET_STRING)?
+        }
+    };
+    reader.finish()?;
+    Ok(private_key.to_vec())
+}
+
+/// Reads DER elements from a byte slice.
+struct DerReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Read the next element, which must have the `tag`, and return its
+    /// content.
+    fn read(&mut self, tag: u8) -> Result<&'a [u8], CryptoError> {
+        let (&actual_tag, rest) = self.bytes.split_first().ok_or(CryptoError::InvalidKey)?;
+        if actual_tag != tag {
+            return Err(CryptoError::InvalidKey);
+        }
+        let (&first_length_byte, mut rest) = rest.split_first().ok_or(CryptoError::InvalidKey)?;
+        let length = if first_length_byte < 0x80 {
+            first_length_byte as usize
+        } else {
+            // Long form of the length. Keys never need more than two bytes.
+            let length_bytes = (first_length_byte & 0x7f) as usize;
+            if length_bytes == 0 || length_bytes > 2 || rest.len() < length_bytes {
+                return Err(CryptoError::InvalidKey);
+            }
+            let length = rest[..length_bytes]
+                .iter()
+                .fold(0usize, |length, &byte| (length << 8) | byte as usize);
+            rest = &rest[length_bytes..];
+            length
+        };
+        if rest.len() < length {
+            return Err(CryptoError::InvalidKey);
+        }
+        let (content, rest) = rest.split_at(length);
+        self.bytes = rest;
+        Ok(content)
+    }
+
+    /// Returns an error if there are bytes left to read.
+    fn finish(&self) -> Result<(), CryptoError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(CryptoError::InvalidKey)
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! # Clock
+//!
+//! OpenMLS needs the current time to create and validate the lifetime of key
+//! packages and to expire retained past epochs. The [`Clock`] trait makes the
+//! source of the time injectable, e.g. to validate key packages against a time
+//! provided by a trusted server on devices with a wrong system clock, or to
+//! test expiry without waiting.
+//!
+//! All times are measured in seconds since the Unix epoch
+//! (1970-01-01T00:00:00Z).
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time.
+pub trait Clock {
+    /// Returns the current time in seconds since the Unix epoch.
+    fn now(&self) -> u64;
+}
+
+/// The system clock. This is used when no other clock is provided.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("SystemTime before UNIX EPOCH!")
+            .as_secs()
+    }
+}
+
+/// A clock that always returns the same time, e.g. a time provided by a
+/// trusted server or a mock time in tests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedClock {
+    time: u64,
+}
+
+impl FixedClock {
+    /// Create a new clock that always returns `time`.
+    pub fn new(time: u64) -> Self {
+        Self { time }
+    }
+
+    /// Move the clock forward by `seconds`.
+    pub fn advance(&mut self, seconds: u64) {
+        self.time += seconds;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.time
+    }
+}
+
+/// A shared handle to a [`Clock`] that is stored in the configuration of a
+/// group. Defaults to the [`SystemClock`].
+///
+/// The clock is not persisted with the group and has to be set again after
+/// loading a group that uses a different clock.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock + Send + Sync>);
+
+impl SharedClock {
+    /// Create a new handle to the `clock`.
+    pub fn new<C: Clock + Send + Sync + 'static>(clock: C) -> Self {
+        Self(Arc::new(clock))
+    }
+}
+
+impl Clock for SharedClock {
+    fn now(&self) -> u64 {
+        self.0.now()
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl std::fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedClock")
+    }
+}
+
+impl PartialEq for SharedClock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
@@ -6,6 +6,7 @@ implement_error! {
     pub enum CodecError {
         EncodingError = "Error encoding.",
         DecodingError = "Error decoding.",
+        TrailingData = "The input contains trailing bytes after the encoded value.",
         Other = "Some other error occurred.",
     }
 }
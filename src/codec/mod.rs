@@ -5,6 +5,7 @@ pub use errors::*;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::collections::HashMap;
 use std::convert::*;
+use std::io::Write;
 
 pub enum VecSize {
     VecU8,
@@ -12,21 +13,46 @@ pub enum VecSize {
     VecU32,
     VecU64,
 }
+
+impl VecSize {
+    /// Returns the number of bytes of the length prefix.
+    pub fn length_bytes(&self) -> usize {
+        match self {
+            VecSize::VecU8 => 1,
+            VecSize::VecU16 => 2,
+            VecSize::VecU32 => 4,
+            VecSize::VecU64 => 8,
+        }
+    }
+
+    /// Returns the maximum length of a vector with this length prefix.
+    fn max_length(&self) -> usize {
+        match self {
+            VecSize::VecU8 => u8::max_value() as usize,
+            VecSize::VecU16 => u16::max_value() as usize,
+            VecSize::VecU32 => u32::max_value() as usize,
+            VecSize::VecU64 => usize::max_value(),
+        }
+    }
+}
+
+/// A cursor over a byte slice. The cursor borrows the slice, so decoding
+/// doesn't copy the input.
 #[derive(Debug)]
-pub struct Cursor {
-    buffer: Vec<u8>,
+pub struct Cursor<'a> {
+    buffer: &'a [u8],
     position: usize,
 }
 
-impl<'a> Cursor {
-    pub fn new(bytes: &[u8]) -> Cursor {
+impl<'a> Cursor<'a> {
+    pub fn new(bytes: &'a [u8]) -> Cursor<'a> {
         Cursor {
-            buffer: bytes.to_vec(),
+            buffer: bytes,
             position: 0,
         }
     }
 
-    pub fn consume(&mut self, length: usize) -> Result<&[u8], CodecError> {
+    pub fn consume(&mut self, length: usize) -> Result<&'a [u8], CodecError> {
         let unread_bytes = self.buffer.len() - self.position;
         if unread_bytes < length {
             return Err(CodecError::DecodingError);
@@ -37,7 +63,7 @@ impl<'a> Cursor {
         Ok(&self.buffer[position..position + length])
     }
 
-    pub fn sub_cursor(&mut self, length: usize) -> Result<Cursor, CodecError> {
+    pub fn sub_cursor(&mut self, length: usize) -> Result<Cursor<'a>, CodecError> {
         self.consume(length).map(|buffer| Cursor::new(buffer))
     }
 
@@ -66,6 +92,75 @@ pub trait Codec: Sized {
     }
 }
 
+/// Encoding of messages that can be large, such as `MLSPlaintext`,
+/// `MLSCiphertext` and `Welcome`.
+///
+/// The length of the encoding can be computed up front, so buffers can be
+/// sized exactly, and the encoding can be written to any `Write` without
+/// copying large payloads into an intermediate buffer.
+pub trait StreamingCodec: Codec {
+    /// Returns the exact length of the encoding.
+    fn encoded_len(&self) -> Result<usize, CodecError>;
+
+    /// Encodes into the `writer`.
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), CodecError>;
+
+    /// Encodes into a buffer that is allocated with the exact length of the
+    /// encoding.
+    fn encode_exact(&self) -> Result<Vec<u8>, CodecError> {
+        let mut buffer = Vec::with_capacity(self.encoded_len()?);
+        self.encode_to(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// Stable import and export of the public wire structs, such as `KeyPackage`,
+/// `Welcome`, `Credential` and `MLSMessage`, in the TLS presentation language
+/// encoding defined by the MLS specification.
+///
+/// Unlike the serde serialization, which is meant for persisting state and
+/// includes internal fields, this is the encoding that is sent over the wire.
+/// Decoding is strict about the framing: the input must contain exactly one
+/// encoded value. Beyond that, decoding only checks what the types below list;
+/// everything else is checked when the value is used, e.g. when a `Welcome` is
+/// processed or a `KeyPackage` is added to a group.
+///
+///  - `KeyPackage`: the ciphersuite and protocol version are supported, the
+///    mandatory extensions are present, the user ID belongs to the credential,
+///    the lifetime doesn't exceed the maximum lifetime and the signature is
+///    valid. Whether the lifetime is valid at the current time is *not*
+///    checked, since the current time isn't known here; use
+///    `KeyPackage::verify_with_clock()` for that.
+///  - `Credential`: only the credential type is checked; there is nothing to
+///    validate for basic credentials.
+///  - `Welcome`: the ciphersuite and protocol version are supported.
+///  - `GroupInfo`: nothing. The signature can only be verified with the
+///    signer's credential from the group's ratchet tree.
+///  - `MLSPlaintext` and `MLSCiphertext`: nothing. The signature, membership
+///    tag and encryption are checked when the message is processed by the
+///    group.
+///  - `MLSMessage`: the checks of the contained type.
+pub trait TlsSerialization: Codec {
+    /// Encodes the value.
+    fn to_tls_bytes(&self) -> Result<Vec<u8>, CodecError> {
+        self.encode_detached()
+    }
+
+    /// Decodes a value from `bytes`.
+    ///
+    /// Returns a `CodecError::TrailingData` error if `bytes` contains more
+    /// than the encoded value, and a `CodecError::DecodingError` if the value
+    /// can't be decoded or fails the checks listed on the trait.
+    fn from_tls_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut cursor = Cursor::new(bytes);
+        let value = Self::decode(&mut cursor)?;
+        if cursor.has_more() {
+            return Err(CodecError::TrailingData);
+        }
+        Ok(value)
+    }
+}
+
 impl Codec for u8 {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         buffer.push(*self);
@@ -296,6 +391,68 @@ pub fn decode_vec<T: Codec>(vec_size: VecSize, cursor: &mut Cursor) -> Result<Ve
     Ok(result)
 }
 
+/// Encodes a byte slice with a length prefix. Unlike `encode_vec()`, the bytes
+/// are copied into the buffer at once.
+pub fn encode_bytes(
+    vec_size: VecSize,
+    buffer: &mut Vec<u8>,
+    bytes: &[u8],
+) -> Result<(), CodecError> {
+    encode_length(&vec_size, buffer, bytes.len())?;
+    buffer.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Writes a byte slice with a length prefix into the `writer`.
+pub fn write_bytes<W: Write>(
+    vec_size: VecSize,
+    writer: &mut W,
+    bytes: &[u8],
+) -> Result<(), CodecError> {
+    let mut length_prefix = Vec::with_capacity(vec_size.length_bytes());
+    encode_length(&vec_size, &mut length_prefix, bytes.len())?;
+    writer
+        .write_all(&length_prefix)
+        .and_then(|_| writer.write_all(bytes))
+        .map_err(|_| CodecError::EncodingError)
+}
+
+/// Decodes a byte slice with a length prefix without copying it.
+pub fn decode_bytes<'a>(
+    vec_size: VecSize,
+    cursor: &mut Cursor<'a>,
+) -> Result<&'a [u8], CodecError> {
+    let length = match vec_size {
+        VecSize::VecU8 => usize::from(u8::decode(cursor)?),
+        VecSize::VecU16 => usize::from(u16::decode(cursor)?),
+        VecSize::VecU32 => u32::decode(cursor)? as usize,
+        VecSize::VecU64 => u64::decode(cursor)? as usize,
+    };
+    cursor.consume(length)
+}
+
+/// Returns the length of a byte slice of `length` bytes encoded with a length
+/// prefix.
+pub fn encoded_bytes_len(vec_size: VecSize, length: usize) -> usize {
+    vec_size.length_bytes() + length
+}
+
+fn encode_length(
+    vec_size: &VecSize,
+    buffer: &mut Vec<u8>,
+    length: usize,
+) -> Result<(), CodecError> {
+    if length > vec_size.max_length() {
+        return Err(CodecError::EncodingError);
+    }
+    match vec_size {
+        VecSize::VecU8 => (length as u8).encode(buffer),
+        VecSize::VecU16 => (length as u16).encode(buffer),
+        VecSize::VecU32 => (length as u32).encode(buffer),
+        VecSize::VecU64 => (length as u64).encode(buffer),
+    }
+}
+
 #[test]
 fn test_cursor() {
     let v = vec![1, 2, 3];
@@ -305,6 +462,31 @@ fn test_cursor() {
     assert!(cursor.consume(1).is_err());
 }
 
+#[test]
+fn test_bytes() {
+    let v: Vec<u8> = vec![1, 2, 3];
+    let mut buffer = vec![];
+    encode_bytes(VecSize::VecU16, &mut buffer, &v).unwrap();
+    assert_eq!(buffer, vec![0u8, 3u8, 1u8, 2u8, 3u8]);
+    assert_eq!(buffer.len(), encoded_bytes_len(VecSize::VecU16, v.len()));
+
+    // The encoding is the same as the one of `encode_vec()`
+    let mut vec_buffer = vec![];
+    encode_vec(VecSize::VecU16, &mut vec_buffer, &v).unwrap();
+    assert_eq!(buffer, vec_buffer);
+
+    let mut written = vec![];
+    write_bytes(VecSize::VecU16, &mut written, &v).unwrap();
+    assert_eq!(buffer, written);
+
+    let cursor = &mut Cursor::new(&buffer);
+    assert_eq!(decode_bytes(VecSize::VecU16, cursor).unwrap(), &v[..]);
+    assert!(cursor.is_empty());
+
+    let too_long = vec![0u8; 256];
+    assert!(encode_bytes(VecSize::VecU8, &mut vec![], &too_long).is_err());
+}
+
 #[test]
 fn test_primitives() {
     let mut buffer = vec![];
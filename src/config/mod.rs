@@ -19,6 +19,10 @@ const DEFAULT_KEY_PACKAGE_LIFETIME: u64 = 60 * 60 * 24 * 28 * 3; // in Seconds
 /// of a `KeyPackage` is extended into the past to allow for skewed clocks. The
 /// value is in seconds and amounts to 1h.
 const DEFAULT_KEY_PACKAGE_LIFETIME_MARGIN: u64 = 60 * 60; // in Seconds
+/// This value is used as the maximum total lifetime of a `KeyPackage` if no
+/// maximum is configured. `KeyPackage`s with a longer lifetime are rejected.
+/// The value is in seconds and amounts to 1 year.
+const DEFAULT_MAX_KEY_PACKAGE_LIFETIME: u64 = 60 * 60 * 24 * 365; // in Seconds
 
 lazy_static! {
      static ref CONFIG: Config = {
@@ -40,6 +44,7 @@ lazy_static! {
             let constants = Constants {
                 default_key_package_lifetime: DEFAULT_KEY_PACKAGE_LIFETIME,
                 key_package_lifetime_margin: DEFAULT_KEY_PACKAGE_LIFETIME_MARGIN,
+                max_key_package_lifetime: DEFAULT_MAX_KEY_PACKAGE_LIFETIME,
             };
             let config = PersistentConfig {
                 protocol_versions: vec![ProtocolVersion::Mls10],
@@ -47,7 +52,7 @@ lazy_static! {
                     Ciphersuite::new(CiphersuiteName::MLS10_128_DHKEMX25519_AES128GCM_SHA256_Ed25519).unwrap(),
                     Ciphersuite::new(CiphersuiteName::MLS10_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519).unwrap(),
                     Ciphersuite::new(CiphersuiteName::MLS10_128_DHKEMP256_AES128GCM_SHA256_P256).unwrap()],
-                    extensions: vec![ExtensionType::Capabilities, ExtensionType::Lifetime, ExtensionType::KeyID],
+                    extensions: vec![ExtensionType::Capabilities, ExtensionType::Lifetime, ExtensionType::KeyID, ExtensionType::LastResort],
                 constants,
             };
             config.into()
@@ -63,6 +68,13 @@ struct Constants {
     /// The amount of time (in seconds) the lifetime of a `KeyPackage` is
     /// extended into the past to allow for skewed clocks.
     key_package_lifetime_margin: u64, // in Seconds
+    /// The maximum total lifetime of a key package in seconds.
+    #[serde(default = "default_max_key_package_lifetime")]
+    max_key_package_lifetime: u64, // in Seconds
+}
+
+fn default_max_key_package_lifetime() -> u64 {
+    DEFAULT_MAX_KEY_PACKAGE_LIFETIME
 }
 
 /// The configuration we use for the library (`Config`) is not exactly the same
@@ -141,6 +153,12 @@ impl Config {
     pub fn key_package_lifetime_margin() -> u64 {
         CONFIG.constants.key_package_lifetime_margin
     }
+
+    /// Get the maximum total lifetime (in seconds) of a `KeyPackage`, i.e. the
+    /// maximum time between its not before and not after time.
+    pub fn max_key_package_lifetime() -> u64 {
+        CONFIG.constants.max_key_package_lifetime
+    }
 }
 
 /// # Protocol Version
@@ -1,14 +1,18 @@
 use crate::ciphersuite::*;
+use crate::codec::CodecError;
 use crate::config::ConfigError;
 
 implement_error! {
     pub enum CredentialError {
         Simple {
             UnsupportedCredentialType = "Unsupported credential type",
+            UnsupportedVersion = "Unsupported version of the credential bundle serialization.",
+            KeyMismatch = "The private key doesn't match the public key of the credential.",
         }
         Complex {
             ConfigError(ConfigError) = "See `ConfigError` for details.",
             CryptoError(CryptoError) = "See `CryptoError` for details.",
+            CodecError(CodecError) = "See `CodecError` for details.",
         }
     }
 }
@@ -136,6 +136,8 @@ impl Codec for Credential {
     }
 }
 
+impl TlsSerialization for Credential {}
+
 // TODO: Drop ciphersuite
 #[derive(Debug, Clone)]
 pub struct BasicCredential {
@@ -190,6 +192,9 @@ fn test_protocol_version() {
     assert_eq!(default_e[0], 1);
 }
 
+/// The version of the serialization written by [`CredentialBundle::to_bytes()`].
+const CREDENTIAL_BUNDLE_VERSION: u8 = 1;
+
 /// This struct contains a credential and the corresponding private key.
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -208,11 +213,56 @@ impl CredentialBundle {
         ciphersuite_name: CiphersuiteName,
     ) -> Result<Self, CredentialError> {
         let ciphersuite = Config::ciphersuite(ciphersuite_name)?;
-        let (private_key, public_key) = ciphersuite.new_signature_keypair()?.into_tuple();
+        let keypair = ciphersuite.new_signature_keypair()?;
+        Self::from_signature_keypair(identity, credential_type, ciphersuite, keypair)
+    }
+
+    /// Create a new `CredentialBundle` of the given credential type for the
+    /// given identity and ciphersuite from the raw bytes of an existing
+    /// `private_key`, e.g. a 32 byte Ed25519 or P-256 private key. The public
+    /// key is derived from the private key.
+    ///
+    /// Returns a `CredentialError::CryptoError` if the `private_key` isn't a
+    /// valid key of the ciphersuite's signature scheme.
+    pub fn from_private_key(
+        identity: Vec<u8>,
+        credential_type: CredentialType,
+        ciphersuite_name: CiphersuiteName,
+        private_key: &[u8],
+    ) -> Result<Self, CredentialError> {
+        let ciphersuite = Config::ciphersuite(ciphersuite_name)?;
+        let keypair = ciphersuite.signature_keypair_from_private_key(private_key)?;
+        Self::from_signature_keypair(identity, credential_type, ciphersuite, keypair)
+    }
+
+    /// Create a new `CredentialBundle` of the given credential type for the
+    /// given identity and ciphersuite from an existing private key in PKCS#8
+    /// encoding (DER). The public key is derived from the private key.
+    ///
+    /// Returns a `CredentialError::CryptoError` if `pkcs8` isn't a valid
+    /// encoding of a key of the ciphersuite's signature scheme.
+    pub fn from_pkcs8(
+        identity: Vec<u8>,
+        credential_type: CredentialType,
+        ciphersuite_name: CiphersuiteName,
+        pkcs8: &[u8],
+    ) -> Result<Self, CredentialError> {
+        let ciphersuite = Config::ciphersuite(ciphersuite_name)?;
+        let keypair = ciphersuite.signature_keypair_from_pkcs8(pkcs8)?;
+        Self::from_signature_keypair(identity, credential_type, ciphersuite, keypair)
+    }
+
+    fn from_signature_keypair(
+        identity: Vec<u8>,
+        credential_type: CredentialType,
+        ciphersuite: &'static Ciphersuite,
+        keypair: SignatureKeypair,
+    ) -> Result<Self, CredentialError> {
+        let (private_key, public_key) = keypair.into_tuple();
         let mls_credential = match credential_type {
             CredentialType::Basic => BasicCredential {
                 identity,
-                ciphersuite: Config::ciphersuite(ciphersuite_name)?,
+                ciphersuite,
                 public_key,
             },
             _ => return Err(CredentialError::UnsupportedCredentialType),
@@ -227,6 +277,67 @@ impl CredentialBundle {
         })
     }
 
+    /// Export the raw bytes of the private key.
+    pub fn export_private_key(&self) -> Vec<u8> {
+        self.signature_private_key.as_slice().to_vec()
+    }
+
+    /// Export the private key in PKCS#8 encoding (DER).
+    pub fn export_pkcs8(&self) -> Vec<u8> {
+        self.signature_private_key.to_pkcs8()
+    }
+
+    /// Serialize the `CredentialBundle`, including the private key.
+    ///
+    /// The serialization starts with a version byte, followed by the TLS
+    /// encoding of the credential and the private key as `opaque<0..2^16-1>`.
+    /// Use [`CredentialBundle::from_bytes()`] to read it.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CredentialError> {
+        let mut buffer = vec![];
+        CREDENTIAL_BUNDLE_VERSION.encode(&mut buffer)?;
+        self.credential.encode(&mut buffer)?;
+        encode_vec(
+            VecSize::VecU16,
+            &mut buffer,
+            self.signature_private_key.as_slice(),
+        )?;
+        Ok(buffer)
+    }
+
+    /// Read a `CredentialBundle` that was serialized with
+    /// [`CredentialBundle::to_bytes()`].
+    ///
+    /// Returns a `CredentialError::UnsupportedVersion` if the serialization
+    /// has an unknown version and a `CredentialError::KeyMismatch` if the
+    /// private key doesn't belong to the public key in the credential.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CredentialError> {
+        let cursor = &mut Cursor::new(bytes);
+        if u8::decode(cursor)? != CREDENTIAL_BUNDLE_VERSION {
+            return Err(CredentialError::UnsupportedVersion);
+        }
+        let credential = Credential::decode(cursor)?;
+        let private_key: Vec<u8> = decode_vec(VecSize::VecU16, cursor)?;
+        if cursor.has_more() {
+            return Err(CredentialError::CodecError(CodecError::TrailingData));
+        }
+
+        let public_key = match &credential.credential {
+            MLSCredentialType::Basic(basic_credential) => &basic_credential.public_key,
+            MLSCredentialType::X509(_) => return Err(CredentialError::UnsupportedCredentialType),
+        };
+        let ciphersuite = Config::ciphersuite(credential.ciphersuite().name())?;
+        let (private_key, derived_public_key) = ciphersuite
+            .signature_keypair_from_private_key(&private_key)?
+            .into_tuple();
+        if &derived_public_key != public_key {
+            return Err(CredentialError::KeyMismatch);
+        }
+        Ok(CredentialBundle {
+            credential,
+            signature_private_key: private_key,
+        })
+    }
+
     pub fn credential(&self) -> &Credential {
         &self.credential
     }
@@ -3,7 +3,31 @@
 //! Each module has their own errors it is returning. This module will defines
 //! helper macros and functions to define OpenMLS errors.
 //!
-//! TODO: define global enum with integer error codes for FFI usage.
+//! For FFI usage, every error variant maps to an [`ErrorCode`] with a stable
+//! `u16` value. The `code()` function of the public error types returns it.
+//! Errors that wrap an error of another module return the code of the wrapped
+//! error, e.g. a `ManagedGroupError::Group(GroupError::WelcomeError(..))`
+//! returns the code of the `WelcomeError`.
+
+use crate::ciphersuite::CryptoError;
+use crate::codec::CodecError;
+use crate::config::ConfigError;
+use crate::credentials::CredentialError;
+use crate::extensions::errors::{
+    CapabilitiesExtensionError, ExtensionError, InvalidExtensionError, KeyPackageIdError,
+    LastResortError, LifetimeExtensionError, ParentHashError, RatchetTreeError, RolesError,
+    UserIdError,
+};
+use crate::framing::errors::{MLSCiphertextError, MLSPlaintextError};
+use crate::group::errors::{
+    ApplyCommitError, CreateCommitError, ExporterError, GroupError, StorageError, WelcomeError,
+};
+use crate::group::{
+    CredentialRotationError, InvalidMessageError, ManagedGroupError, MembershipError,
+    RolePolicyError,
+};
+use crate::key_packages::KeyPackageError;
+use crate::tree::TreeError;
 
 // Macro helpers
 
@@ -196,3 +220,616 @@ impl ErrorPayload {
         format!("{:?}", self.0.clone())
     }
 }
+
+/// Stable numeric error codes for FFI consumers.
+///
+/// The values are grouped by module and never change. New codes are only
+/// added, and codes of removed errors are not reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum ErrorCode {
+    // Codec errors
+    CodecEncodingError = 100,
+    CodecDecodingError = 101,
+    CodecTrailingData = 102,
+    CodecOther = 103,
+
+    // Config errors
+    ConfigInvalidConfig = 200,
+    ConfigUnsupportedMlsVersion = 201,
+    ConfigUnsupportedCiphersuite = 202,
+    ConfigUnsupportedSignatureScheme = 203,
+
+    // Crypto errors
+    CryptoLibraryError = 300,
+    CryptoHpkeDecryptionError = 301,
+    CryptoInvalidKey = 302,
+
+    // Credential errors
+    CredentialUnsupportedCredentialType = 400,
+    CredentialUnsupportedVersion = 401,
+    CredentialKeyMismatch = 402,
+
+    // Key package errors
+    KeyPackageUnsupportedCiphersuite = 500,
+    KeyPackageUnknownConfigError = 501,
+    KeyPackageMandatoryExtensionsMissing = 502,
+    KeyPackageInvalidLifetimeExtension = 503,
+    KeyPackageLifetimeTooLong = 504,
+    KeyPackageInvalidSignature = 505,
+    KeyPackageLibraryError = 506,
+    KeyPackageDuplicateExtension = 507,
+    KeyPackageNoCiphersuitesSupplied = 508,
+    KeyPackageCiphersuiteMismatch = 509,
+    KeyPackageUserIdMismatch = 510,
+    KeyPackageSignatureSchemeMismatch = 511,
+    KeyPackageCapabilitiesExtensionSupplied = 512,
+    KeyPackageIdentityMismatch = 513,
+
+    // Extension errors
+    ExtensionInvalidExtensionType = 600,
+    ExtensionInvalidLifetime = 601,
+    ExtensionLifetimeExpired = 602,
+    ExtensionInvalidCapabilities = 603,
+    ExtensionCapabilitiesEmptyVersionsField = 604,
+    ExtensionCapabilitiesUnsupportedCiphersuite = 605,
+    ExtensionInvalidKeyPackageId = 606,
+    ExtensionInvalidParentHash = 607,
+    ExtensionInvalidRatchetTree = 608,
+    ExtensionInvalidRoles = 609,
+    ExtensionRolesDuplicateIdentity = 610,
+    ExtensionInvalidUserId = 611,
+    ExtensionInvalidLastResort = 612,
+    ExtensionDuplicate = 613,
+
+    // Tree errors
+    TreeInvalidArguments = 700,
+    TreeInvalidUpdatePath = 701,
+    TreeInvalidTree = 702,
+    TreePathSecretDecryptionError = 703,
+
+    // Plaintext errors
+    PlaintextNotAnApplicationMessage = 900,
+    PlaintextInvalidMembershipTag = 901,
+
+    // Ciphertext errors
+    CiphertextInvalidContentType = 1000,
+    CiphertextInvalidSenderData = 1001,
+    CiphertextGenerationOutOfBound = 1002,
+    CiphertextUnknownSender = 1003,
+    CiphertextInvalidPadding = 1004,
+    CiphertextWrongEpoch = 1005,
+    CiphertextReplay = 1006,
+    CiphertextDecryptionError = 1007,
+    CiphertextDecodingError = 1008,
+    CiphertextInvalidSignature = 1009,
+    CiphertextSecretReuse = 1010,
+
+    // Welcome errors
+    WelcomeCiphersuiteMismatch = 1100,
+    WelcomeJoinerSecretNotFound = 1101,
+    WelcomeMissingRatchetTree = 1102,
+    WelcomeTreeHashMismatch = 1103,
+    WelcomeConfirmationTagMismatch = 1104,
+    WelcomeInvalidGroupInfoSignature = 1105,
+    WelcomeGroupInfoDecryptionFailure = 1106,
+    WelcomeDuplicateRatchetTreeExtension = 1107,
+    WelcomeUnsupportedMlsVersion = 1108,
+    WelcomeUnknownError = 1109,
+    WelcomeGroupSecretsDecryptionFailure = 1110,
+
+    // Apply commit errors
+    ApplyCommitEpochMismatch = 1200,
+    ApplyCommitWrongPlaintextContentType = 1201,
+    ApplyCommitSelfRemoved = 1202,
+    ApplyCommitPathKeyPackageVerificationFailure = 1203,
+    ApplyCommitProposalKeyPackageVerificationFailure = 1204,
+    ApplyCommitCredentialIdentityMismatch = 1205,
+    ApplyCommitNoParentHashExtension = 1206,
+    ApplyCommitParentHashMismatch = 1207,
+    ApplyCommitPlaintextSignatureFailure = 1208,
+    ApplyCommitInvalidMembershipTag = 1209,
+    ApplyCommitRequiredPathNotFound = 1210,
+    ApplyCommitConfirmationTagMismatch = 1211,
+    ApplyCommitMissingOwnKeyPackage = 1212,
+    ApplyCommitMissingProposal = 1213,
+    ApplyCommitOwnKeyNotFound = 1214,
+
+    // Create commit errors
+    CreateCommitCannotRemoveSelf = 1300,
+    CreateCommitOwnKeyNotFound = 1301,
+
+    // Exporter errors
+    ExporterKeyLengthTooLong = 1400,
+    ExporterKeyLengthZero = 1401,
+    ExporterUnknownEpoch = 1402,
+
+    // Other group errors
+    GroupSecretTypeError = 1500,
+    GroupProposalQueueError = 1501,
+
+    // Storage errors
+    StorageInvalidKeyLength = 1600,
+    StorageInvalidHeader = 1601,
+    StorageUnsupportedVersion = 1602,
+    StorageEncryptionError = 1603,
+    StorageDecryptionError = 1604,
+    StorageGroupIdMismatch = 1605,
+    StorageEpochRollback = 1606,
+    StorageStateTooOld = 1607,
+
+    // Managed group errors
+    ManagedGroupLibraryError = 2000,
+    ManagedGroupUseAfterEviction = 2001,
+    ManagedGroupPendingProposalsExist = 2002,
+    ManagedGroupUnsupportedPreSharedKeys = 2003,
+
+    // Membership errors
+    MembershipUnknownIdentity = 2100,
+    MembershipUnknownUser = 2101,
+    MembershipNoKeyPackages = 2102,
+    MembershipUserMismatch = 2103,
+    MembershipNoMatchingKeyPackage = 2104,
+    MembershipUserAlreadyMember = 2105,
+
+    // Role policy errors
+    RolePolicyNotEnabled = 2200,
+    RolePolicyInsufficientRole = 2201,
+    RolePolicyUnknownMember = 2202,
+    RolePolicyNoOwnerLeft = 2203,
+    RolePolicyInvalidRoles = 2204,
+
+    // Credential rotation errors
+    CredentialRotationIdentityMismatch = 2300,
+    CredentialRotationCredentialMismatch = 2301,
+    CredentialRotationNotCommitted = 2302,
+
+    // Invalid message errors
+    InvalidMessageUnexpectedWireFormat = 2400,
+    InvalidMessageInvalidMembershipTag = 2401,
+    InvalidMessageInvalidCiphertext = 2402,
+    InvalidMessageCommitWithInvalidProposals = 2403,
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(error_code: ErrorCode) -> u16 {
+        error_code as u16
+    }
+}
+
+impl CodecError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CodecError::EncodingError => ErrorCode::CodecEncodingError,
+            CodecError::DecodingError => ErrorCode::CodecDecodingError,
+            CodecError::TrailingData => ErrorCode::CodecTrailingData,
+            CodecError::Other => ErrorCode::CodecOther,
+        }
+    }
+}
+
+impl ConfigError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ConfigError::InvalidConfig => ErrorCode::ConfigInvalidConfig,
+            ConfigError::UnsupportedMlsVersion => ErrorCode::ConfigUnsupportedMlsVersion,
+            ConfigError::UnsupportedCiphersuite => ErrorCode::ConfigUnsupportedCiphersuite,
+            ConfigError::UnsupportedSignatureScheme => ErrorCode::ConfigUnsupportedSignatureScheme,
+        }
+    }
+}
+
+impl CryptoError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CryptoError::CryptoLibraryError => ErrorCode::CryptoLibraryError,
+            CryptoError::HpkeDecryptionError => ErrorCode::CryptoHpkeDecryptionError,
+            CryptoError::InvalidKey => ErrorCode::CryptoInvalidKey,
+        }
+    }
+}
+
+impl CredentialError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CredentialError::ConfigError(e) => e.code(),
+            CredentialError::CryptoError(e) => e.code(),
+            CredentialError::CodecError(e) => e.code(),
+            CredentialError::UnsupportedCredentialType => {
+                ErrorCode::CredentialUnsupportedCredentialType
+            }
+            CredentialError::UnsupportedVersion => ErrorCode::CredentialUnsupportedVersion,
+            CredentialError::KeyMismatch => ErrorCode::CredentialKeyMismatch,
+        }
+    }
+}
+
+impl KeyPackageError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            KeyPackageError::ConfigError(e) => e.code(),
+            KeyPackageError::UnsupportedCiphersuite => ErrorCode::KeyPackageUnsupportedCiphersuite,
+            KeyPackageError::UnknownConfigError => ErrorCode::KeyPackageUnknownConfigError,
+            KeyPackageError::MandatoryExtensionsMissing => {
+                ErrorCode::KeyPackageMandatoryExtensionsMissing
+            }
+            KeyPackageError::InvalidLifetimeExtension => {
+                ErrorCode::KeyPackageInvalidLifetimeExtension
+            }
+            KeyPackageError::LifetimeTooLong => ErrorCode::KeyPackageLifetimeTooLong,
+            KeyPackageError::InvalidSignature => ErrorCode::KeyPackageInvalidSignature,
+            KeyPackageError::LibraryError => ErrorCode::KeyPackageLibraryError,
+            KeyPackageError::DuplicateExtension => ErrorCode::KeyPackageDuplicateExtension,
+            KeyPackageError::NoCiphersuitesSupplied => ErrorCode::KeyPackageNoCiphersuitesSupplied,
+            KeyPackageError::CiphersuiteMismatch => ErrorCode::KeyPackageCiphersuiteMismatch,
+            KeyPackageError::UserIdMismatch => ErrorCode::KeyPackageUserIdMismatch,
+            KeyPackageError::SignatureSchemeMismatch => {
+                ErrorCode::KeyPackageSignatureSchemeMismatch
+            }
+            KeyPackageError::CapabilitiesExtensionSupplied => {
+                ErrorCode::KeyPackageCapabilitiesExtensionSupplied
+            }
+            KeyPackageError::IdentityMismatch => ErrorCode::KeyPackageIdentityMismatch,
+            KeyPackageError::ExtensionError(e) => e.code(),
+        }
+    }
+}
+
+impl ExtensionError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ExtensionError::InvalidExtensionType(_) => ErrorCode::ExtensionInvalidExtensionType,
+            ExtensionError::Capabilities(e) => e.code(),
+            ExtensionError::Lifetime(e) => e.code(),
+            ExtensionError::KeyPackageId(e) => e.code(),
+            ExtensionError::ParentHash(e) => e.code(),
+            ExtensionError::RatchetTree(e) => e.code(),
+            ExtensionError::Roles(e) => e.code(),
+            ExtensionError::UserId(e) => e.code(),
+            ExtensionError::LastResort(e) => e.code(),
+            ExtensionError::CodecError(e) => e.code(),
+            ExtensionError::ConfigError(e) => e.code(),
+            ExtensionError::InvalidExtension(e) => e.code(),
+        }
+    }
+}
+
+impl LifetimeExtensionError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            LifetimeExtensionError::Invalid => ErrorCode::ExtensionInvalidLifetime,
+            LifetimeExtensionError::Expired => ErrorCode::ExtensionLifetimeExpired,
+        }
+    }
+}
+
+impl CapabilitiesExtensionError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CapabilitiesExtensionError::Invalid => ErrorCode::ExtensionInvalidCapabilities,
+            CapabilitiesExtensionError::EmptyVersionsField => {
+                ErrorCode::ExtensionCapabilitiesEmptyVersionsField
+            }
+            CapabilitiesExtensionError::UnsupportedCiphersuite => {
+                ErrorCode::ExtensionCapabilitiesUnsupportedCiphersuite
+            }
+        }
+    }
+}
+
+impl KeyPackageIdError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            KeyPackageIdError::Invalid => ErrorCode::ExtensionInvalidKeyPackageId,
+        }
+    }
+}
+
+impl ParentHashError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ParentHashError::Invalid => ErrorCode::ExtensionInvalidParentHash,
+        }
+    }
+}
+
+impl RatchetTreeError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            RatchetTreeError::Invalid => ErrorCode::ExtensionInvalidRatchetTree,
+        }
+    }
+}
+
+impl RolesError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            RolesError::Invalid => ErrorCode::ExtensionInvalidRoles,
+            RolesError::DuplicateIdentity => ErrorCode::ExtensionRolesDuplicateIdentity,
+        }
+    }
+}
+
+impl UserIdError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            UserIdError::Invalid => ErrorCode::ExtensionInvalidUserId,
+        }
+    }
+}
+
+impl LastResortError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            LastResortError::Invalid => ErrorCode::ExtensionInvalidLastResort,
+        }
+    }
+}
+
+impl InvalidExtensionError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            InvalidExtensionError::Duplicate => ErrorCode::ExtensionDuplicate,
+        }
+    }
+}
+
+impl TreeError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TreeError::ConfigError(e) => e.code(),
+            TreeError::InvalidArguments => ErrorCode::TreeInvalidArguments,
+            TreeError::InvalidUpdatePath => ErrorCode::TreeInvalidUpdatePath,
+            TreeError::InvalidTree => ErrorCode::TreeInvalidTree,
+            TreeError::PathSecretDecryptionError(_) => ErrorCode::TreePathSecretDecryptionError,
+        }
+    }
+}
+
+impl MLSPlaintextError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MLSPlaintextError::NotAnApplicationMessage => {
+                ErrorCode::PlaintextNotAnApplicationMessage
+            }
+            MLSPlaintextError::InvalidMembershipTag => ErrorCode::PlaintextInvalidMembershipTag,
+        }
+    }
+}
+
+impl MLSCiphertextError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MLSCiphertextError::InvalidContentType => ErrorCode::CiphertextInvalidContentType,
+            MLSCiphertextError::InvalidSenderData => ErrorCode::CiphertextInvalidSenderData,
+            MLSCiphertextError::GenerationOutOfBound => ErrorCode::CiphertextGenerationOutOfBound,
+            MLSCiphertextError::UnknownSender => ErrorCode::CiphertextUnknownSender,
+            MLSCiphertextError::InvalidPadding => ErrorCode::CiphertextInvalidPadding,
+            MLSCiphertextError::WrongEpoch => ErrorCode::CiphertextWrongEpoch,
+            MLSCiphertextError::Replay => ErrorCode::CiphertextReplay,
+            MLSCiphertextError::DecryptionError => ErrorCode::CiphertextDecryptionError,
+            MLSCiphertextError::DecodingError => ErrorCode::CiphertextDecodingError,
+            MLSCiphertextError::InvalidSignature => ErrorCode::CiphertextInvalidSignature,
+            MLSCiphertextError::SecretReuse => ErrorCode::CiphertextSecretReuse,
+        }
+    }
+}
+
+impl WelcomeError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            WelcomeError::CiphersuiteMismatch => ErrorCode::WelcomeCiphersuiteMismatch,
+            WelcomeError::JoinerSecretNotFound => ErrorCode::WelcomeJoinerSecretNotFound,
+            WelcomeError::MissingRatchetTree => ErrorCode::WelcomeMissingRatchetTree,
+            WelcomeError::TreeHashMismatch => ErrorCode::WelcomeTreeHashMismatch,
+            WelcomeError::ConfirmationTagMismatch => ErrorCode::WelcomeConfirmationTagMismatch,
+            WelcomeError::InvalidGroupInfoSignature => ErrorCode::WelcomeInvalidGroupInfoSignature,
+            WelcomeError::GroupInfoDecryptionFailure => {
+                ErrorCode::WelcomeGroupInfoDecryptionFailure
+            }
+            WelcomeError::DuplicateRatchetTreeExtension => {
+                ErrorCode::WelcomeDuplicateRatchetTreeExtension
+            }
+            WelcomeError::UnsupportedMlsVersion => ErrorCode::WelcomeUnsupportedMlsVersion,
+            WelcomeError::UnknownError => ErrorCode::WelcomeUnknownError,
+            WelcomeError::InvalidRatchetTree(e) => e.code(),
+            WelcomeError::GroupSecretsDecryptionFailure(_) => {
+                ErrorCode::WelcomeGroupSecretsDecryptionFailure
+            }
+        }
+    }
+}
+
+impl ApplyCommitError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApplyCommitError::EpochMismatch => ErrorCode::ApplyCommitEpochMismatch,
+            ApplyCommitError::WrongPlaintextContentType => {
+                ErrorCode::ApplyCommitWrongPlaintextContentType
+            }
+            ApplyCommitError::SelfRemoved => ErrorCode::ApplyCommitSelfRemoved,
+            ApplyCommitError::PathKeyPackageVerificationFailure => {
+                ErrorCode::ApplyCommitPathKeyPackageVerificationFailure
+            }
+            ApplyCommitError::ProposalKeyPackageVerificationFailure => {
+                ErrorCode::ApplyCommitProposalKeyPackageVerificationFailure
+            }
+            ApplyCommitError::CredentialIdentityMismatch => {
+                ErrorCode::ApplyCommitCredentialIdentityMismatch
+            }
+            ApplyCommitError::NoParentHashExtension => ErrorCode::ApplyCommitNoParentHashExtension,
+            ApplyCommitError::ParentHashMismatch => ErrorCode::ApplyCommitParentHashMismatch,
+            ApplyCommitError::PlaintextSignatureFailure => {
+                ErrorCode::ApplyCommitPlaintextSignatureFailure
+            }
+            ApplyCommitError::InvalidMembershipTag => ErrorCode::ApplyCommitInvalidMembershipTag,
+            ApplyCommitError::RequiredPathNotFound => ErrorCode::ApplyCommitRequiredPathNotFound,
+            ApplyCommitError::ConfirmationTagMismatch => {
+                ErrorCode::ApplyCommitConfirmationTagMismatch
+            }
+            ApplyCommitError::MissingOwnKeyPackage => ErrorCode::ApplyCommitMissingOwnKeyPackage,
+            ApplyCommitError::MissingProposal => ErrorCode::ApplyCommitMissingProposal,
+            ApplyCommitError::OwnKeyNotFound => ErrorCode::ApplyCommitOwnKeyNotFound,
+            ApplyCommitError::DecryptionFailure(e) => e.code(),
+        }
+    }
+}
+
+impl CreateCommitError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CreateCommitError::CannotRemoveSelf => ErrorCode::CreateCommitCannotRemoveSelf,
+            CreateCommitError::OwnKeyNotFound => ErrorCode::CreateCommitOwnKeyNotFound,
+        }
+    }
+}
+
+impl ExporterError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ExporterError::KeyLengthTooLong => ErrorCode::ExporterKeyLengthTooLong,
+            ExporterError::KeyLengthZero => ErrorCode::ExporterKeyLengthZero,
+            ExporterError::UnknownEpoch => ErrorCode::ExporterUnknownEpoch,
+        }
+    }
+}
+
+impl StorageError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StorageError::InvalidKeyLength => ErrorCode::StorageInvalidKeyLength,
+            StorageError::InvalidHeader => ErrorCode::StorageInvalidHeader,
+            StorageError::UnsupportedVersion => ErrorCode::StorageUnsupportedVersion,
+            StorageError::EncryptionError => ErrorCode::StorageEncryptionError,
+            StorageError::DecryptionError => ErrorCode::StorageDecryptionError,
+            StorageError::GroupIdMismatch => ErrorCode::StorageGroupIdMismatch,
+            StorageError::EpochRollback => ErrorCode::StorageEpochRollback,
+            StorageError::StateTooOld => ErrorCode::StorageStateTooOld,
+        }
+    }
+}
+
+impl GroupError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            GroupError::MLSCiphertextError(e) => e.code(),
+            GroupError::WelcomeError(e) => e.code(),
+            GroupError::ApplyCommitError(e) => e.code(),
+            GroupError::CreateCommitError(e) => e.code(),
+            GroupError::ConfigError(e) => e.code(),
+            GroupError::ExporterError(e) => e.code(),
+            GroupError::SecretTypeError(_) => ErrorCode::GroupSecretTypeError,
+            GroupError::ProposalQueueError(_) => ErrorCode::GroupProposalQueueError,
+            GroupError::KeyPackageError(e) => e.code(),
+        }
+    }
+}
+
+impl ManagedGroupError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ManagedGroupError::Codec(e) => e.code(),
+            ManagedGroupError::Config(e) => e.code(),
+            ManagedGroupError::Group(e) => e.code(),
+            ManagedGroupError::CreateCommit(e) => e.code(),
+            ManagedGroupError::Exporter(e) => e.code(),
+            ManagedGroupError::RolePolicy(e) => e.code(),
+            ManagedGroupError::Membership(e) => e.code(),
+            ManagedGroupError::CredentialRotation(e) => e.code(),
+            ManagedGroupError::KeyPackage(e) => e.code(),
+            ManagedGroupError::LibraryError(_) => ErrorCode::ManagedGroupLibraryError,
+            ManagedGroupError::UseAfterEviction(_) => ErrorCode::ManagedGroupUseAfterEviction,
+            ManagedGroupError::PendingProposalsExist(_) => {
+                ErrorCode::ManagedGroupPendingProposalsExist
+            }
+            ManagedGroupError::Unsupported(_) => ErrorCode::ManagedGroupUnsupportedPreSharedKeys,
+        }
+    }
+}
+
+impl MembershipError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MembershipError::UnknownIdentity => ErrorCode::MembershipUnknownIdentity,
+            MembershipError::UnknownUser => ErrorCode::MembershipUnknownUser,
+            MembershipError::NoKeyPackages => ErrorCode::MembershipNoKeyPackages,
+            MembershipError::UserMismatch => ErrorCode::MembershipUserMismatch,
+            MembershipError::NoMatchingKeyPackage => ErrorCode::MembershipNoMatchingKeyPackage,
+            MembershipError::UserAlreadyMember => ErrorCode::MembershipUserAlreadyMember,
+        }
+    }
+}
+
+impl RolePolicyError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            RolePolicyError::NotEnabled => ErrorCode::RolePolicyNotEnabled,
+            RolePolicyError::InsufficientRole => ErrorCode::RolePolicyInsufficientRole,
+            RolePolicyError::UnknownMember => ErrorCode::RolePolicyUnknownMember,
+            RolePolicyError::NoOwnerLeft => ErrorCode::RolePolicyNoOwnerLeft,
+            RolePolicyError::InvalidRoles => ErrorCode::RolePolicyInvalidRoles,
+        }
+    }
+}
+
+impl CredentialRotationError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CredentialRotationError::IdentityMismatch => {
+                ErrorCode::CredentialRotationIdentityMismatch
+            }
+            CredentialRotationError::CredentialMismatch => {
+                ErrorCode::CredentialRotationCredentialMismatch
+            }
+            CredentialRotationError::NotCommitted => ErrorCode::CredentialRotationNotCommitted,
+        }
+    }
+}
+
+impl InvalidMessageError {
+    /// Get the stable [`ErrorCode`] of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            InvalidMessageError::CommitError(e) => e.code(),
+            InvalidMessageError::GroupError(e) => e.code(),
+            InvalidMessageError::UnexpectedWireFormat => {
+                ErrorCode::InvalidMessageUnexpectedWireFormat
+            }
+            InvalidMessageError::InvalidMembershipTag => {
+                ErrorCode::InvalidMessageInvalidMembershipTag
+            }
+            InvalidMessageError::InvalidCiphertext(_) => ErrorCode::InvalidMessageInvalidCiphertext,
+            InvalidMessageError::CommitWithInvalidProposals(_) => {
+                ErrorCode::InvalidMessageCommitWithInvalidProposals
+            }
+        }
+    }
+}
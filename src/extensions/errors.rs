@@ -9,6 +9,9 @@
 //! * `KeyPackageIdError`
 //! * `ParentHashError`
 //! * `RatchetTreeError`
+//! * `RolesError`
+//! * `UserIdError`
+//! * `LastResortError`
 
 use crate::error::ErrorString;
 use crate::{codec::CodecError, config::ConfigError};
@@ -27,6 +30,12 @@ implement_error! {
             "Parent hash extension error. See `ParentHashError` for details.",
         RatchetTree(RatchetTreeError) =
             "Ratchet tree extension error. See `RatchetTreeError` for details.",
+        Roles(RolesError) =
+            "Roles extension error. See `RolesError` for details.",
+        UserId(UserIdError) =
+            "User ID extension error. See `UserIdError` for details.",
+        LastResort(LastResortError) =
+            "Last resort extension error. See `LastResortError` for details.",
         CodecError(CodecError) =
             "Error decoding or encoding an extension.",
         ConfigError(ConfigError) =
@@ -69,6 +78,25 @@ implement_error! {
     }
 }
 
+implement_error! {
+    pub enum RolesError {
+        Invalid = "Invalid roles extensions.",
+        DuplicateIdentity = "The roles extension lists an identity more than once.",
+    }
+}
+
+implement_error! {
+    pub enum UserIdError {
+        Invalid = "Invalid user ID extensions.",
+    }
+}
+
+implement_error! {
+    pub enum LastResortError {
+        Invalid = "Invalid last resort extensions.",
+    }
+}
+
 implement_error! {
     pub enum InvalidExtensionError {
         Duplicate = "The provided extension list contains duplicate extensions.",
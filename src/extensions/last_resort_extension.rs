@@ -0,0 +1,60 @@
+//! # Last resort extension
+//!
+//! > Key Package Extension
+//!
+//! Key packages are meant to be used only once. When all published key
+//! packages of a client are consumed, nobody can add the client to a group
+//! until it publishes new ones, which an offline client can't do. A key package
+//! with the last resort extension may be used more than once. It should only be
+//! handed out when no other key package of the client is available.
+//!
+//! The bundle of a last resort key package is kept when it is used to join a
+//! group, see [`KeyPackageStore`](`crate::key_packages::KeyPackageStore`).
+//!
+//! This extension is not part of the MLS specification and therefore uses a
+//! value from the private range of extension types. It has no content.
+//!
+//! ```text
+//! struct {} LastResort;
+//! ```
+
+use super::{
+    Deserialize, Extension, ExtensionError, ExtensionStruct, ExtensionType, LastResortError,
+    Serialize,
+};
+
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LastResortExtension {}
+
+impl LastResortExtension {
+    /// Create a new last resort extension.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Extension for LastResortExtension {
+    fn extension_type(&self) -> ExtensionType {
+        ExtensionType::LastResort
+    }
+
+    /// Build a new LastResortExtension from a byte slice.
+    fn new_from_bytes(bytes: &[u8]) -> Result<Self, ExtensionError>
+    where
+        Self: Sized,
+    {
+        if !bytes.is_empty() {
+            return Err(ExtensionError::LastResort(LastResortError::Invalid));
+        }
+        Ok(Self {})
+    }
+
+    fn to_extension_struct(&self) -> ExtensionStruct {
+        ExtensionStruct::new(ExtensionType::LastResort, vec![])
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
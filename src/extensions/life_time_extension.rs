@@ -13,6 +13,7 @@
 //! Applications MUST define a maximum total lifetime that is acceptable for a
 //! KeyPackage, and reject any KeyPackage where the total lifetime is longer
 //! than this duration.This extension MUST always be present in a KeyPackage.
+//! The maximum total lifetime is set in the `Config`.
 //!
 //! ``` text
 //! uint64 not_before;
@@ -22,11 +23,10 @@ use super::{
     Deserialize, Extension, ExtensionError, ExtensionStruct, ExtensionType, LifetimeExtensionError,
     Serialize,
 };
+use crate::clock::{Clock, SystemClock};
 use crate::codec::{Codec, Cursor};
 use crate::config::Config;
 
-use std::time::{SystemTime, UNIX_EPOCH};
-
 /// The lifetime extension holds a not before and a not after time measured in
 /// seconds since the Unix epoch (1970-01-01T00:00:00Z).
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -40,13 +40,18 @@ impl LifetimeExtension {
     /// Note that the lifetime is extended 1h into the past to adapt to skewed
     /// clocks.
     pub fn new(t: u64) -> Self {
+        Self::new_with_clock(t, &SystemClock)
+    }
+
+    /// Create a new lifetime extensions with lifetime `t` (in seconds),
+    /// starting at the current time of the `clock`.
+    /// Note that the lifetime is extended 1h into the past to adapt to skewed
+    /// clocks.
+    pub fn new_with_clock(t: u64, clock: &dyn Clock) -> Self {
         let lifetime_margin: u64 = Config::key_package_lifetime_margin();
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("SystemTime before UNIX EPOCH!")
-            .as_secs();
-        let not_before = now - lifetime_margin;
-        let not_after = now + t;
+        let now = clock.now();
+        let not_before = now.saturating_sub(lifetime_margin);
+        let not_after = now.saturating_add(t);
         Self {
             not_before,
             not_after,
@@ -55,12 +60,21 @@ impl LifetimeExtension {
 
     /// Returns true if this lifetime is valid.
     pub(crate) fn is_valid(&self) -> bool {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("SystemTime before UNIX EPOCH!")
-            .as_secs();
+        self.is_valid_with_clock(&SystemClock)
+    }
+
+    /// Returns true if this lifetime is valid at the current time of the
+    /// `clock`.
+    pub(crate) fn is_valid_with_clock(&self, clock: &dyn Clock) -> bool {
+        let now = clock.now();
         self.not_before < now && now < self.not_after
     }
+
+    /// Returns true if the total lifetime of this extension doesn't exceed the
+    /// maximum lifetime set in the `Config`.
+    pub(crate) fn has_acceptable_range(&self) -> bool {
+        self.not_after.saturating_sub(self.not_before) <= Config::max_key_package_lifetime()
+    }
 }
 
 impl Default for LifetimeExtension {
@@ -83,14 +97,15 @@ impl Extension for LifetimeExtension {
         let mut cursor = Cursor::new(bytes);
         let not_before = u64::decode(&mut cursor)?;
         let not_after = u64::decode(&mut cursor)?;
-        let out = Self {
-            not_before,
-            not_after,
-        };
-        if !out.is_valid() {
+        if not_before >= not_after {
             return Err(ExtensionError::Lifetime(LifetimeExtensionError::Invalid));
         }
-        Ok(out)
+        // Whether the lifetime is valid at the current time is checked when
+        // the key package is verified.
+        Ok(Self {
+            not_before,
+            not_after,
+        })
     }
 
     fn to_extension_struct(&self) -> ExtensionStruct {
@@ -6,16 +6,22 @@ pub(crate) use serde::{Deserialize, Serialize};
 mod capabilities_extension;
 pub mod errors;
 mod key_package_id_extension;
+mod last_resort_extension;
 mod life_time_extension;
 mod parent_hash_extension;
 mod ratchet_tree_extension;
+mod roles_extension;
+mod user_id_extension;
 
 pub use capabilities_extension::CapabilitiesExtension;
 pub(crate) use errors::*;
 pub use key_package_id_extension::KeyIDExtension;
+pub use last_resort_extension::LastResortExtension;
 pub use life_time_extension::LifetimeExtension;
 pub(crate) use parent_hash_extension::ParentHashExtension;
 pub(crate) use ratchet_tree_extension::RatchetTreeExtension;
+pub use roles_extension::{Role, RolesExtension};
+pub use user_id_extension::{UserIdExtension, USER_ID_SEPARATOR};
 
 #[cfg(test)]
 mod test_extensions;
@@ -23,6 +29,9 @@ mod test_extensions;
 /// # Extension types
 ///
 /// [IANA registrations](https://messaginglayersecurity.rocks/mls-protocol/draft-ietf-mls-protocol.html#name-mls-extension-types)
+///
+/// Extension types from `0xff00` on are reserved for private use. OpenMLS uses
+/// them for extensions that are not part of the specification.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Ord, PartialOrd)]
 #[repr(u16)]
 pub enum ExtensionType {
@@ -32,6 +41,9 @@ pub enum ExtensionType {
     KeyID = 3,
     ParentHash = 4,
     RatchetTree = 5,
+    Roles = 0xff00,
+    UserId = 0xff01,
+    LastResort = 0xff02,
 }
 
 /// The default extension type is invalid.
@@ -56,6 +68,9 @@ impl TryFrom<u16> for ExtensionType {
             3 => Ok(ExtensionType::KeyID),
             4 => Ok(ExtensionType::ParentHash),
             5 => Ok(ExtensionType::RatchetTree),
+            0xff00 => Ok(ExtensionType::Roles),
+            0xff01 => Ok(ExtensionType::UserId),
+            0xff02 => Ok(ExtensionType::LastResort),
             _ => Err(CodecError::DecodingError),
         }
     }
@@ -135,6 +150,9 @@ fn from_bytes(ext_type: ExtensionType, bytes: &[u8]) -> Result<Box<dyn Extension
         ExtensionType::Lifetime => Ok(Box::new(LifetimeExtension::new_from_bytes(bytes)?)),
         ExtensionType::ParentHash => Ok(Box::new(ParentHashExtension::new_from_bytes(bytes)?)),
         ExtensionType::RatchetTree => Ok(Box::new(RatchetTreeExtension::new_from_bytes(bytes)?)),
+        ExtensionType::Roles => Ok(Box::new(RolesExtension::new_from_bytes(bytes)?)),
+        ExtensionType::UserId => Ok(Box::new(UserIdExtension::new_from_bytes(bytes)?)),
+        ExtensionType::LastResort => Ok(Box::new(LastResortExtension::new_from_bytes(bytes)?)),
         _ => Err(ExtensionError::InvalidExtensionType(
             format!("Invalid extension type {:?}.", ext_type).into(),
         )),
@@ -254,6 +272,42 @@ pub trait Extension: Debug + ExtensionHelper + Send {
             )),
         }
     }
+
+    /// Get a reference to the `RolesExtension`.
+    /// Returns an `InvalidExtensionType` error if called on an `Extension`
+    /// that's not a `RolesExtension`.
+    fn to_roles_extension(&self) -> Result<&RolesExtension, ExtensionError> {
+        match self.as_any().downcast_ref::<RolesExtension>() {
+            Some(e) => Ok(e),
+            None => Err(ExtensionError::InvalidExtensionType(
+                "This is not a RolesExtension".into(),
+            )),
+        }
+    }
+
+    /// Get a reference to the `UserIdExtension`.
+    /// Returns an `InvalidExtensionType` error if called on an `Extension`
+    /// that's not a `UserIdExtension`.
+    fn to_user_id_extension(&self) -> Result<&UserIdExtension, ExtensionError> {
+        match self.as_any().downcast_ref::<UserIdExtension>() {
+            Some(e) => Ok(e),
+            None => Err(ExtensionError::InvalidExtensionType(
+                "This is not a UserIdExtension".into(),
+            )),
+        }
+    }
+
+    /// Get a reference to the `LastResortExtension`.
+    /// Returns an `InvalidExtensionType` error if called on an `Extension`
+    /// that's not a `LastResortExtension`.
+    fn to_last_resort_extension(&self) -> Result<&LastResortExtension, ExtensionError> {
+        match self.as_any().downcast_ref::<LastResortExtension>() {
+            Some(e) => Ok(e),
+            None => Err(ExtensionError::InvalidExtensionType(
+                "This is not a LastResortExtension".into(),
+            )),
+        }
+    }
 }
 
 // A slightly hacky work around to make `Extensions` clonable.
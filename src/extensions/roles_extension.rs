@@ -0,0 +1,194 @@
+//! # Roles extension
+//!
+//! > GroupContext Extension
+//!
+//! The roles extension assigns a [`Role`] to the members of a group. Members
+//! are identified by the identity of their credential. Members that are not
+//! listed in the extension have the role [`Role::Member`].
+//!
+//! This extension is not part of the MLS specification. It is used by the role
+//! policy of the `ManagedGroup` and therefore uses a value from the private
+//! range of extension types.
+//!
+//! ```text
+//! enum {
+//!     reserved(0),
+//!     owner(1),
+//!     admin(2),
+//!     member(3),
+//!     (255)
+//! } Role;
+//!
+//! struct {
+//!     opaque identity<0..2^16-1>;
+//!     Role role;
+//! } RoleEntry;
+//!
+//! RoleEntry roles<0..2^32-1>;
+//! ```
+
+use std::convert::TryFrom;
+
+use super::{
+    Deserialize, Extension, ExtensionError, ExtensionStruct, ExtensionType, RolesError, Serialize,
+};
+use crate::codec::{decode_vec, encode_vec, Codec, CodecError, Cursor, VecSize};
+
+/// The role of a member in a group.
+///
+/// Roles are ordered by the rights they grant, i.e. `Owner > Admin > Member`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Role {
+    /// Owners can add and remove any member and change roles.
+    Owner = 1,
+    /// Admins can add members and remove members that are neither owners nor
+    /// admins.
+    Admin = 2,
+    /// Members can only update their own leaf and remove themselves.
+    Member = 3,
+}
+
+impl Role {
+    /// Returns `true` if this role grants at least the rights of `other`.
+    pub fn at_least(&self, other: Role) -> bool {
+        (*self as u8) <= (other as u8)
+    }
+}
+
+impl TryFrom<u8> for Role {
+    type Error = CodecError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Role::Owner),
+            2 => Ok(Role::Admin),
+            3 => Ok(Role::Member),
+            _ => Err(CodecError::DecodingError),
+        }
+    }
+}
+
+impl Codec for Role {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        (*self as u8).encode(buffer)
+    }
+
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        Role::try_from(u8::decode(cursor)?)
+    }
+}
+
+/// A single entry of the roles extension.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+struct RoleEntry {
+    identity: Vec<u8>,
+    role: Role,
+}
+
+impl Codec for RoleEntry {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        encode_vec(VecSize::VecU16, buffer, &self.identity)?;
+        self.role.encode(buffer)
+    }
+
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        let identity = decode_vec(VecSize::VecU16, cursor)?;
+        let role = Role::decode(cursor)?;
+        Ok(RoleEntry { identity, role })
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RolesExtension {
+    roles: Vec<RoleEntry>,
+}
+
+impl RolesExtension {
+    /// Create a new roles extension where `owner` is the only owner of the
+    /// group.
+    pub fn new(owner: &[u8]) -> Self {
+        let mut extension = Self::default();
+        extension.set_role(owner, Role::Owner);
+        extension
+    }
+
+    /// Get the role of the member with the given identity.
+    /// Members that are not listed have the role `Role::Member`.
+    pub fn role(&self, identity: &[u8]) -> Role {
+        self.roles
+            .iter()
+            .find(|entry| entry.identity == identity)
+            .map(|entry| entry.role)
+            .unwrap_or(Role::Member)
+    }
+
+    /// Set the role of the member with the given identity.
+    /// Setting a role to `Role::Member` removes the identity from the list.
+    pub fn set_role(&mut self, identity: &[u8], role: Role) {
+        self.roles.retain(|entry| entry.identity != identity);
+        if role != Role::Member {
+            self.roles.push(RoleEntry {
+                identity: identity.to_vec(),
+                role,
+            });
+        }
+    }
+
+    /// Get a list of all identities that have a role other than
+    /// `Role::Member`.
+    pub fn roles(&self) -> Vec<(&[u8], Role)> {
+        self.roles
+            .iter()
+            .map(|entry| (entry.identity.as_slice(), entry.role))
+            .collect()
+    }
+
+    /// Drop the roles of all identities that are not in `identities`.
+    pub(crate) fn retain(&mut self, identities: &[&[u8]]) {
+        self.roles
+            .retain(|entry| identities.contains(&entry.identity.as_slice()));
+    }
+
+    /// Returns `true` if there is at least one owner.
+    pub fn has_owner(&self) -> bool {
+        self.roles.iter().any(|entry| entry.role == Role::Owner)
+    }
+}
+
+#[typetag::serde]
+impl Extension for RolesExtension {
+    fn extension_type(&self) -> ExtensionType {
+        ExtensionType::Roles
+    }
+
+    /// Build a new RolesExtension from a byte slice.
+    fn new_from_bytes(bytes: &[u8]) -> Result<Self, ExtensionError>
+    where
+        Self: Sized,
+    {
+        let cursor = &mut Cursor::new(bytes);
+        let roles: Vec<RoleEntry> = match decode_vec(VecSize::VecU32, cursor) {
+            Ok(roles) => roles,
+            Err(_) => return Err(ExtensionError::Roles(RolesError::Invalid)),
+        };
+        // Every identity must only be listed once.
+        for (i, entry) in roles.iter().enumerate() {
+            if roles[i + 1..].iter().any(|e| e.identity == entry.identity) {
+                return Err(ExtensionError::Roles(RolesError::DuplicateIdentity));
+            }
+        }
+        Ok(Self { roles })
+    }
+
+    fn to_extension_struct(&self) -> ExtensionStruct {
+        let mut extension_data: Vec<u8> = vec![];
+        encode_vec(VecSize::VecU32, &mut extension_data, &self.roles).unwrap();
+        let extension_type = ExtensionType::Roles;
+        ExtensionStruct::new(extension_type, extension_data)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
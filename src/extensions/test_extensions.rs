@@ -3,12 +3,16 @@
 //! Proper testing is done through the public APIs.
 
 use super::*;
+use crate::clock::FixedClock;
 use crate::codec::{Codec, Cursor};
+use crate::config::Config;
 
 #[test]
 fn capabilities() {
     // A capabilities extension with the default values for openmls.
-    let extension_bytes = [0, 1, 0, 16, 1, 1, 6, 0, 1, 0, 3, 0, 2, 6, 0, 1, 0, 2, 0, 3];
+    let extension_bytes = [
+        0, 1, 0, 18, 1, 1, 6, 0, 1, 0, 3, 0, 2, 8, 0, 1, 0, 2, 0, 3, 0xff, 0x02,
+    ];
 
     let ext = CapabilitiesExtension::default();
     let ext_struct = ext.to_extension_struct();
@@ -51,3 +55,47 @@ fn lifetime() {
     std::thread::sleep(std::time::Duration::from_secs(1));
     assert!(!ext.is_valid());
 }
+
+#[test]
+fn lifetime_with_clock() {
+    let mut clock = FixedClock::new(1_000_000);
+    let ext = LifetimeExtension::new_with_clock(60, &clock);
+    assert!(ext.is_valid_with_clock(&clock));
+
+    // The lifetime is extended into the past to adapt to skewed clocks.
+    let skewed_clock = FixedClock::new(1_000_000 - Config::key_package_lifetime_margin() + 1);
+    assert!(ext.is_valid_with_clock(&skewed_clock));
+
+    // The extension expires after its lifetime.
+    clock.advance(60);
+    assert!(!ext.is_valid_with_clock(&clock));
+
+    // Decoding doesn't depend on the current time.
+    let extension_struct = ext.to_extension_struct();
+    let decoded = LifetimeExtension::new_from_bytes(&extension_struct.extension_data).unwrap();
+    assert_eq!(ext, decoded);
+}
+
+#[test]
+fn last_resort() {
+    // A last resort extension has no content.
+    let extension_bytes = [0xff, 0x02, 0, 0];
+
+    let ext = LastResortExtension::new();
+    let ext_struct = ext.to_extension_struct();
+    assert_eq!(ExtensionType::LastResort, ext.extension_type());
+    assert_eq!(
+        &extension_bytes[..],
+        &ext_struct.encode_detached().unwrap()[..]
+    );
+
+    let decoded_struct = ExtensionStruct::decode(&mut Cursor::new(&extension_bytes)).unwrap();
+    assert_eq!(ext_struct, decoded_struct);
+    assert_eq!(ext, LastResortExtension::new_from_bytes(&[]).unwrap());
+
+    // Any content is invalid.
+    assert_eq!(
+        LastResortExtension::new_from_bytes(&[0]),
+        Err(ExtensionError::LastResort(LastResortError::Invalid))
+    );
+}
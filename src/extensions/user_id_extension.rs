@@ -0,0 +1,90 @@
+//! # User ID extension
+//!
+//! > Key Package Extension
+//!
+//! The user ID extension links several leaves of a group to the same user,
+//! e.g. when a user is a member of the group with multiple devices. All
+//! devices of a user carry the same user ID in their key packages, while every
+//! device has its own credential. Leaves without this extension are treated
+//! as a user of their own, identified by the identity of their credential.
+//!
+//! The user ID is bound to the credential of the device: the identity of the
+//! credential must either be the user ID itself or start with the user ID
+//! followed by [`USER_ID_SEPARATOR`], e.g. `alice:phone` for the user `alice`.
+//! Key packages that claim a user ID that doesn't match their credential are
+//! rejected, so a client can't claim the user ID of somebody else.
+//!
+//! This extension is not part of the MLS specification and therefore uses a
+//! value from the private range of extension types.
+//!
+//! ```text
+//! opaque user_id<0..2^16-1>;
+//! ```
+
+use super::{
+    Deserialize, Extension, ExtensionError, ExtensionStruct, ExtensionType, Serialize, UserIdError,
+};
+use crate::codec::{decode_vec, encode_vec, Cursor, VecSize};
+
+/// Separates the user ID from the device name in the identity of a
+/// credential.
+pub const USER_ID_SEPARATOR: u8 = b':';
+
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UserIdExtension {
+    user_id: Vec<u8>,
+}
+
+impl UserIdExtension {
+    /// Create a new user ID extension from a byte slice.
+    pub fn new(user_id: &[u8]) -> Self {
+        Self {
+            user_id: user_id.to_vec(),
+        }
+    }
+
+    /// Get the value of the user ID as byte slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.user_id
+    }
+
+    /// Returns `true` if a credential with the given `identity` may claim
+    /// this user ID, i.e. if the identity is the user ID or starts with the
+    /// user ID followed by [`USER_ID_SEPARATOR`].
+    pub fn matches_identity(&self, identity: &[u8]) -> bool {
+        identity == self.user_id.as_slice()
+            || (identity.len() > self.user_id.len()
+                && identity.starts_with(&self.user_id)
+                && identity[self.user_id.len()] == USER_ID_SEPARATOR)
+    }
+}
+
+#[typetag::serde]
+impl Extension for UserIdExtension {
+    fn extension_type(&self) -> ExtensionType {
+        ExtensionType::UserId
+    }
+
+    /// Build a new UserIdExtension from a byte slice.
+    fn new_from_bytes(bytes: &[u8]) -> Result<Self, ExtensionError>
+    where
+        Self: Sized,
+    {
+        let cursor = &mut Cursor::new(bytes);
+        match decode_vec(VecSize::VecU16, cursor) {
+            Ok(user_id) => Ok(Self { user_id }),
+            Err(_) => Err(ExtensionError::UserId(UserIdError::Invalid)),
+        }
+    }
+
+    fn to_extension_struct(&self) -> ExtensionStruct {
+        let mut extension_data: Vec<u8> = vec![];
+        encode_vec(VecSize::VecU16, &mut extension_data, &self.user_id).unwrap();
+        let extension_type = ExtensionType::UserId;
+        ExtensionStruct::new(extension_type, extension_data)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -43,7 +43,7 @@ impl MLSCiphertext {
         ratchet_nonce.xor_with_reuse_guard(&reuse_guard);
         let ciphertext = ratchet_key
             .aead_seal(
-                &Self::encode_padded_ciphertext_content_detached(mls_plaintext).unwrap(),
+                &Self::encode_padded_ciphertext_content_detached(mls_plaintext, mls_group).unwrap(),
                 &mls_ciphertext_content_aad_bytes,
                 &ratchet_nonce,
             )
@@ -91,34 +91,20 @@ impl MLSCiphertext {
         &self,
         ciphersuite: &Ciphersuite,
         indexed_members: HashMap<LeafIndex, &Credential>,
-        epoch_secrets: &EpochSecrets,
+        sender_data_secret: &SenderDataSecret,
         secret_tree: &mut SecretTree,
+        replay_cache: &mut ReplayCache,
         context: &GroupContext,
     ) -> Result<MLSPlaintext, MLSCiphertextError> {
-        // Derive key from the key schedule using the ciphertext.
-        let sender_data_key = AeadKey::from_sender_data_secret(
-            ciphersuite,
-            &self.ciphertext,
-            epoch_secrets.sender_data_secret(),
-        );
-        // Derive initial nonce from the key schedule using the ciphertext.
-        let sender_data_nonce = AeadNonce::from_sender_data_secret(
-            ciphersuite,
-            &self.ciphertext,
-            epoch_secrets.sender_data_secret(),
-        );
-        let mls_ciphertext_sender_data_aad =
-            MLSCiphertextSenderDataAAD::new(self.group_id.clone(), self.epoch, self.content_type);
-        let mls_ciphertext_sender_data_aad_bytes =
-            mls_ciphertext_sender_data_aad.encode_detached().unwrap();
-        let sender_data_bytes = &sender_data_key
-            .aead_open(
-                &self.encrypted_sender_data,
-                &mls_ciphertext_sender_data_aad_bytes,
-                &sender_data_nonce,
-            )
-            .unwrap();
-        let sender_data = MLSSenderData::from_bytes(&sender_data_bytes).unwrap();
+        let sender_data = self.sender_data(ciphersuite, sender_data_secret)?;
+        // Reject ciphertexts that were already decrypted
+        if replay_cache.contains(
+            sender_data.sender,
+            self.content_type,
+            sender_data.generation,
+        ) {
+            return Err(MLSCiphertextError::Replay);
+        }
         let secret_type = match SecretType::try_from(&self.content_type) {
             Ok(secret_type) => secret_type,
             Err(_) => return Err(MLSCiphertextError::InvalidContentType),
@@ -130,8 +116,17 @@ impl MLSCiphertext {
             sender_data.generation,
         ) {
             Ok(ratchet_secrets) => ratchet_secrets,
+            Err(SecretTreeError::SecretReuseError) => return Err(MLSCiphertextError::SecretReuse),
             Err(_) => return Err(MLSCiphertextError::GenerationOutOfBound),
         };
+        // The secrets of the generation are consumed now, so the generation is
+        // marked as seen even if the rest of the decryption fails
+        replay_cache.insert(
+            sender_data.sender,
+            self.content_type,
+            sender_data.generation,
+            secret_tree.configuration().out_of_order_tolerance(),
+        );
         ratchet_nonce.xor_with_reuse_guard(&sender_data.reuse_guard);
         let mls_ciphertext_content_aad = MLSCiphertextContentAAD {
             group_id: self.group_id.clone(),
@@ -141,15 +136,23 @@ impl MLSCiphertext {
         };
         let mls_ciphertext_content_aad_bytes =
             mls_ciphertext_content_aad.encode_detached().unwrap();
-        let mls_ciphertext_content_bytes = ratchet_key
-            .aead_open(
-                &self.ciphertext,
-                &mls_ciphertext_content_aad_bytes,
-                &ratchet_nonce,
-            )
-            .unwrap();
+        let mls_ciphertext_content_bytes = match ratchet_key.aead_open(
+            &self.ciphertext,
+            &mls_ciphertext_content_aad_bytes,
+            &ratchet_nonce,
+        ) {
+            Ok(bytes) => bytes,
+            Err(_) => return Err(MLSCiphertextError::DecryptionError),
+        };
         let mls_ciphertext_content =
-            MLSCiphertextContent::from_bytes(&mls_ciphertext_content_bytes).unwrap();
+            match MLSCiphertextContent::from_bytes(&mls_ciphertext_content_bytes) {
+                Ok(content) => content,
+                Err(_) => return Err(MLSCiphertextError::DecodingError),
+            };
+        // The padding must consist of zero bytes only
+        if mls_ciphertext_content.padding.iter().any(|&byte| byte != 0) {
+            return Err(MLSCiphertextError::InvalidPadding);
+        }
         let sender = Sender {
             sender_type: SenderType::Member,
             sender: sender_data.sender,
@@ -162,6 +165,7 @@ impl MLSCiphertext {
             content_type: self.content_type,
             content: mls_ciphertext_content.content,
             signature: mls_ciphertext_content.signature,
+            membership_tag: None,
         };
         let credential = match indexed_members.get(&sender_data.sender) {
             Some(c) => c,
@@ -171,10 +175,40 @@ impl MLSCiphertext {
         };
 
         let serialized_context = context.encode_detached().unwrap();
-        assert!(mls_plaintext.verify(Some(serialized_context), credential));
+        if !mls_plaintext.verify(Some(serialized_context), credential) {
+            return Err(MLSCiphertextError::InvalidSignature);
+        }
         Ok(mls_plaintext)
     }
 
+    /// Decrypt the sender data of this `MLSCiphertext`. This doesn't touch
+    /// the `SecretTree`.
+    pub(crate) fn sender_data(
+        &self,
+        ciphersuite: &Ciphersuite,
+        sender_data_secret: &SenderDataSecret,
+    ) -> Result<MLSSenderData, MLSCiphertextError> {
+        // Derive key from the key schedule using the ciphertext.
+        let sender_data_key =
+            AeadKey::from_sender_data_secret(ciphersuite, &self.ciphertext, sender_data_secret);
+        // Derive initial nonce from the key schedule using the ciphertext.
+        let sender_data_nonce =
+            AeadNonce::from_sender_data_secret(ciphersuite, &self.ciphertext, sender_data_secret);
+        let mls_ciphertext_sender_data_aad =
+            MLSCiphertextSenderDataAAD::new(self.group_id.clone(), self.epoch, self.content_type);
+        let mls_ciphertext_sender_data_aad_bytes =
+            mls_ciphertext_sender_data_aad.encode_detached().unwrap();
+        let sender_data_bytes = sender_data_key
+            .aead_open(
+                &self.encrypted_sender_data,
+                &mls_ciphertext_sender_data_aad_bytes,
+                &sender_data_nonce,
+            )
+            .map_err(|_| MLSCiphertextError::InvalidSenderData)?;
+        MLSSenderData::from_bytes(&sender_data_bytes)
+            .map_err(|_| MLSCiphertextError::InvalidSenderData)
+    }
+
     /// Returns `true` if this is a handshake message and `false` otherwise.
     pub fn is_handshake_message(&self) -> bool {
         self.content_type.is_handshake_message()
@@ -182,23 +216,112 @@ impl MLSCiphertext {
 
     fn encode_padded_ciphertext_content_detached(
         mls_plaintext: &MLSPlaintext,
+        mls_group: &MlsGroup,
     ) -> Result<Vec<u8>, CodecError> {
         let mut buffer = vec![];
         mls_plaintext.content.encode(&mut buffer)?;
         mls_plaintext.signature.encode(&mut buffer)?;
         let padding_offset = buffer.len() + 2 + TAG_BYTES;
-        // TODO: The PADDING SIZE should be retrieved from the config.
-        const PADDING_SIZE: usize = 10;
-        let mut padding_length = PADDING_SIZE - (padding_offset % PADDING_SIZE);
-        if PADDING_SIZE == padding_length {
-            padding_length = 0;
-        }
+        let padding_length = mls_group.padding_length(padding_offset);
         let padding_block = vec![0u8; padding_length];
         encode_vec(VecSize::VecU16, &mut buffer, &padding_block)?;
         Ok(buffer)
     }
 }
 
+/// A borrowed view of an encoded `MLSCiphertext`, created with
+/// [`MLSCiphertext::decode_borrowed()`]. All variable-length fields point into
+/// the encoded message.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MLSCiphertextRef<'a> {
+    pub(crate) group_id: &'a [u8],
+    pub(crate) epoch: GroupEpoch,
+    pub(crate) content_type: ContentType,
+    pub(crate) authenticated_data: &'a [u8],
+    pub(crate) encrypted_sender_data: &'a [u8],
+    pub(crate) ciphertext: &'a [u8],
+}
+
+impl<'a> MLSCiphertextRef<'a> {
+    /// Get the group ID.
+    pub fn group_id(&self) -> &'a [u8] {
+        self.group_id
+    }
+
+    /// Get the epoch.
+    pub fn epoch(&self) -> &GroupEpoch {
+        &self.epoch
+    }
+
+    /// Get the content type.
+    pub fn content_type(&self) -> ContentType {
+        self.content_type
+    }
+
+    /// Get the authenticated data.
+    pub fn authenticated_data(&self) -> &'a [u8] {
+        self.authenticated_data
+    }
+
+    /// Get the encrypted sender data.
+    pub fn encrypted_sender_data(&self) -> &'a [u8] {
+        self.encrypted_sender_data
+    }
+
+    /// Get the ciphertext.
+    pub fn ciphertext(&self) -> &'a [u8] {
+        self.ciphertext
+    }
+
+    /// Copy the borrowed values into an `MLSCiphertext`.
+    pub fn into_owned(self) -> MLSCiphertext {
+        MLSCiphertext {
+            group_id: GroupId {
+                value: self.group_id.to_vec(),
+            },
+            epoch: self.epoch,
+            content_type: self.content_type,
+            authenticated_data: self.authenticated_data.to_vec(),
+            encrypted_sender_data: self.encrypted_sender_data.to_vec(),
+            ciphertext: self.ciphertext.to_vec(),
+        }
+    }
+}
+
+/// The decrypted sender data of an `MLSCiphertext`, see
+/// [`MlsGroup::peek_sender_data()`](`crate::group::MlsGroup::peek_sender_data()`).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SenderData {
+    sender: LeafIndex,
+    generation: u32,
+    content_type: ContentType,
+}
+
+impl SenderData {
+    pub(crate) fn new(sender_data: &MLSSenderData, content_type: ContentType) -> Self {
+        Self {
+            sender: sender_data.sender,
+            generation: sender_data.generation,
+            content_type,
+        }
+    }
+
+    /// Get the leaf index of the sender.
+    pub fn sender(&self) -> LeafIndex {
+        self.sender
+    }
+
+    /// Get the generation of the sender's ratchet that encrypted the message.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Get the content type of the message.
+    pub fn content_type(&self) -> ContentType {
+        self.content_type
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct MLSSenderData {
     pub(crate) sender: LeafIndex,
@@ -1,26 +1,95 @@
 use super::*;
 
+use std::io::Write;
+
 impl Codec for MLSPlaintext {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         self.group_id.encode(buffer)?;
         self.epoch.encode(buffer)?;
         self.sender.encode(buffer)?;
-        encode_vec(VecSize::VecU32, buffer, &self.authenticated_data)?;
+        encode_bytes(VecSize::VecU32, buffer, &self.authenticated_data)?;
         self.content_type.encode(buffer)?;
         self.content.encode(buffer)?;
         self.signature.encode(buffer)?;
+        self.membership_tag.encode(buffer)?;
         Ok(())
     }
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
-        let group_id = GroupId::decode(cursor)?;
+        Ok(MLSPlaintext::decode_borrowed(cursor)?.into_owned())
+    }
+}
+
+impl TlsSerialization for MLSPlaintext {}
+
+impl StreamingCodec for MLSPlaintext {
+    fn encoded_len(&self) -> Result<usize, CodecError> {
+        let content_len = match &self.content {
+            MLSPlaintextContentType::Application(application_data) => {
+                1 + encoded_bytes_len(VecSize::VecU32, application_data.len())
+            }
+            content => content.encode_detached()?.len(),
+        };
+        Ok(encoded_bytes_len(VecSize::VecU8, self.group_id.value.len())
+            + GROUP_EPOCH_LENGTH
+            + SENDER_LENGTH
+            + encoded_bytes_len(VecSize::VecU32, self.authenticated_data.len())
+            + CONTENT_TYPE_LENGTH
+            + content_len
+            + encoded_bytes_len(VecSize::VecU16, self.signature.as_slice().len())
+            + self.membership_tag.encode_detached()?.len())
+    }
+
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), CodecError> {
+        let mut header = vec![];
+        self.group_id.encode(&mut header)?;
+        self.epoch.encode(&mut header)?;
+        self.sender.encode(&mut header)?;
+        write(writer, &header)?;
+        write_bytes(VecSize::VecU32, writer, &self.authenticated_data)?;
+        match &self.content {
+            MLSPlaintextContentType::Application(application_data) => {
+                write(
+                    writer,
+                    &[self.content_type as u8, ContentType::Application as u8],
+                )?;
+                write_bytes(VecSize::VecU32, writer, application_data)?;
+            }
+            content => {
+                let mut content_bytes = vec![self.content_type as u8];
+                content.encode(&mut content_bytes)?;
+                write(writer, &content_bytes)?;
+            }
+        }
+        write_bytes(VecSize::VecU16, writer, self.signature.as_slice())?;
+        write(writer, &self.membership_tag.encode_detached()?)
+    }
+}
+
+impl MLSPlaintext {
+    /// Decodes an `MLSPlaintext` without copying its group ID, authenticated
+    /// data, application data and signature.
+    pub fn decode_borrowed<'a>(cursor: &mut Cursor<'a>) -> Result<MLSPlaintextRef<'a>, CodecError> {
+        let group_id = decode_bytes(VecSize::VecU8, cursor)?;
         let epoch = GroupEpoch::decode(cursor)?;
         let sender = Sender::decode(cursor)?;
-        let authenticated_data = decode_vec(VecSize::VecU32, cursor)?;
+        let authenticated_data = decode_bytes(VecSize::VecU32, cursor)?;
         let content_type = ContentType::decode(cursor)?;
-        let content = MLSPlaintextContentType::decode(cursor)?;
-        let signature = Signature::decode(cursor)?;
+        let content = match ContentType::from(u8::decode(cursor)?) {
+            ContentType::Application => {
+                MLSPlaintextContentRef::Application(decode_bytes(VecSize::VecU32, cursor)?)
+            }
+            ContentType::Proposal => MLSPlaintextContentRef::Proposal(Proposal::decode(cursor)?),
+            ContentType::Commit => {
+                let commit = Commit::decode(cursor)?;
+                let confirmation_tag = ConfirmationTag::decode(cursor)?;
+                MLSPlaintextContentRef::Commit((commit, confirmation_tag))
+            }
+            _ => return Err(CodecError::DecodingError),
+        };
+        let signature = decode_bytes(VecSize::VecU16, cursor)?;
+        let membership_tag = Option::<MembershipTag>::decode(cursor)?;
 
-        Ok(MLSPlaintext {
+        Ok(MLSPlaintextRef {
             group_id,
             epoch,
             sender,
@@ -28,6 +97,7 @@ impl Codec for MLSPlaintext {
             content_type,
             content,
             signature,
+            membership_tag,
         })
     }
 }
@@ -37,20 +107,53 @@ impl Codec for MLSCiphertext {
         self.group_id.encode(buffer)?;
         self.epoch.encode(buffer)?;
         self.content_type.encode(buffer)?;
-        encode_vec(VecSize::VecU32, buffer, &self.authenticated_data)?;
-        encode_vec(VecSize::VecU8, buffer, &self.encrypted_sender_data)?;
-        encode_vec(VecSize::VecU32, buffer, &self.ciphertext)?;
+        encode_bytes(VecSize::VecU32, buffer, &self.authenticated_data)?;
+        encode_bytes(VecSize::VecU8, buffer, &self.encrypted_sender_data)?;
+        encode_bytes(VecSize::VecU32, buffer, &self.ciphertext)?;
         Ok(())
     }
 
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
-        let group_id = GroupId::decode(cursor)?;
+        Ok(MLSCiphertext::decode_borrowed(cursor)?.into_owned())
+    }
+}
+
+impl TlsSerialization for MLSCiphertext {}
+
+impl StreamingCodec for MLSCiphertext {
+    fn encoded_len(&self) -> Result<usize, CodecError> {
+        Ok(encoded_bytes_len(VecSize::VecU8, self.group_id.value.len())
+            + GROUP_EPOCH_LENGTH
+            + CONTENT_TYPE_LENGTH
+            + encoded_bytes_len(VecSize::VecU32, self.authenticated_data.len())
+            + encoded_bytes_len(VecSize::VecU8, self.encrypted_sender_data.len())
+            + encoded_bytes_len(VecSize::VecU32, self.ciphertext.len()))
+    }
+
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), CodecError> {
+        let mut header = vec![];
+        self.group_id.encode(&mut header)?;
+        self.epoch.encode(&mut header)?;
+        self.content_type.encode(&mut header)?;
+        write(writer, &header)?;
+        write_bytes(VecSize::VecU32, writer, &self.authenticated_data)?;
+        write_bytes(VecSize::VecU8, writer, &self.encrypted_sender_data)?;
+        write_bytes(VecSize::VecU32, writer, &self.ciphertext)
+    }
+}
+
+impl MLSCiphertext {
+    /// Decodes an `MLSCiphertext` without copying its variable-length fields.
+    pub fn decode_borrowed<'a>(
+        cursor: &mut Cursor<'a>,
+    ) -> Result<MLSCiphertextRef<'a>, CodecError> {
+        let group_id = decode_bytes(VecSize::VecU8, cursor)?;
         let epoch = GroupEpoch::decode(cursor)?;
         let content_type = ContentType::decode(cursor)?;
-        let authenticated_data = decode_vec(VecSize::VecU32, cursor)?;
-        let encrypted_sender_data = decode_vec(VecSize::VecU8, cursor)?;
-        let ciphertext = decode_vec(VecSize::VecU32, cursor)?;
-        Ok(MLSCiphertext {
+        let authenticated_data = decode_bytes(VecSize::VecU32, cursor)?;
+        let encrypted_sender_data = decode_bytes(VecSize::VecU8, cursor)?;
+        let ciphertext = decode_bytes(VecSize::VecU32, cursor)?;
+        Ok(MLSCiphertextRef {
             group_id,
             epoch,
             content_type,
@@ -61,6 +164,17 @@ impl Codec for MLSCiphertext {
     }
 }
 
+// Lengths of the fixed-size fields
+const GROUP_EPOCH_LENGTH: usize = 8;
+const SENDER_LENGTH: usize = 5;
+const CONTENT_TYPE_LENGTH: usize = 1;
+
+fn write<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), CodecError> {
+    writer
+        .write_all(bytes)
+        .map_err(|_| CodecError::EncodingError)
+}
+
 impl Codec for ContentType {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         (*self as u8).encode(buffer)?;
@@ -76,7 +190,7 @@ impl Codec for MLSPlaintextContentType {
         match self {
             MLSPlaintextContentType::Application(application_data) => {
                 ContentType::Application.encode(buffer)?;
-                encode_vec(VecSize::VecU32, buffer, application_data)?;
+                encode_bytes(VecSize::VecU32, buffer, application_data)?;
             }
             MLSPlaintextContentType::Proposal(proposal) => {
                 ContentType::Proposal.encode(buffer)?;
@@ -94,8 +208,10 @@ impl Codec for MLSPlaintextContentType {
         let content_type = ContentType::from(u8::decode(cursor)?);
         match content_type {
             ContentType::Application => {
-                let application_data = decode_vec(VecSize::VecU32, cursor)?;
-                Ok(MLSPlaintextContentType::Application(application_data))
+                let application_data = decode_bytes(VecSize::VecU32, cursor)?;
+                Ok(MLSPlaintextContentType::Application(
+                    application_data.to_vec(),
+                ))
             }
             ContentType::Proposal => {
                 let proposal = Proposal::decode(cursor)?;
@@ -126,6 +242,17 @@ impl Codec for MLSPlaintextTBS {
     }
 }
 
+impl Codec for MembershipTag {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        encode_vec(VecSize::VecU8, buffer, &self.0)?;
+        Ok(())
+    }
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        let inner = decode_vec(VecSize::VecU8, cursor)?;
+        Ok(MembershipTag(inner))
+    }
+}
+
 impl Codec for MLSSenderData {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         self.sender.encode(buffer)?;
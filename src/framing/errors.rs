@@ -6,13 +6,22 @@
 implement_error! {
     pub enum MLSPlaintextError {
         NotAnApplicationMessage = "The MLSPlaintext message is not an application message.",
+        InvalidMembershipTag = "The membership tag of the MLSPlaintext is missing or invalid.",
     }
 }
 
 implement_error! {
     pub enum MLSCiphertextError {
         InvalidContentType = "The MLSCiphertext has an invalid content type.",
+        InvalidSenderData = "The sender data of the MLSCiphertext couldn't be decrypted.",
         GenerationOutOfBound = "Couldn't find a ratcheting secret for the given sender and generation.",
         UnknownSender = "Sender is not part of the group",
+        InvalidPadding = "The padding of the MLSCiphertext contains non-zero bytes.",
+        WrongEpoch = "The MLSCiphertext is from an epoch that is neither the current one nor a retained past epoch.",
+        Replay = "An MLSCiphertext with the same sender and generation was already decrypted in this epoch.",
+        DecryptionError = "The content of the MLSCiphertext couldn't be decrypted.",
+        DecodingError = "The decrypted content of the MLSCiphertext couldn't be decoded.",
+        InvalidSignature = "The signature of the decrypted MLSPlaintext is invalid.",
+        SecretReuse = "The ratcheting secret for the given sender and generation was already used.",
     }
 }
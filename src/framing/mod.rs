@@ -11,11 +11,15 @@ pub(crate) use serde::{Deserialize, Serialize};
 pub mod ciphertext;
 pub mod codec;
 pub mod errors;
+pub mod padding;
 pub mod plaintext;
+pub(crate) mod replay_cache;
 pub mod sender;
 pub use ciphertext::*;
 pub(crate) use errors::*;
+pub use padding::*;
 pub use plaintext::*;
+pub(crate) use replay_cache::*;
 use sender::*;
 
 #[cfg(test)]
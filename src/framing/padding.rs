@@ -0,0 +1,125 @@
+//! # Padding
+//!
+//! The content of an `MLSCiphertext` is padded with zero bytes before it is
+//! encrypted to hide the length of the plaintext. The [`PaddingPolicy`] of a
+//! group determines how many padding bytes are added to application and
+//! handshake messages. The length that is padded is the length of the AEAD
+//! ciphertext, i.e. the encoded content and signature, the length prefix of the
+//! padding and the AEAD tag.
+//!
+//! Since the padding is encoded as `opaque padding<0..2^16-1>`, at most
+//! `u16::MAX` padding bytes are added. Block sizes above `2^16` and powers of
+//! two for ciphertexts longer than `2^16` bytes therefore aren't always
+//! reached.
+//!
+//! The padding policy is persisted with the group. A [`CustomPadding`]
+//! strategy can be set through a [`SharedPadding`] handle instead. Like the
+//! clock of a group, it isn't persisted and has to be set again after loading
+//! a group. Until then, the persisted padding policy is used.
+
+use super::*;
+
+use std::sync::Arc;
+
+/// The padding strategy of a group.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PaddingPolicy {
+    /// Don't pad ciphertexts.
+    None,
+    /// Pad ciphertexts to a multiple of the block size. A block size of `0`
+    /// disables padding. At most `u16::MAX` padding bytes are added, so block
+    /// sizes above `2^16` are not always reached.
+    BlockSize(u32),
+    /// Pad ciphertexts to the next power of two. At most `u16::MAX` padding
+    /// bytes are added, so ciphertexts longer than `2^16` bytes are not always
+    /// padded to a power of two.
+    PowerOfTwo,
+}
+
+impl PaddingPolicy {
+    /// Returns the number of padding bytes for a ciphertext of the given
+    /// unpadded length. The result is capped at `u16::MAX`.
+    pub fn padding_length(&self, length: usize) -> usize {
+        let padding_length = match self {
+            PaddingPolicy::None => 0,
+            PaddingPolicy::BlockSize(0) => 0,
+            PaddingPolicy::BlockSize(block_size) => {
+                let block_size = *block_size as usize;
+                (block_size - (length % block_size)) % block_size
+            }
+            PaddingPolicy::PowerOfTwo => length.next_power_of_two() - length,
+        };
+        padding_length.min(u16::MAX as usize)
+    }
+}
+
+/// A custom padding strategy, e.g. with buckets tuned to the message sizes of
+/// an application.
+pub trait CustomPadding {
+    /// Returns the number of padding bytes for a ciphertext of the given
+    /// unpadded length. Values above `u16::MAX` are capped.
+    fn padding_length(&self, length: usize) -> usize;
+}
+
+/// A shared handle to a [`CustomPadding`] strategy that is stored in the
+/// configuration of a group. If it is set, it is used instead of the
+/// [`PaddingPolicy`] of the group.
+///
+/// The strategy is not persisted with the group and has to be set again after
+/// loading a group.
+#[derive(Clone)]
+pub struct SharedPadding(Arc<dyn CustomPadding + Send + Sync>);
+
+impl SharedPadding {
+    /// Create a new handle to the `padding` strategy.
+    pub fn new<P: CustomPadding + Send + Sync + 'static>(padding: P) -> Self {
+        Self(Arc::new(padding))
+    }
+
+    /// Returns the number of padding bytes for a ciphertext of the given
+    /// unpadded length. The result is capped at `u16::MAX`.
+    pub fn padding_length(&self, length: usize) -> usize {
+        self.0.padding_length(length).min(u16::MAX as usize)
+    }
+}
+
+impl std::fmt::Debug for SharedPadding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SharedPadding")
+    }
+}
+
+impl PartialEq for SharedPadding {
+    /// Two handles are equal if they point to the same strategy.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for PaddingPolicy {
+    fn default() -> Self {
+        PaddingPolicy::BlockSize(10)
+    }
+}
+
+impl Codec for PaddingPolicy {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self {
+            PaddingPolicy::None => 0u8.encode(buffer)?,
+            PaddingPolicy::BlockSize(block_size) => {
+                1u8.encode(buffer)?;
+                block_size.encode(buffer)?;
+            }
+            PaddingPolicy::PowerOfTwo => 2u8.encode(buffer)?,
+        }
+        Ok(())
+    }
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        match u8::decode(cursor)? {
+            0 => Ok(PaddingPolicy::None),
+            1 => Ok(PaddingPolicy::BlockSize(u32::decode(cursor)?)),
+            2 => Ok(PaddingPolicy::PowerOfTwo),
+            _ => Err(CodecError::DecodingError),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use super::*;
 
+use subtle::ConstantTimeEq;
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct MLSPlaintext {
     pub(crate) group_id: GroupId,
@@ -9,6 +11,7 @@ pub struct MLSPlaintext {
     pub(crate) content_type: ContentType,
     pub(crate) content: MLSPlaintextContentType,
     pub(crate) signature: Signature,
+    pub(crate) membership_tag: Option<MembershipTag>,
 }
 
 impl MLSPlaintext {
@@ -31,6 +34,7 @@ impl MLSPlaintext {
             content_type: ContentType::from(&content),
             content,
             signature: Signature::new_empty(),
+            membership_tag: None,
         };
         let serialized_context = context.encode_detached().unwrap();
         mls_plaintext.sign(credential_bundle, Some(serialized_context));
@@ -64,6 +68,39 @@ impl MLSPlaintext {
         let signature_input = MLSPlaintextTBS::new_from(&self, serialized_context_option);
         signature_input.verify(credential, &self.signature)
     }
+
+    /// Compute the membership tag of this `MLSPlaintext` with the membership
+    /// key of the current epoch and add it to the message.
+    pub(crate) fn set_membership_tag(
+        &mut self,
+        ciphersuite: &Ciphersuite,
+        serialized_context: Vec<u8>,
+        membership_key: &Secret,
+    ) -> Result<(), CodecError> {
+        let tbm = MLSPlaintextTBM::new_from(&self, serialized_context)?;
+        self.membership_tag = Some(MembershipTag::new(ciphersuite, membership_key, &tbm));
+        Ok(())
+    }
+
+    /// Verify the membership tag of this `MLSPlaintext` with the membership
+    /// key of the current epoch. Fails if the tag is missing or invalid.
+    pub(crate) fn verify_membership_tag(
+        &self,
+        ciphersuite: &Ciphersuite,
+        serialized_context: Vec<u8>,
+        membership_key: &Secret,
+    ) -> Result<(), MLSPlaintextError> {
+        let membership_tag = match &self.membership_tag {
+            Some(membership_tag) => membership_tag,
+            None => return Err(MLSPlaintextError::InvalidMembershipTag),
+        };
+        let tbm = MLSPlaintextTBM::new_from(&self, serialized_context)
+            .map_err(|_| MLSPlaintextError::InvalidMembershipTag)?;
+        if !membership_tag.verify(ciphersuite, membership_key, &tbm) {
+            return Err(MLSPlaintextError::InvalidMembershipTag);
+        }
+        Ok(())
+    }
     pub fn as_application_message(&self) -> Result<&[u8], MLSPlaintextError> {
         match &self.content {
             MLSPlaintextContentType::Application(message) => Ok(message),
@@ -87,6 +124,90 @@ impl MLSPlaintext {
     }
 }
 
+/// A borrowed view of an encoded `MLSPlaintext`, created with
+/// [`MLSPlaintext::decode_borrowed()`]. The group ID, the authenticated data,
+/// the application data and the signature point into the encoded message.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MLSPlaintextRef<'a> {
+    pub(crate) group_id: &'a [u8],
+    pub(crate) epoch: GroupEpoch,
+    pub(crate) sender: Sender,
+    pub(crate) authenticated_data: &'a [u8],
+    pub(crate) content_type: ContentType,
+    pub(crate) content: MLSPlaintextContentRef<'a>,
+    pub(crate) signature: &'a [u8],
+    pub(crate) membership_tag: Option<MembershipTag>,
+}
+
+/// The content of an `MLSPlaintextRef`. Only application data is borrowed,
+/// proposals and commits are decoded.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, Clone)]
+pub enum MLSPlaintextContentRef<'a> {
+    Application(&'a [u8]),
+    Proposal(Proposal),
+    Commit((Commit, ConfirmationTag)),
+}
+
+impl<'a> MLSPlaintextRef<'a> {
+    /// Get the group ID.
+    pub fn group_id(&self) -> &'a [u8] {
+        self.group_id
+    }
+
+    /// Get the epoch.
+    pub fn epoch(&self) -> &GroupEpoch {
+        &self.epoch
+    }
+
+    /// Get the sender leaf index of this message.
+    pub fn sender(&self) -> LeafIndex {
+        self.sender.to_leaf_index()
+    }
+
+    /// Get the authenticated data.
+    pub fn authenticated_data(&self) -> &'a [u8] {
+        self.authenticated_data
+    }
+
+    /// Returns a reference to the `content` field
+    pub fn content(&self) -> &MLSPlaintextContentRef<'a> {
+        &self.content
+    }
+
+    pub fn as_application_message(&self) -> Result<&'a [u8], MLSPlaintextError> {
+        match self.content {
+            MLSPlaintextContentRef::Application(message) => Ok(message),
+            _ => Err(MLSPlaintextError::NotAnApplicationMessage),
+        }
+    }
+
+    /// Copy the borrowed values into an `MLSPlaintext`.
+    pub fn into_owned(self) -> MLSPlaintext {
+        let content = match self.content {
+            MLSPlaintextContentRef::Application(message) => {
+                MLSPlaintextContentType::Application(message.to_vec())
+            }
+            MLSPlaintextContentRef::Proposal(proposal) => {
+                MLSPlaintextContentType::Proposal(proposal)
+            }
+            MLSPlaintextContentRef::Commit(commit) => MLSPlaintextContentType::Commit(commit),
+        };
+        MLSPlaintext {
+            group_id: GroupId {
+                value: self.group_id.to_vec(),
+            },
+            epoch: self.epoch,
+            sender: self.sender,
+            authenticated_data: self.authenticated_data.to_vec(),
+            content_type: self.content_type,
+            content,
+            signature: Signature::from_bytes(self.signature),
+            membership_tag: self.membership_tag,
+        }
+    }
+}
+
 #[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum ContentType {
@@ -177,6 +298,62 @@ impl MLSPlaintextTBS {
     }
 }
 
+/// The input to the membership tag of an `MLSPlaintext`.
+///
+/// ```text
+/// struct {
+///   MLSPlaintextTBS tbs;
+///   opaque signature<0..2^16-1>;
+/// } MLSPlaintextTBM;
+/// ```
+pub(crate) struct MLSPlaintextTBM {
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl MLSPlaintextTBM {
+    pub(crate) fn new_from(
+        mls_plaintext: &MLSPlaintext,
+        serialized_context: Vec<u8>,
+    ) -> Result<Self, CodecError> {
+        let mut bytes =
+            MLSPlaintextTBS::new_from(mls_plaintext, Some(serialized_context)).encode_detached()?;
+        mls_plaintext.signature.encode(&mut bytes)?;
+        Ok(MLSPlaintextTBM { bytes })
+    }
+}
+
+/// The membership tag authenticates an `MLSPlaintext` that is sent by a
+/// member as coming from a member of the group in the current epoch.
+///
+/// ```text
+/// membership_tag = MAC(membership_key, MLSPlaintextTBM);
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct MembershipTag(pub(crate) Vec<u8>);
+
+impl MembershipTag {
+    /// Compute the membership tag over the `MLSPlaintextTBM`.
+    pub(crate) fn new(
+        ciphersuite: &Ciphersuite,
+        membership_key: &Secret,
+        tbm: &MLSPlaintextTBM,
+    ) -> Self {
+        MembershipTag(ciphersuite.mac(membership_key, &tbm.bytes))
+    }
+
+    /// Verify the membership tag over the `MLSPlaintextTBM`. The tags are
+    /// compared in constant time.
+    pub(crate) fn verify(
+        &self,
+        ciphersuite: &Ciphersuite,
+        membership_key: &Secret,
+        tbm: &MLSPlaintextTBM,
+    ) -> bool {
+        let expected_tag = ciphersuite.mac(membership_key, &tbm.bytes);
+        self.0.ct_eq(&expected_tag).into()
+    }
+}
+
 pub(crate) struct MLSPlaintextCommitContent {
     pub(crate) group_id: GroupId,
     pub(crate) epoch: GroupEpoch,
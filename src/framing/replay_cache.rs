@@ -0,0 +1,66 @@
+//! # Replay cache
+//!
+//! Within an epoch, every `MLSCiphertext` is encrypted with the key of a
+//! unique combination of sender, ratchet (handshake or application) and
+//! generation. The [`ReplayCache`] remembers this combination for all
+//! ciphertexts that were decrypted in an epoch, so that a ciphertext that is
+//! delivered again is rejected with `MLSCiphertextError::Replay` instead of
+//! being processed twice. The cache is persisted with the group, so replays are
+//! also detected after a group was saved and loaded.
+//!
+//! The cache is bounded by the out-of-order tolerance of the `SenderRatchet`s.
+//! For every sender and ratchet, only the generations within the tolerance of
+//! the newest decrypted generation are kept. Older generations are rejected by
+//! the `SenderRatchet` anyway.
+
+use super::*;
+
+use std::collections::HashSet;
+
+/// The senders and generations of all decrypted ciphertexts of an epoch.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub(crate) struct ReplayCache {
+    // (sender, is handshake message, generation)
+    seen: HashSet<(LeafIndex, bool, u32)>,
+}
+
+impl ReplayCache {
+    /// Returns `true` if a ciphertext with this content type was already
+    /// decrypted for the sender and generation.
+    pub(crate) fn contains(
+        &self,
+        sender: LeafIndex,
+        content_type: ContentType,
+        generation: u32,
+    ) -> bool {
+        self.seen
+            .contains(&(sender, content_type.is_handshake_message(), generation))
+    }
+
+    /// Remembers a decrypted ciphertext. Generations of the same sender and
+    /// ratchet that are no longer within the `out_of_order_tolerance` of the
+    /// newest one are forgotten.
+    pub(crate) fn insert(
+        &mut self,
+        sender: LeafIndex,
+        content_type: ContentType,
+        generation: u32,
+        out_of_order_tolerance: u32,
+    ) {
+        let is_handshake_message = content_type.is_handshake_message();
+        self.seen.insert((sender, is_handshake_message, generation));
+        let newest_generation = self
+            .seen
+            .iter()
+            .filter(|(s, h, _)| *s == sender && *h == is_handshake_message)
+            .map(|(_, _, g)| *g)
+            .max()
+            .unwrap_or(generation);
+        self.seen.retain(|(s, h, g)| {
+            *s != sender
+                || *h != is_handshake_message
+                || newest_generation - *g < out_of_order_tolerance
+        });
+    }
+}
@@ -22,12 +22,14 @@ fn codec() {
             content_type: ContentType::Application,
             content: MLSPlaintextContentType::Application(vec![4, 5, 6]),
             signature: Signature::new_empty(),
+            membership_tag: None,
         };
         let context = GroupContext {
             group_id: GroupId::random(),
             epoch: GroupEpoch(1u64),
             tree_hash: vec![],
             confirmed_transcript_hash: vec![],
+            extensions: vec![],
         };
         let serialized_context = context.encode_detached().unwrap();
         let signature_input = MLSPlaintextTBS::new_from(&orig, Some(serialized_context));
@@ -65,12 +67,14 @@ fn context_presence() {
             content_type: ContentType::Application,
             content: MLSPlaintextContentType::Application(vec![4, 5, 6]),
             signature: Signature::new_empty(),
+            membership_tag: None,
         };
         let context = GroupContext {
             group_id: GroupId::random(),
             epoch: GroupEpoch(1u64),
             tree_hash: vec![],
             confirmed_transcript_hash: vec![],
+            extensions: vec![],
         };
         let serialized_context = context.encode_detached().unwrap();
         let signature_input = MLSPlaintextTBS::new_from(&orig, Some(serialized_context.clone()));
@@ -138,11 +142,9 @@ fn unknown_sender() {
         .unwrap();
 
         // Alice adds Bob
-        let bob_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            bob_key_package.clone(),
-        );
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
 
         let (commit, _welcome_option, _kpb_option) = group_alice
             .create_commit(
@@ -160,11 +162,13 @@ fn unknown_sender() {
 
         // Alice adds Charlie
 
-        let charlie_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            charlie_key_package.clone(),
-        );
+        let charlie_add_proposal = group_alice
+            .create_add_proposal(
+                group_aad,
+                &alice_credential_bundle,
+                charlie_key_package.clone(),
+            )
+            .expect("Could not create proposal.");
 
         let (commit, welcome_option, _kpb_option) = group_alice
             .create_commit(
@@ -275,3 +279,120 @@ fn unknown_sender() {
         );
     }
 }
+
+/// This tests the padding lengths of the padding policies
+#[test]
+fn padding_length() {
+    assert_eq!(PaddingPolicy::None.padding_length(33), 0);
+    assert_eq!(PaddingPolicy::BlockSize(0).padding_length(33), 0);
+    assert_eq!(PaddingPolicy::BlockSize(10).padding_length(33), 7);
+    assert_eq!(PaddingPolicy::BlockSize(10).padding_length(40), 0);
+    assert_eq!(PaddingPolicy::PowerOfTwo.padding_length(33), 31);
+    assert_eq!(PaddingPolicy::PowerOfTwo.padding_length(64), 0);
+    // The padding is limited by the size of its length prefix
+    assert_eq!(
+        PaddingPolicy::PowerOfTwo.padding_length((1 << 17) + 1),
+        u16::MAX as usize
+    );
+    assert_eq!(
+        PaddingPolicy::BlockSize(1 << 20).padding_length(1),
+        u16::MAX as usize
+    );
+
+    let policy = PaddingPolicy::BlockSize(32);
+    let encoded = policy.encode_detached().unwrap();
+    assert_eq!(
+        PaddingPolicy::decode(&mut Cursor::new(&encoded)).unwrap(),
+        policy
+    );
+}
+
+/// This tests that the replay cache only keeps the generations within the
+/// out-of-order tolerance
+#[test]
+fn replay_cache_is_bounded() {
+    let alice = LeafIndex::from(0u32);
+    let bob = LeafIndex::from(1u32);
+    let mut replay_cache = ReplayCache::default();
+    replay_cache.insert(bob, ContentType::Application, 0, 3);
+    replay_cache.insert(alice, ContentType::Commit, 0, 3);
+    for generation in 0..10 {
+        replay_cache.insert(alice, ContentType::Application, generation, 3);
+    }
+    for generation in 7..10 {
+        assert!(replay_cache.contains(alice, ContentType::Application, generation));
+    }
+    assert!(!replay_cache.contains(alice, ContentType::Application, 6));
+    // Other senders and ratchets are not affected
+    assert!(replay_cache.contains(bob, ContentType::Application, 0));
+    assert!(replay_cache.contains(alice, ContentType::Commit, 0));
+}
+
+/// This tests the computation and verification of the membership tag
+#[test]
+fn membership_tag() {
+    use crate::ciphersuite::*;
+    use crate::config::*;
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let credential_bundle =
+            CredentialBundle::new(vec![7, 8, 9], CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let context = GroupContext {
+            group_id: GroupId::random(),
+            epoch: GroupEpoch(1u64),
+            tree_hash: vec![],
+            confirmed_transcript_hash: vec![],
+            extensions: vec![],
+        };
+        let serialized_context = context.encode_detached().unwrap();
+        let membership_key = Secret::random(ciphersuite.hash_length());
+        let mut mls_plaintext = MLSPlaintext::new(
+            LeafIndex::from(2u32),
+            &[1, 2, 3],
+            MLSPlaintextContentType::Application(vec![4, 5, 6]),
+            &credential_bundle,
+            &context,
+        );
+
+        // A missing membership tag is invalid
+        assert_eq!(
+            mls_plaintext.verify_membership_tag(
+                ciphersuite,
+                serialized_context.clone(),
+                &membership_key
+            ),
+            Err(MLSPlaintextError::InvalidMembershipTag)
+        );
+
+        mls_plaintext
+            .set_membership_tag(ciphersuite, serialized_context.clone(), &membership_key)
+            .unwrap();
+        assert!(mls_plaintext
+            .verify_membership_tag(ciphersuite, serialized_context.clone(), &membership_key)
+            .is_ok());
+
+        // The membership tag survives encoding
+        let encoded = mls_plaintext.encode_detached().unwrap();
+        let decoded = MLSPlaintext::decode(&mut Cursor::new(&encoded)).unwrap();
+        assert_eq!(decoded, mls_plaintext);
+
+        // A different membership key is rejected
+        let other_membership_key = Secret::random(ciphersuite.hash_length());
+        assert_eq!(
+            mls_plaintext.verify_membership_tag(
+                ciphersuite,
+                serialized_context.clone(),
+                &other_membership_key
+            ),
+            Err(MLSPlaintextError::InvalidMembershipTag)
+        );
+
+        // Modifications of the message are rejected
+        mls_plaintext.authenticated_data = vec![3, 2, 1];
+        assert_eq!(
+            mls_plaintext.verify_membership_tag(ciphersuite, serialized_context, &membership_key),
+            Err(MLSPlaintextError::InvalidMembershipTag)
+        );
+    }
+}
@@ -1,11 +1,12 @@
 //! # MLS Group errors
 //!
-//! `WelcomeError`, `ApplyCommitError`, `DecryptionError`, and
-//! `CreateCommitError`.
+//! `WelcomeError`, `ApplyCommitError`, `DecryptionError`,
+//! `CreateCommitError`, and `StorageError`.
 
 use crate::ciphersuite::CryptoError;
 use crate::config::ConfigError;
 use crate::framing::errors::MLSCiphertextError;
+use crate::key_packages::KeyPackageError;
 use crate::messages::errors::ProposalQueueError;
 use crate::tree::{secret_tree::SecretTypeError, TreeError};
 
@@ -27,6 +28,8 @@ implement_error! {
             "See [`SecretTypeError`](`crate::tree::secret_tree::SecretTypeError`) for details",
             ProposalQueueError(ProposalQueueError) =
         "See [`ProposalQueueError`](`crate::messages::errors::ProposalQueueError`) for details",
+        KeyPackageError(KeyPackageError) =
+            "See [`KeyPackageError`](`crate::key_packages::KeyPackageError`) for details",
     }
 }
 
@@ -74,12 +77,18 @@ implement_error! {
                 "Tried to apply a commit to a group we are not a part of.",
             PathKeyPackageVerificationFailure =
                 "Unable to verify the key package signature.",
+            ProposalKeyPackageVerificationFailure =
+                "The key package of an Add or Update proposal is invalid or expired.",
+            CredentialIdentityMismatch =
+                "An Update proposal or the path of the Commit changes the identity of a member.",
             NoParentHashExtension =
                 "Parent hash extension is missing.",
             ParentHashMismatch =
                 "Parent hash values don't match.",
             PlaintextSignatureFailure =
                 "MLSPlaintext signature is invalid.",
+            InvalidMembershipTag =
+                "The membership tag of the Commit or of a proposal is missing or invalid.",
             RequiredPathNotFound =
                 "Unable to determine commit path.",
             ConfirmationTagMismatch =
@@ -111,5 +120,24 @@ implement_error! {
     pub enum ExporterError {
         KeyLengthTooLong =
             "The requested key length is not supported (too large).",
+        KeyLengthZero =
+            "The requested key length is zero.",
+        UnknownEpoch =
+            "The epoch is neither the current one nor a retained past epoch.",
+    }
+}
+
+implement_error! {
+    pub enum StorageError {
+        InvalidKeyLength = "The storage key doesn't have the correct length.",
+        InvalidHeader = "The header of the sealed state is malformed.",
+        UnsupportedVersion = "The version of the sealed state is not supported.",
+        EncryptionError = "The state could not be sealed.",
+        DecryptionError =
+            "The sealed state could not be authenticated with the storage key.",
+        GroupIdMismatch = "The sealed state belongs to a different group.",
+        EpochRollback = "The sealed state is older than the expected epoch.",
+        StateTooOld =
+            "The state was persisted by an older version that didn't store the membership key.",
     }
 }
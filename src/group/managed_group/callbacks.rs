@@ -48,13 +48,26 @@ use crate::group::*;
 ///     added_member: &Credential,
 /// );
 /// pub type MemberRemoved = fn(managed_group: &ManagedGroup, aad: &[u8], removal: &Removal);
-/// pub type MemberUpdated =
-///     fn(managed_group: &ManagedGroup, aad: &[u8], updated_member: &Credential);
+/// pub type MemberUpdated = fn(
+///     managed_group: &ManagedGroup,
+///     aad: &[u8],
+///     old_credential: &Credential,
+///     new_credential: &Credential,
+/// );
+/// pub type UserAdded =
+///     fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, user_id: &[u8]);
+/// pub type UserRemoved =
+///     fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, user_id: &[u8]);
 /// pub type AppMessageReceived =
 ///     fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, message: &[u8]);
 /// pub type InvalidMessageReceived = fn(managed_group: &ManagedGroup, error: InvalidMessageError);
 /// pub type ErrorOccured = fn(managed_group: &ManagedGroup, error: ManagedGroupError);
 /// ```
+///
+/// The user events aggregate the member events of a Commit per user (see
+/// [`KeyPackage::user_id()`](crate::prelude::KeyPackage::user_id())).
+/// `UserAdded` is called when the first device of a user joined the group and
+/// `UserRemoved` when the last device of a user left the group.
 #[derive(Default, Copy, Clone)]
 pub struct ManagedGroupCallbacks {
     // Validator functions
@@ -66,6 +79,8 @@ pub struct ManagedGroupCallbacks {
     pub(crate) member_added: Option<MemberAdded>,
     pub(crate) member_removed: Option<MemberRemoved>,
     pub(crate) member_updated: Option<MemberUpdated>,
+    pub(crate) user_added: Option<UserAdded>,
+    pub(crate) user_removed: Option<UserRemoved>,
     pub(crate) app_message_received: Option<AppMessageReceived>,
     pub(crate) invalid_message_received: Option<InvalidMessageReceived>,
     pub(crate) error_occured: Option<ErrorOccured>,
@@ -80,6 +95,8 @@ impl<'a> ManagedGroupCallbacks {
             member_added: None,
             member_removed: None,
             member_updated: None,
+            user_added: None,
+            user_removed: None,
             app_message_received: None,
             invalid_message_received: None,
             error_occured: None,
@@ -110,11 +127,23 @@ impl<'a> ManagedGroupCallbacks {
         self.member_removed = Some(member_removed);
         self
     }
-    /// Event listener function for UpdateProposals
+    /// Event listener function for UpdateProposals. It gets the credential of
+    /// the member before and after the update, which differ if the member
+    /// rotated its credential.
     pub fn with_member_updated(mut self, member_updated: MemberUpdated) -> Self {
         self.member_updated = Some(member_updated);
         self
     }
+    /// Event listener function for users that joined the group
+    pub fn with_user_added(mut self, user_added: UserAdded) -> Self {
+        self.user_added = Some(user_added);
+        self
+    }
+    /// Event listener function for users that left the group
+    pub fn with_user_removed(mut self, user_removed: UserRemoved) -> Self {
+        self.user_removed = Some(user_removed);
+        self
+    }
     /// Event listener function for application messages
     pub fn with_app_message_received(mut self, app_message_received: AppMessageReceived) -> Self {
         self.app_message_received = Some(app_message_received);
@@ -195,7 +224,16 @@ pub type AutoSave = fn(managed_group: &ManagedGroup);
 pub type MemberAdded =
     fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, added_member: &Credential);
 pub type MemberRemoved = fn(managed_group: &ManagedGroup, aad: &[u8], removal: &Removal);
-pub type MemberUpdated = fn(managed_group: &ManagedGroup, aad: &[u8], updated_member: &Credential);
+pub type MemberUpdated = fn(
+    managed_group: &ManagedGroup,
+    aad: &[u8],
+    old_credential: &Credential,
+    new_credential: &Credential,
+);
+pub type UserAdded =
+    fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, user_id: &[u8]);
+pub type UserRemoved =
+    fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, user_id: &[u8]);
 pub type AppMessageReceived =
     fn(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, message: &[u8]);
 pub type InvalidMessageReceived = fn(managed_group: &ManagedGroup, error: InvalidMessageError);
@@ -0,0 +1,171 @@
+use crate::framing::*;
+use crate::group::*;
+use crate::key_packages::{KeyPackage, KeyPackageBundle};
+use crate::messages::{proposals::*, Welcome};
+
+/// A builder for a single Commit that covers several operations at once.
+///
+/// A `CommitBuilder` is created with [`ManagedGroup::commit()`]. All operations
+/// added to the builder, as well as the pending proposals of the group, are
+/// covered by one Commit when [`finish()`](`CommitBuilder::finish()`) is
+/// called. This allows to apply a complete change of the group membership in a
+/// single epoch.
+///
+/// ```ignore
+/// let (mls_messages, welcome_option) = managed_group
+///     .commit()
+///     .add(key_package)
+///     .remove(leaf_index)
+///     .update(None)
+///     .finish()?;
+/// ```
+///
+/// Pre-shared keys are not supported yet (see #141), a Commit that includes
+/// one with [`psk()`](`CommitBuilder::psk()`) fails.
+pub struct CommitBuilder<'a, 'b> {
+    managed_group: &'b mut ManagedGroup<'a>,
+    key_packages: Vec<KeyPackage>,
+    removed_members: Vec<usize>,
+    self_update: bool,
+    key_package_bundle: Option<KeyPackageBundle>,
+    psk_ids: Vec<Vec<u8>>,
+}
+
+impl<'a, 'b> CommitBuilder<'a, 'b> {
+    pub(crate) fn new(managed_group: &'b mut ManagedGroup<'a>) -> Self {
+        Self {
+            managed_group,
+            key_packages: vec![],
+            removed_members: vec![],
+            self_update: false,
+            key_package_bundle: None,
+            psk_ids: vec![],
+        }
+    }
+
+    /// Adds the member with the given `KeyPackage`. Key packages for other
+    /// ciphersuites than the group's are ignored as in
+    /// [`ManagedGroup::add_members()`].
+    pub fn add(mut self, key_package: KeyPackage) -> Self {
+        self.key_packages.push(key_package);
+        self
+    }
+
+    /// Removes the member with the given leaf index (see
+    /// [`ManagedGroup::members()`]).
+    pub fn remove(mut self, member: usize) -> Self {
+        self.removed_members.push(member);
+        self
+    }
+
+    /// Updates the own leaf node.
+    ///
+    /// A [`KeyPackageBundle`](crate::prelude::KeyPackageBundle) can optionally
+    /// be provided. If not, a new one will be created on the fly.
+    pub fn update(mut self, key_package_bundle_option: Option<KeyPackageBundle>) -> Self {
+        self.self_update = true;
+        self.key_package_bundle = key_package_bundle_option;
+        self
+    }
+
+    /// Includes the pre-shared key with the given ID.
+    ///
+    /// Pre-shared keys are not supported yet (see #141), so
+    /// [`finish()`](`CommitBuilder::finish()`) returns
+    /// [`UnsupportedError::PreSharedKeys`] if this was called.
+    pub fn psk(mut self, psk_id: &[u8]) -> Self {
+        self.psk_ids.push(psk_id.to_vec());
+        self
+    }
+
+    /// Creates the Commit over all operations of the builder and all pending
+    /// proposals.
+    ///
+    /// If successful, it returns a `Vec` of
+    /// [`MLSMessage`](crate::prelude::MLSMessage) and an optional
+    /// [`Welcome`](crate::prelude::Welcome) message if members were added.
+    pub fn finish(self) -> Result<(Vec<MLSMessage>, Option<Welcome>), ManagedGroupError> {
+        let managed_group = self.managed_group;
+        if !managed_group.active {
+            return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
+        }
+        if !self.psk_ids.is_empty() {
+            return Err(ManagedGroupError::Unsupported(
+                UnsupportedError::PreSharedKeys,
+            ));
+        }
+
+        // Create add and remove proposals by value
+        let mut proposals = managed_group
+            .select_key_packages(&self.key_packages)?
+            .into_iter()
+            .map(|key_package| Proposal::Add(AddProposal { key_package }))
+            .chain(self.removed_members.iter().map(|member| {
+                Proposal::Remove(RemoveProposal {
+                    removed: *member as u32,
+                })
+            }))
+            .collect::<Vec<Proposal>>();
+        // Owners drop the roles of removed members in the same Commit
+        if let Some(pruned_roles_proposal) =
+            managed_group.pruned_roles_proposal(&self.removed_members)
+        {
+            proposals.push(pruned_roles_proposal);
+        }
+        managed_group.check_own_proposals(&proposals)?;
+        let proposals_by_value = &proposals.iter().collect::<Vec<&Proposal>>();
+
+        // If a KeyPackageBundle was provided, create an UpdateProposal
+        let mut plaintext_messages = if let Some(key_package_bundle) = self.key_package_bundle {
+            let update_proposal = managed_group.group.create_update_proposal(
+                &managed_group.aad,
+                &managed_group.credential_bundle,
+                key_package_bundle.key_package().clone(),
+            );
+            managed_group.own_kpbs.push(key_package_bundle);
+            vec![update_proposal]
+        } else {
+            vec![]
+        };
+
+        // Include pending proposals
+        let proposals_by_reference: Vec<&MLSPlaintext> = managed_group
+            .pending_proposals
+            .iter()
+            .chain(plaintext_messages.iter())
+            .collect();
+
+        // Create Commit over all proposals
+        let (commit, welcome_option, kpb_option) = managed_group.group.create_commit(
+            &managed_group.aad,
+            &managed_group.credential_bundle,
+            &proposals_by_reference,
+            proposals_by_value,
+            self.self_update,
+        )?;
+
+        // Add the Commit message to the other pending messages
+        plaintext_messages.push(commit);
+
+        // If it was a full Commit, we have to save the KeyPackageBundle for later.
+        // Updates and removals always require a full Commit.
+        match kpb_option {
+            Some(kpb) => managed_group.own_kpbs.push(kpb),
+            None if self.self_update || !self.removed_members.is_empty() => {
+                return Err(ManagedGroupError::LibraryError(
+                    "We didn't get a key package for a full commit.".into(),
+                ))
+            }
+            None => {}
+        }
+
+        // Convert MLSPlaintext messages to MLSMessage and encrypt them if required by
+        // the configuration
+        let mls_messages = managed_group.plaintext_to_mls_messages(plaintext_messages)?;
+
+        // Since the state of the group was changed, call the auto-save function
+        managed_group.auto_save();
+
+        Ok((mls_messages, welcome_option))
+    }
+}
@@ -1,4 +1,7 @@
 use super::*;
+use crate::clock::SharedClock;
+use crate::framing::{PaddingPolicy, SharedPadding};
+use crate::tree::SenderRatchetConfiguration;
 
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +21,31 @@ pub struct ManagedGroupConfig {
     pub(crate) handshake_message_format: HandshakeMessageFormat,
     /// Defines the update policy
     pub(crate) update_policy: UpdatePolicy,
+    /// Defines whether new groups are created with the built-in role policy
+    #[serde(default)]
+    pub(crate) role_policy: bool,
+    /// Defines the out-of-order tolerance and maximum forward distance for
+    /// incoming messages
+    #[serde(default)]
+    pub(crate) sender_ratchet_configuration: SenderRatchetConfiguration,
+    /// Defines how application and handshake messages are padded
+    #[serde(default)]
+    pub(crate) padding_policy: PaddingPolicy,
+    /// Defines a custom padding strategy that is used instead of the padding
+    /// policy
+    #[serde(skip)]
+    pub(crate) custom_padding: Option<SharedPadding>,
+    /// Defines the number of past epochs for which late application messages
+    /// can be decrypted
+    #[serde(default)]
+    pub(crate) past_epochs: u32,
+    /// Defines the maximum age in seconds of retained past epochs
+    #[serde(default)]
+    pub(crate) past_epochs_max_age: u64,
+    /// Defines the clock used to validate key packages and to expire past
+    /// epochs
+    #[serde(skip)]
+    pub(crate) clock: SharedClock,
     /// Callbacks
     #[serde(skip)]
     pub(crate) callbacks: ManagedGroupCallbacks,
@@ -32,9 +60,97 @@ impl ManagedGroupConfig {
         ManagedGroupConfig {
             handshake_message_format,
             update_policy,
+            role_policy: false,
+            sender_ratchet_configuration: SenderRatchetConfiguration::default(),
+            padding_policy: PaddingPolicy::default(),
+            custom_padding: None,
+            past_epochs: 0,
+            past_epochs_max_age: 0,
+            clock: SharedClock::default(),
             callbacks,
         }
     }
+    /// Enables the built-in role policy for groups created with this
+    /// configuration. The creator of the group becomes its owner. Groups
+    /// joined through a `Welcome` message use the policy of the group.
+    pub fn with_role_policy(mut self, role_policy: bool) -> Self {
+        self.role_policy = role_policy;
+        self
+    }
+    pub fn role_policy(&self) -> bool {
+        self.role_policy
+    }
+    /// Sets how many messages can arrive out of order and how many messages
+    /// can be skipped by a sender. Larger values make decryption more robust
+    /// on lossy or high-latency transports, smaller values keep fewer secrets.
+    pub fn with_sender_ratchet_configuration(
+        mut self,
+        sender_ratchet_configuration: SenderRatchetConfiguration,
+    ) -> Self {
+        self.sender_ratchet_configuration = sender_ratchet_configuration;
+        self
+    }
+    pub fn sender_ratchet_configuration(&self) -> &SenderRatchetConfiguration {
+        &self.sender_ratchet_configuration
+    }
+    /// Sets the padding policy that hides the length of application and
+    /// handshake messages.
+    pub fn with_padding_policy(mut self, padding_policy: PaddingPolicy) -> Self {
+        self.padding_policy = padding_policy;
+        self
+    }
+    pub fn padding_policy(&self) -> &PaddingPolicy {
+        &self.padding_policy
+    }
+    /// Sets a custom padding strategy that is used instead of the padding
+    /// policy. The strategy isn't persisted and has to be set again with
+    /// [`ManagedGroup::set_configuration()`] after a group was loaded.
+    pub fn with_custom_padding(mut self, custom_padding: SharedPadding) -> Self {
+        self.custom_padding = Some(custom_padding);
+        self
+    }
+    pub fn custom_padding(&self) -> Option<&SharedPadding> {
+        self.custom_padding.as_ref()
+    }
+    /// Retains the secrets of up to `past_epochs` past epochs, so that
+    /// application messages that were sent before a Commit can still be
+    /// decrypted after the Commit was processed. Past epochs older than
+    /// `max_age` seconds are deleted, unless `max_age` is `0`. Retaining past
+    /// epochs weakens forward secrecy, so the values should be kept small.
+    pub fn with_past_epoch_retention(mut self, past_epochs: u32, max_age: u64) -> Self {
+        self.past_epochs = past_epochs;
+        self.past_epochs_max_age = max_age;
+        self
+    }
+    pub fn past_epochs(&self) -> u32 {
+        self.past_epochs
+    }
+    pub fn past_epochs_max_age(&self) -> u64 {
+        self.past_epochs_max_age
+    }
+    /// Sets the clock that is used to validate the lifetime of key packages
+    /// and to expire past epochs instead of the system clock. The clock isn't
+    /// persisted and has to be set again with
+    /// [`ManagedGroup::set_configuration()`] after a group was loaded.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+    /// Returns the `GroupConfig` for the underlying `MlsGroup`
+    pub(crate) fn group_config(&self) -> GroupConfig {
+        GroupConfig {
+            sender_ratchet_configuration: self.sender_ratchet_configuration,
+            padding_policy: self.padding_policy,
+            custom_padding: self.custom_padding.clone(),
+            additional_as_epochs: self.past_epochs,
+            additional_as_epochs_max_age: self.past_epochs_max_age,
+            clock: self.clock.clone(),
+            ..GroupConfig::default()
+        }
+    }
     pub fn callbacks(&self) -> &ManagedGroupCallbacks {
         &self.callbacks
     }
@@ -7,6 +7,7 @@ use crate::codec::CodecError;
 use crate::config::ConfigError;
 use crate::error::{ErrorPayload, ErrorString};
 use crate::group::{ApplyCommitError, CreateCommitError, ExporterError, GroupError};
+use crate::key_packages::KeyPackageError;
 
 implement_error! {
     pub enum ManagedGroupError {
@@ -26,6 +27,16 @@ implement_error! {
             "See [`PendingProposalsError`](`PendingProposalsError`) for details",
         Exporter(ExporterError) =
             "See [`ExporterError`](`crate::group::ExporterError`) for details",
+        RolePolicy(RolePolicyError) =
+            "See [`RolePolicyError`](`RolePolicyError`) for details",
+        Membership(MembershipError) =
+            "See [`MembershipError`](`MembershipError`) for details",
+        CredentialRotation(CredentialRotationError) =
+            "See [`CredentialRotationError`](`CredentialRotationError`) for details",
+        KeyPackage(KeyPackageError) =
+            "See [`KeyPackageError`](`crate::key_packages::KeyPackageError`) for details",
+        Unsupported(UnsupportedError) =
+            "See [`UnsupportedError`](`UnsupportedError`) for details",
     }
 }
 
@@ -41,15 +52,58 @@ implement_error! {
     }
 }
 
+implement_error! {
+    pub enum UnsupportedError {
+        PreSharedKeys = "Pre-shared keys are not supported yet (see #141).",
+    }
+}
+
+implement_error! {
+    pub enum MembershipError {
+        UnknownIdentity = "There is no member with the given identity in the group.",
+        UnknownUser = "There is no device of the given user in the group.",
+        NoKeyPackages = "No key packages were provided.",
+        UserMismatch = "The key packages don't belong to the same user.",
+        NoMatchingKeyPackage = "None of the key packages of a member matches the ciphersuite of the group.",
+        UserAlreadyMember = "The user already has a device in the group.",
+    }
+}
+
+implement_error! {
+    pub enum CredentialRotationError {
+        IdentityMismatch = "The new credential has a different identity than the current one.",
+        CredentialMismatch = "The credential bundle doesn't match the own credential in the group.",
+        NotCommitted = "The Update proposal that rotates the credential was not committed.",
+    }
+}
+
+implement_error! {
+    pub enum RolePolicyError {
+        NotEnabled = "The group does not have a role policy.",
+        InsufficientRole = "The role of the member does not allow this operation.",
+        UnknownMember = "The member is not part of the group.",
+        NoOwnerLeft = "The operation would leave the group without an owner.",
+        InvalidRoles = "The roles extension is malformed.",
+    }
+}
+
 implement_error! {
     pub enum InvalidMessageError {
-        InvalidCiphertext(ErrorPayload) =
-            "An invalid ciphertext was provided. The error returns the associated data of the ciphertext.",
-        CommitWithInvalidProposals(ErrorString) =
-            "A commit contained an invalid proposal. Additional detail is provided.",
-        CommitError(ApplyCommitError) =
-            "See [`ApplyCommitError`](`crate::group::ApplyCommitError`) for details",
-        GroupError(GroupError) =
-            "See [`GroupError`](`crate::group::GroupError`) for details",
+        Simple {
+            UnexpectedWireFormat =
+                "The message is neither an MLSPlaintext nor an MLSCiphertext.",
+            InvalidMembershipTag =
+                "The membership tag of an MLSPlaintext is missing or invalid.",
+        }
+        Complex {
+            InvalidCiphertext(ErrorPayload) =
+                "An invalid ciphertext was provided. The error returns the associated data of the ciphertext.",
+            CommitWithInvalidProposals(ErrorString) =
+                "A commit contained an invalid proposal. Additional detail is provided.",
+            CommitError(ApplyCommitError) =
+                "See [`ApplyCommitError`](`crate::group::ApplyCommitError`) for details",
+            GroupError(GroupError) =
+                "See [`GroupError`](`crate::group::GroupError`) for details",
+        }
     }
 }
@@ -1,24 +1,34 @@
 pub mod callbacks;
+mod commit_builder;
 pub mod config;
 pub mod errors;
+mod roles;
 mod ser;
 #[cfg(test)]
 mod test_managed_group;
 
+use crate::codec::{Codec, CodecError, Cursor, TlsSerialization};
+use crate::config::ProtocolVersion;
 use crate::credentials::{Credential, CredentialBundle};
+use crate::extensions::*;
 use crate::framing::*;
 use crate::group::*;
 use crate::key_packages::{KeyPackage, KeyPackageBundle};
-use crate::messages::{proposals::*, Welcome};
+use crate::messages::{proposals::*, GroupInfo, Welcome};
 use crate::tree::index::LeafIndex;
 use crate::tree::node::Node;
 
 use std::collections::HashMap;
 use std::io::{Error, Read, Write};
 
+use super::storage;
 pub use callbacks::*;
+pub use commit_builder::CommitBuilder;
 pub use config::*;
-pub use errors::{InvalidMessageError, ManagedGroupError, PendingProposalsError, UseAfterEviction};
+pub use errors::{
+    CredentialRotationError, InvalidMessageError, ManagedGroupError, MembershipError,
+    PendingProposalsError, RolePolicyError, UnsupportedError, UseAfterEviction,
+};
 use ser::*;
 
 /// A `ManagedGroup` represents an [MlsGroup] with
@@ -45,7 +55,9 @@ use ser::*;
 ///
 /// The application policy for the group can be enforced by implementing the
 /// validator callback functions and selectively allowing/ disallowing each
-/// operation (see [`ManagedGroupCallbacks`])
+/// operation (see [`ManagedGroupCallbacks`]). Additionally, a built-in role
+/// policy with owners, admins and members can be enabled through
+/// [`ManagedGroupConfig::with_role_policy()`].
 ///
 /// Changes to the group state are dispatched as events through callback
 /// functions (see [`ManagedGroupCallbacks`]).
@@ -54,6 +66,14 @@ use ser::*;
 pub struct ManagedGroup<'a> {
     // CredentialBundle used to sign messages
     credential_bundle: &'a CredentialBundle,
+    // A new CredentialBundle that replaces `credential_bundle` once the Update
+    // proposal created by `rotate_credential()` was committed. It isn't
+    // persisted and has to be set again with `set_pending_credential_bundle()`
+    // after the group was loaded.
+    pending_credential_bundle: Option<&'a CredentialBundle>,
+    // The credential of the pending credential rotation. Unlike the bundle, it
+    // is persisted.
+    pending_credential: Option<Credential>,
     // The group configuration. See `ManagedGroupCongig` for more information.
     managed_group_config: ManagedGroupConfig,
     // the internal `MlsGroup` used for lower level operations. See `MlsGroup` for more
@@ -65,6 +85,10 @@ pub struct ManagedGroup<'a> {
     // Own `KeyPackageBundle`s that were created for update proposals or commits. The vector is
     // emptied after every epoch change.
     own_kpbs: Vec<KeyPackageBundle>,
+    // Own handshake messages that were sent as `MLSCiphertext`, together with their encoding. Since
+    // the keys are deleted after encryption, they can't be decrypted when they come back from the
+    // DS and are looked up here instead. The vector is emptied after every epoch change.
+    own_handshake_messages: Vec<(Vec<u8>, MLSPlaintext)>,
     // The AAD that is used for all outgoing handshake messages. The AAD can be set through
     // `set_aad()`.
     aad: Vec<u8>,
@@ -84,19 +108,31 @@ impl<'a> ManagedGroup<'a> {
         group_id: GroupId,
         key_package_bundle: KeyPackageBundle,
     ) -> Result<Self, ManagedGroupError> {
-        let group = MlsGroup::new(
+        // The creator of the group is its first owner if the role policy is enabled
+        let extensions: Vec<Box<dyn Extension>> = if managed_group_config.role_policy {
+            vec![Box::new(RolesExtension::new(
+                credential_bundle.credential().identity(),
+            ))]
+        } else {
+            Vec::new()
+        };
+        let group = MlsGroup::new_with_extensions(
             &group_id.as_slice(),
             key_package_bundle.key_package().ciphersuite_name(),
             key_package_bundle,
-            GroupConfig::default(),
+            managed_group_config.group_config(),
+            extensions,
         )?;
 
         let managed_group = ManagedGroup {
             credential_bundle,
+            pending_credential_bundle: None,
+            pending_credential: None,
             managed_group_config: managed_group_config.clone(),
             group,
             pending_proposals: vec![],
             own_kpbs: vec![],
+            own_handshake_messages: vec![],
             aad: vec![],
             active: true,
         };
@@ -115,14 +151,22 @@ impl<'a> ManagedGroup<'a> {
         ratchet_tree: Option<Vec<Option<Node>>>,
         key_package_bundle: KeyPackageBundle,
     ) -> Result<Self, GroupError> {
-        let group = MlsGroup::new_from_welcome(welcome, ratchet_tree, key_package_bundle)?;
+        let group = MlsGroup::new_from_welcome_with_config(
+            welcome,
+            ratchet_tree,
+            key_package_bundle,
+            managed_group_config.group_config(),
+        )?;
 
         let managed_group = ManagedGroup {
             credential_bundle,
+            pending_credential_bundle: None,
+            pending_credential: None,
             managed_group_config: managed_group_config.clone(),
             group,
             pending_proposals: vec![],
             own_kpbs: vec![],
+            own_handshake_messages: vec![],
             aad: vec![],
             active: true,
         };
@@ -138,6 +182,12 @@ impl<'a> ManagedGroup<'a> {
     /// Adds members to the group
     ///
     /// New members are added by providing a `KeyPackage` for each member.
+    /// Key packages of a member for other ciphersuites than the group's are
+    /// ignored, so all key packages created with
+    /// [`KeyPackageBundle::new_for_ciphersuites()`] can be provided. If none of
+    /// the key packages of a member matches the group's ciphersuite,
+    /// `ManagedGroupError::Membership(MembershipError::NoMatchingKeyPackage)`
+    /// is returned.
     ///
     /// If successful, it returns a `Vec` of
     /// [`MLSMessage`](crate::prelude::MLSMessage) and a
@@ -146,35 +196,13 @@ impl<'a> ManagedGroup<'a> {
         &mut self,
         key_packages: &[KeyPackage],
     ) -> Result<(Vec<MLSMessage>, Welcome), ManagedGroupError> {
-        if !self.active {
-            return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
-        }
-
-        // Create add proposals by value from key packages
-        let proposals = key_packages
+        let (mls_messages, welcome_option) = key_packages
             .iter()
-            .map(|key_package| {
-                Proposal::Add(AddProposal {
-                    key_package: key_package.clone(),
-                })
+            .cloned()
+            .fold(self.commit(), |builder, key_package| {
+                builder.add(key_package)
             })
-            .collect::<Vec<Proposal>>();
-        let proposals_by_value = &proposals.iter().collect::<Vec<&Proposal>>();
-
-        // Include pending proposals
-        let proposals_by_reference = &self
-            .pending_proposals
-            .iter()
-            .collect::<Vec<&MLSPlaintext>>();
-
-        // Create Commit over all proposals
-        let (commit, welcome_option, kpb_option) = self.group.create_commit(
-            &self.aad,
-            &self.credential_bundle,
-            proposals_by_reference,
-            proposals_by_value,
-            false,
-        )?;
+            .finish()?;
         let welcome = match welcome_option {
             Some(welcome) => welcome,
             None => {
@@ -183,25 +211,13 @@ impl<'a> ManagedGroup<'a> {
                 ))
             }
         };
-
-        // If it was a full Commit, we have to save the KeyPackageBundle for later
-        if let Some(kpb) = kpb_option {
-            self.own_kpbs.push(kpb);
-        }
-
-        // Convert MLSPlaintext messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_messages = self.plaintext_to_mls_messages(vec![commit])?;
-
-        // Since the state of the group was changed, call the auto-save function
-        self.auto_save();
-
         Ok((mls_messages, welcome))
     }
 
     /// Removes members from the group
     ///
-    /// Members are removed by providing the index of their leaf in the tree.
+    /// Members are removed by providing the index of their leaf in the tree
+    /// (see [`members()`](`ManagedGroup::members()`)).
     ///
     /// If successful, it returns a `Vec` of
     /// [`MLSMessage`](crate::prelude::MLSMessage) and an optional
@@ -211,56 +227,92 @@ impl<'a> ManagedGroup<'a> {
         &mut self,
         members: &[usize],
     ) -> Result<(Vec<MLSMessage>, Option<Welcome>), ManagedGroupError> {
-        if !self.active {
-            return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
-        }
-
-        // Create add proposals by value
-        let proposals = members
-            .iter()
-            .map(|member| {
-                Proposal::Remove(RemoveProposal {
-                    removed: *member as u32,
-                })
-            })
-            .collect::<Vec<Proposal>>();
-        let proposals_by_value = &proposals.iter().collect::<Vec<&Proposal>>();
-
-        // Include pending proposals
-        let proposals_by_reference = &self
-            .pending_proposals
+        members
             .iter()
-            .collect::<Vec<&MLSPlaintext>>();
+            .fold(self.commit(), |builder, member| builder.remove(*member))
+            .finish()
+    }
 
-        // Create Commit over all proposals
-        let (commit, welcome_option, kpb_option) = self.group.create_commit(
-            &self.aad,
-            &self.credential_bundle,
-            proposals_by_reference,
-            proposals_by_value,
-            false,
-        )?;
+    /// Removes members from the group
+    ///
+    /// Members are removed by providing the identity of their credential.
+    /// Returns `ManagedGroupError::Membership(MembershipError::UnknownIdentity)`
+    /// if one of the identities is not a member of the group.
+    ///
+    /// If successful, it returns a `Vec` of
+    /// [`MLSMessage`](crate::prelude::MLSMessage) and an optional
+    /// [`Welcome`](crate::prelude::Welcome) message if there were add proposals
+    /// in the queue of pending proposals.
+    pub fn remove_members_by_identity(
+        &mut self,
+        identities: &[&[u8]],
+    ) -> Result<(Vec<MLSMessage>, Option<Welcome>), ManagedGroupError> {
+        let members = self.member_indices(identities)?;
+        self.remove_members(&members)
+    }
 
-        // It has to be a full Commit and we have to save the KeyPackageBundle for later
-        if let Some(kpb) = kpb_option {
-            self.own_kpbs.push(kpb);
-        } else {
-            return Err(ManagedGroupError::LibraryError(
-                "We didn't get a key package for a full commit.".into(),
-            ));
+    /// Adds a user with all of its devices to the group
+    ///
+    /// A `KeyPackage` has to be provided for each device of the user. All key
+    /// packages must have the same user ID (see
+    /// [`KeyPackage::user_id()`](crate::prelude::KeyPackage::user_id())),
+    /// otherwise `ManagedGroupError::Membership(MembershipError::UserMismatch)`
+    /// is returned. If the user already has a device in the group,
+    /// `ManagedGroupError::Membership(MembershipError::UserAlreadyMember)` is
+    /// returned; new devices of an existing user are added with
+    /// [`add_members()`](`ManagedGroup::add_members()`).
+    ///
+    /// If successful, it returns a `Vec` of
+    /// [`MLSMessage`](crate::prelude::MLSMessage) and a
+    /// [`Welcome`](crate::prelude::Welcome) message.
+    pub fn add_user(
+        &mut self,
+        key_packages: &[KeyPackage],
+    ) -> Result<(Vec<MLSMessage>, Welcome), ManagedGroupError> {
+        let user_id = match key_packages.first() {
+            Some(key_package) => key_package.user_id(),
+            None => return Err(MembershipError::NoKeyPackages.into()),
+        };
+        if key_packages
+            .iter()
+            .any(|key_package| key_package.user_id() != user_id)
+        {
+            return Err(MembershipError::UserMismatch.into());
         }
+        if !self.user_devices(user_id).is_empty() {
+            return Err(MembershipError::UserAlreadyMember.into());
+        }
+        self.add_members(key_packages)
+    }
 
-        // Convert MLSPlaintext messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_messages = self.plaintext_to_mls_messages(vec![commit])?;
-
-        // Since the state of the group was changed, call the auto-save function
-        self.auto_save();
-
-        Ok((mls_messages, welcome_option))
+    /// Removes a user with all of its devices from the group
+    ///
+    /// Returns `ManagedGroupError::Membership(MembershipError::UnknownUser)`
+    /// if the user has no devices in the group.
+    ///
+    /// If successful, it returns a `Vec` of
+    /// [`MLSMessage`](crate::prelude::MLSMessage) and an optional
+    /// [`Welcome`](crate::prelude::Welcome) message if there were add proposals
+    /// in the queue of pending proposals.
+    pub fn remove_user(
+        &mut self,
+        user_id: &[u8],
+    ) -> Result<(Vec<MLSMessage>, Option<Welcome>), ManagedGroupError> {
+        let members = self
+            .user_devices(user_id)
+            .iter()
+            .map(|(leaf_index, _)| leaf_index.as_usize())
+            .collect::<Vec<usize>>();
+        if members.is_empty() {
+            return Err(MembershipError::UnknownUser.into());
+        }
+        self.remove_members(&members)
     }
 
     /// Creates proposals to add members to the group
+    ///
+    /// Key packages are selected by ciphersuite as in
+    /// [`add_members()`](`ManagedGroup::add_members()`).
     pub fn propose_add_members(
         &mut self,
         key_packages: &[KeyPackage],
@@ -268,16 +320,20 @@ impl<'a> ManagedGroup<'a> {
         if !self.active {
             return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
         }
-        let plaintext_messages: Vec<MLSPlaintext> = key_packages
-            .iter()
+        let plaintext_messages: Vec<MLSPlaintext> = self
+            .select_key_packages(key_packages)?
+            .into_iter()
             .map(|key_package| {
-                self.group.create_add_proposal(
-                    &self.aad,
-                    &self.credential_bundle,
-                    key_package.clone(),
-                )
+                self.group
+                    .create_add_proposal(&self.aad, &self.credential_bundle, key_package)
             })
-            .collect();
+            .collect::<Result<Vec<MLSPlaintext>, GroupError>>()?;
+        self.check_own_proposals(
+            &plaintext_messages
+                .iter()
+                .map(|plaintext| plaintext.content.to_proposal().clone())
+                .collect::<Vec<Proposal>>(),
+        )?;
 
         let mls_messages = self.plaintext_to_mls_messages(plaintext_messages)?;
 
@@ -288,6 +344,9 @@ impl<'a> ManagedGroup<'a> {
     }
 
     /// Creates proposals to remove members from the group
+    ///
+    /// Members are identified by the index of their leaf in the tree (see
+    /// [`members()`](`ManagedGroup::members()`)).
     pub fn propose_remove_members(
         &mut self,
         members: &[usize],
@@ -305,6 +364,12 @@ impl<'a> ManagedGroup<'a> {
                 )
             })
             .collect();
+        self.check_own_proposals(
+            &plaintext_messages
+                .iter()
+                .map(|plaintext| plaintext.content.to_proposal().clone())
+                .collect::<Vec<Proposal>>(),
+        )?;
 
         let mls_messages = self.plaintext_to_mls_messages(plaintext_messages)?;
 
@@ -314,32 +379,86 @@ impl<'a> ManagedGroup<'a> {
         Ok(mls_messages)
     }
 
-    /// Leave the group
+    /// Creates proposals to remove members from the group
+    ///
+    /// Members are identified by the identity of their credential.
+    /// Returns `ManagedGroupError::Membership(MembershipError::UnknownIdentity)`
+    /// if one of the identities is not a member of the group.
+    pub fn propose_remove_members_by_identity(
+        &mut self,
+        identities: &[&[u8]],
+    ) -> Result<Vec<MLSMessage>, ManagedGroupError> {
+        let members = self.member_indices(identities)?;
+        self.propose_remove_members(&members)
+    }
+
+    /// Returns a [`CommitBuilder`] to create a single Commit that covers
+    /// several operations, e.g. adding and removing members at the same time.
+    pub fn commit(&mut self) -> CommitBuilder<'a, '_> {
+        CommitBuilder::new(self)
+    }
+
+    /// Leave the group. The last owner of a group with the role policy can't
+    /// leave.
     pub fn leave_group(&mut self) -> Result<Vec<MLSMessage>, ManagedGroupError> {
         if !self.active {
             return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
         }
-        let remove_proposal = self.group.create_remove_proposal(
-            &self.aad,
-            &self.credential_bundle,
-            LeafIndex::from(self.group.tree().own_node_index()),
-        );
+        let own_index = LeafIndex::from(self.group.tree().own_node_index());
+        // The last owner can't leave the group
+        self.check_own_proposals(&[Proposal::Remove(RemoveProposal {
+            removed: own_index.as_u32(),
+        })])?;
+        let remove_proposal =
+            self.group
+                .create_remove_proposal(&self.aad, &self.credential_bundle, own_index);
 
         self.plaintext_to_mls_messages(vec![remove_proposal])
     }
 
     /// Gets the current list of members
-    pub fn members(&self) -> Vec<Credential> {
-        let mut members: Vec<Credential> = vec![];
-        let tree = self.group.tree();
-        let leaf_count = self.group.tree().leaf_count();
-        for index in 0..leaf_count.as_usize() {
-            let leaf = &tree.nodes[LeafIndex::from(index)];
-            if let Some(leaf_node) = leaf.key_package() {
-                members.push(leaf_node.credential().clone());
+    ///
+    /// Each member is returned together with the index of its leaf in the
+    /// tree. Note that blank leaves are skipped, so the position of a member
+    /// in the list is not necessarily its leaf index.
+    pub fn members(&self) -> Vec<(LeafIndex, Credential)> {
+        self.indexed_key_packages()
+            .into_iter()
+            .map(|(leaf_index, key_package)| (leaf_index, key_package.credential().clone()))
+            .collect()
+    }
+
+    /// Gets the leaf index of the member with the given identity, or `None` if
+    /// there is no such member
+    pub fn member_index(&self, identity: &[u8]) -> Option<LeafIndex> {
+        self.members()
+            .into_iter()
+            .find(|(_, credential)| credential.identity().as_slice() == identity)
+            .map(|(leaf_index, _)| leaf_index)
+    }
+
+    /// Gets the devices of the user with the given user ID
+    ///
+    /// A device is a leaf whose key package has the given user ID (see
+    /// [`KeyPackage::user_id()`](crate::prelude::KeyPackage::user_id())). Each
+    /// device is returned together with the index of its leaf in the tree.
+    pub fn user_devices(&self, user_id: &[u8]) -> Vec<(LeafIndex, Credential)> {
+        self.indexed_key_packages()
+            .into_iter()
+            .filter(|(_, key_package)| key_package.user_id() == user_id)
+            .map(|(leaf_index, key_package)| (leaf_index, key_package.credential().clone()))
+            .collect()
+    }
+
+    /// Gets the IDs of all users that have at least one device in the group
+    pub fn users(&self) -> Vec<Vec<u8>> {
+        let mut users: Vec<Vec<u8>> = vec![];
+        for (_, key_package) in self.indexed_key_packages() {
+            if !users.iter().any(|user_id| user_id == key_package.user_id()) {
+                users.push(key_package.user_id().to_vec());
             }
         }
-        members
+        users
     }
 
     // === Process messages ===
@@ -357,24 +476,43 @@ impl<'a> ManagedGroup<'a> {
                 // If it is a ciphertext we decrypt it and return the plaintext by value
                 MLSMessage::Ciphertext(ciphertext) => {
                     let aad = ciphertext.authenticated_data.clone();
-                    match self.group.decrypt(&ciphertext) {
-                        Ok(plaintext) => (plaintext, Some(aad)),
-                        Err(_) => {
-                            // If there is a callback for that event we should call it
-                            self.invalid_message_event(InvalidMessageError::InvalidCiphertext(
-                                aad.into(),
-                            ));
-                            // Since we cannot decrypt the MLSCiphertext to a MLSPlaintext we move
-                            // to the next message
-                            continue;
+                    // Our own messages can't be decrypted, since the keys were deleted after
+                    // encryption
+                    if let Some(plaintext) = self.own_handshake_message(&ciphertext) {
+                        (plaintext, Some(aad))
+                    } else {
+                        match self.group.decrypt(&ciphertext) {
+                            Ok(plaintext) => (plaintext, Some(aad)),
+                            Err(_) => {
+                                // If there is a callback for that event we should call it
+                                self.invalid_message_event(InvalidMessageError::InvalidCiphertext(
+                                    aad.into(),
+                                ));
+                                // Since we cannot decrypt the MLSCiphertext to a MLSPlaintext we move
+                                // to the next message
+                                continue;
+                            }
                         }
                     }
                 }
-                // If it is a plaintext message we just return the reference
-                MLSMessage::Plaintext(plaintext) => (plaintext, None),
+                // If it is a plaintext message we check that it was sent by a
+                // member and return it by value
+                MLSMessage::Plaintext(plaintext) => {
+                    if self.group.verify_membership_tag(&plaintext).is_err() {
+                        self.invalid_message_event(InvalidMessageError::InvalidMembershipTag);
+                        continue;
+                    }
+                    (plaintext, None)
+                }
+                // Other messages aren't sent to the group
+                _ => {
+                    self.invalid_message_event(InvalidMessageError::UnexpectedWireFormat);
+                    continue;
+                }
             };
-            // Save the current member list for validation end events
+            // Save the current member and user lists for validation end events
             let indexed_members = self.indexed_members();
+            let users = self.users();
             // See what kind of message it is
             match plaintext.content {
                 MLSPlaintextContentType::Proposal(_) => {
@@ -428,10 +566,19 @@ impl<'a> ManagedGroup<'a> {
                                 &plaintext.sender.sender,
                                 &indexed_members,
                             );
+                            self.send_user_events(
+                                &users,
+                                &plaintext.sender.sender,
+                                &indexed_members,
+                            );
+                            // Sign with the new credential if our credential rotation was
+                            // committed
+                            self.apply_pending_credential_bundle();
                             // We don't need the pending proposals and key package bundles any
                             // longer
                             self.pending_proposals.clear();
                             self.own_kpbs.clear();
+                            self.own_handshake_messages.clear();
                         }
                         Err(apply_commit_error) => match apply_commit_error {
                             GroupError::ApplyCommitError(ApplyCommitError::SelfRemoved) => {
@@ -513,12 +660,16 @@ impl<'a> ManagedGroup<'a> {
         // Include pending proposals into Commit
         let messages_to_commit: Vec<&MLSPlaintext> = self.pending_proposals.iter().collect();
 
+        // Owners drop the roles of removed members in the same Commit
+        let pruned_roles_proposal = self.pruned_roles_proposal(&[]);
+        let proposals_by_value: Vec<&Proposal> = pruned_roles_proposal.iter().collect();
+
         // Create Commit over all pending proposals
         let (commit, welcome_option, kpb_option) = self.group.create_commit(
             &self.aad,
             &self.credential_bundle,
             &messages_to_commit,
-            &[],
+            &proposals_by_value,
             true,
         )?;
 
@@ -546,10 +697,29 @@ impl<'a> ManagedGroup<'a> {
     pub fn export_secret(
         &self,
         label: &str,
+        context: &[u8],
         key_length: usize,
     ) -> Result<Vec<u8>, ManagedGroupError> {
         if self.active {
-            Ok(self.group.export_secret(label, key_length)?)
+            Ok(self.group.export_secret(label, context, key_length)?)
+        } else {
+            Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error))
+        }
+    }
+
+    /// Exports a secret from the given epoch, which is either the current
+    /// one or a retained past epoch
+    pub fn export_secret_for_epoch(
+        &self,
+        epoch: GroupEpoch,
+        label: &str,
+        context: &[u8],
+        key_length: usize,
+    ) -> Result<Vec<u8>, ManagedGroupError> {
+        if self.active {
+            Ok(self
+                .group
+                .export_secret_for_epoch(epoch, label, context, key_length)?)
         } else {
             Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error))
         }
@@ -562,9 +732,12 @@ impl<'a> ManagedGroup<'a> {
         &self.managed_group_config
     }
 
-    /// Sets the configuration
+    /// Sets the configuration. The sender ratchet configuration, the padding
+    /// policy, the past epoch retention and the clock are applied to the
+    /// underlying `MlsGroup` right away.
     pub fn set_configuration(&mut self, managed_group_config: &ManagedGroupConfig) {
         self.managed_group_config = managed_group_config.clone();
+        self.group.set_config(managed_group_config.group_config());
 
         // Since the state of the group was changed, call the auto-save function
         self.auto_save();
@@ -596,9 +769,53 @@ impl<'a> ManagedGroup<'a> {
         self.active
     }
 
-    /// Sets a different `CredentialBundle`
-    pub fn set_credential_bundle(&mut self, credential_bundle: &'a CredentialBundle) {
+    /// Sets a different `CredentialBundle` to sign messages. This only changes
+    /// the local signing key and doesn't tell the group about it. Use
+    /// [`rotate_credential()`](`ManagedGroup::rotate_credential()`) to change
+    /// the credential in the group.
+    ///
+    /// Returns `ManagedGroupError::CredentialRotation(CredentialMismatch)` if
+    /// the credential isn't the one of the own leaf in the group.
+    pub fn set_credential_bundle(
+        &mut self,
+        credential_bundle: &'a CredentialBundle,
+    ) -> Result<(), ManagedGroupError> {
+        if self.group.tree().own_key_package().credential() != credential_bundle.credential() {
+            return Err(ManagedGroupError::CredentialRotation(
+                CredentialRotationError::CredentialMismatch,
+            ));
+        }
         self.credential_bundle = credential_bundle;
+        Ok(())
+    }
+
+    /// Returns the new credential of a pending credential rotation, see
+    /// [`rotate_credential()`](`ManagedGroup::rotate_credential()`).
+    pub fn pending_credential(&self) -> Option<&Credential> {
+        self.pending_credential.as_ref()
+    }
+
+    /// Sets the `CredentialBundle` of a pending credential rotation after the
+    /// group was loaded, since only its credential is persisted. If the
+    /// rotation was committed in the meantime, the bundle is used to sign
+    /// messages right away.
+    ///
+    /// Returns `ManagedGroupError::CredentialRotation(CredentialMismatch)` if
+    /// the credential isn't the one of the pending rotation.
+    pub fn set_pending_credential_bundle(
+        &mut self,
+        credential_bundle: &'a CredentialBundle,
+    ) -> Result<(), ManagedGroupError> {
+        if self.pending_credential.as_ref() != Some(credential_bundle.credential()) {
+            return Err(ManagedGroupError::CredentialRotation(
+                CredentialRotationError::CredentialMismatch,
+            ));
+        }
+        self.pending_credential_bundle = Some(credential_bundle);
+        if self.group.tree().own_key_package().credential() == credential_bundle.credential() {
+            self.apply_pending_credential_bundle();
+        }
+        Ok(())
     }
 
     /// Returns own credential
@@ -611,6 +828,11 @@ impl<'a> ManagedGroup<'a> {
         self.group.group_id()
     }
 
+    /// Get the current epoch
+    pub fn epoch(&self) -> u64 {
+        self.group.context().epoch.0
+    }
+
     /// Updates the own leaf node
     ///
     /// A [`KeyPackageBundle`](crate::prelude::KeyPackageBundle) can optionally
@@ -624,61 +846,7 @@ impl<'a> ManagedGroup<'a> {
         &mut self,
         key_package_bundle_option: Option<KeyPackageBundle>,
     ) -> Result<(Vec<MLSMessage>, Option<Welcome>), ManagedGroupError> {
-        if !self.active {
-            return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
-        }
-
-        // If a KeyPackageBundle was provided, create an UpdateProposal
-        let mut plaintext_messages = if let Some(key_package_bundle) = key_package_bundle_option {
-            let update_proposal = self.group.create_update_proposal(
-                &self.aad,
-                &self.credential_bundle,
-                key_package_bundle.key_package().clone(),
-            );
-            self.own_kpbs.push(key_package_bundle);
-            vec![update_proposal]
-        } else {
-            vec![]
-        };
-
-        // Include pending proposals into Commit
-        let messages_to_commit: Vec<&MLSPlaintext> = self
-            .pending_proposals
-            .iter()
-            .chain(plaintext_messages.iter())
-            .collect();
-
-        // Create Commit over all proposals
-        let (commit, welcome_option, kpb_option) = self.group.create_commit(
-            &self.aad,
-            &self.credential_bundle,
-            &messages_to_commit,
-            &[],
-            true, /* force_self_update */
-        )?;
-
-        // Add the Commit message to the other pending messages
-        plaintext_messages.push(commit);
-
-        // Take the new KeyPackageBundle and save it for later
-        let kpb = match kpb_option {
-            Some(kpb) => kpb,
-            None => {
-                return Err(ManagedGroupError::LibraryError(
-                    "We didn't get a key package for a full commit on self update.".into(),
-                ))
-            }
-        };
-        self.own_kpbs.push(kpb);
-
-        // Convert MLSPlaintext messages to MLSMessage and encrypt them if required by
-        // the configuration
-        let mls_messages = self.plaintext_to_mls_messages(plaintext_messages)?;
-
-        // Since the state of the group was changed, call the auto-save function
-        self.auto_save();
-
-        Ok((mls_messages, welcome_option))
+        self.commit().update(key_package_bundle_option).finish()
     }
 
     /// Creates a proposal to update the own leaf node
@@ -718,21 +886,145 @@ impl<'a> ManagedGroup<'a> {
         Ok(mls_messages)
     }
 
+    /// Creates an Update proposal that rotates the own credential, e.g. to
+    /// replace the signature key. The new `credential_bundle` must have the
+    /// same identity as the current one.
+    ///
+    /// The proposal is signed with the current credential, which proves to the
+    /// other members that the new credential belongs to the same member. The
+    /// new credential is used to sign messages once the proposal was committed
+    /// by another member, since a Commit can't contain the committer's own
+    /// Update proposals. If another Commit is applied first, the rotation is
+    /// dropped and the `error_occured` callback is called with
+    /// `CredentialRotationError::NotCommitted`.
+    ///
+    /// Only the credential of the pending rotation is persisted when the group
+    /// is saved. After loading the group, the new `credential_bundle` has to be
+    /// set again with
+    /// [`set_pending_credential_bundle()`](`ManagedGroup::set_pending_credential_bundle()`).
+    ///
+    /// Returns `ManagedGroupError::CredentialRotation(IdentityMismatch)` if the
+    /// identities of the credentials differ.
+    pub fn rotate_credential(
+        &mut self,
+        credential_bundle: &'a CredentialBundle,
+    ) -> Result<Vec<MLSMessage>, ManagedGroupError> {
+        if !self.active {
+            return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
+        }
+        if credential_bundle.credential().identity() != self.credential().identity() {
+            return Err(ManagedGroupError::CredentialRotation(
+                CredentialRotationError::IdentityMismatch,
+            ));
+        }
+
+        // Create a new KeyPackageBundle with the new credential
+        let tree = self.group.tree();
+        let mut key_package_bundle =
+            KeyPackageBundle::from_rekeyed_key_package(tree.own_key_package());
+        drop(tree);
+        key_package_bundle
+            .key_package_mut()
+            .set_credential(credential_bundle.credential().clone());
+        key_package_bundle.sign(credential_bundle);
+
+        // The Update proposal is signed with the current credential
+        let plaintext_messages = vec![self.group.create_update_proposal(
+            &self.aad,
+            &self.credential_bundle,
+            key_package_bundle.key_package().clone(),
+        )];
+
+        self.own_kpbs.push(key_package_bundle);
+        self.pending_credential_bundle = Some(credential_bundle);
+        self.pending_credential = Some(credential_bundle.credential().clone());
+
+        let mls_messages = self.plaintext_to_mls_messages(plaintext_messages)?;
+
+        // Since the state of the group was changed, call the auto-save function
+        self.auto_save();
+
+        Ok(mls_messages)
+    }
+
     /// Returns a list of proposal
     pub fn pending_proposals(&self) -> &[MLSPlaintext] {
         &self.pending_proposals
     }
 
+    // === Roles ===
+
+    /// Returns the roles of the group if the role policy is enabled and `None`
+    /// otherwise.
+    pub fn roles(&self) -> Option<RolesExtension> {
+        self.group
+            .context()
+            .extension_with_type(ExtensionType::Roles)
+            .and_then(|extension| extension.to_roles_extension().ok())
+            .cloned()
+    }
+
+    /// Returns the role of the member with the given identity if the role
+    /// policy is enabled and `None` otherwise.
+    pub fn role(&self, identity: &[u8]) -> Option<Role> {
+        self.roles().map(|roles| roles.role(identity))
+    }
+
+    /// Creates a proposal to change the role of the member with the given
+    /// identity. Only owners can change roles and the group must be left with
+    /// at least one owner.
+    pub fn propose_role_change(
+        &mut self,
+        identity: &[u8],
+        role: Role,
+    ) -> Result<Vec<MLSMessage>, ManagedGroupError> {
+        if !self.active {
+            return Err(ManagedGroupError::UseAfterEviction(UseAfterEviction::Error));
+        }
+        let current_roles = match self.roles() {
+            Some(roles) => roles,
+            None => return Err(RolePolicyError::NotEnabled.into()),
+        };
+        // Drop the roles of identities that are no longer in the group
+        let indexed_members = self.indexed_members();
+        let members = indexed_members
+            .values()
+            .map(|credential| credential.identity().as_slice())
+            .collect::<Vec<&[u8]>>();
+        let mut new_roles = current_roles.clone();
+        new_roles.retain(&members);
+        new_roles.set_role(identity, role);
+
+        // Keep all other group context extensions
+        let extensions = self.extensions_with_roles(new_roles);
+        roles::authorize_extensions(&current_roles, self.credential().identity(), &extensions)?;
+
+        let plaintext_messages = vec![self.group.create_group_context_extensions_proposal(
+            &self.aad,
+            &self.credential_bundle,
+            extensions,
+        )];
+
+        let mls_messages = self.plaintext_to_mls_messages(plaintext_messages)?;
+
+        // Since the state of the group was changed, call the auto-save function
+        self.auto_save();
+
+        Ok(mls_messages)
+    }
+
     // === Load & save ===
 
-    /// Loads the state from persisted state
+    /// Loads the state from persisted state. The `credential_bundle` must
+    /// contain the credential of the own leaf in the group, otherwise an
+    /// `ErrorKind::InvalidData` error is returned.
     pub fn load<R: Read>(
         reader: R,
         credential_bundle: &'a CredentialBundle,
         callbacks: &ManagedGroupCallbacks,
     ) -> Result<ManagedGroup<'a>, Error> {
         let serialized_managed_group: SerializedManagedGroup = serde_json::from_reader(reader)?;
-        Ok(serialized_managed_group.into_managed_group(credential_bundle, callbacks))
+        serialized_managed_group.into_managed_group(credential_bundle, callbacks)
     }
 
     /// Persists the state
@@ -741,6 +1033,54 @@ impl<'a> ManagedGroup<'a> {
         writer.write_all(&serialized_managed_group.into_bytes())
     }
 
+    /// Loads the state from persisted state that was sealed with
+    /// [`save_encrypted()`](`ManagedGroup::save_encrypted()`). The state is
+    /// authenticated with the `storage_key` before it is deserialized.
+    ///
+    /// The sealed state must belong to the group with the given `group_id`,
+    /// otherwise `StorageError::GroupIdMismatch` is returned. To detect a
+    /// rollback to an older state, the application can pass the last epoch it
+    /// knows of (see [`epoch()`](`ManagedGroup::epoch()`)) as `min_epoch`;
+    /// older states are rejected with `StorageError::EpochRollback`. As with
+    /// [`load()`](`ManagedGroup::load()`), the `credential_bundle` must contain
+    /// the credential of the own leaf in the group.
+    pub fn load_encrypted<R: Read>(
+        mut reader: R,
+        storage_key: &StorageKey,
+        group_id: &GroupId,
+        min_epoch: u64,
+        credential_bundle: &'a CredentialBundle,
+        callbacks: &ManagedGroupCallbacks,
+    ) -> Result<ManagedGroup<'a>, Error> {
+        let mut sealed_managed_group = vec![];
+        reader.read_to_end(&mut sealed_managed_group)?;
+        let serialized_managed_group = storage::open(
+            storage_key,
+            group_id,
+            GroupEpoch(min_epoch),
+            &sealed_managed_group,
+        )?;
+        let serialized_managed_group: SerializedManagedGroup =
+            serde_json::from_slice(&serialized_managed_group)?;
+        serialized_managed_group.into_managed_group(credential_bundle, callbacks)
+    }
+
+    /// Persists the state sealed with the `storage_key`. The group ID and the
+    /// epoch are authenticated along with the state.
+    pub fn save_encrypted<W: Write>(
+        &self,
+        writer: &mut W,
+        storage_key: &StorageKey,
+    ) -> Result<(), Error> {
+        let serialized_managed_group = serde_json::to_vec(self)?;
+        writer.write_all(&storage::seal(
+            storage_key,
+            self.group_id(),
+            self.group.context().epoch,
+            &serialized_managed_group,
+        )?)
+    }
+
     // === Extensions ===
 
     /// Export the Ratchet Tree
@@ -763,7 +1103,9 @@ impl<'a> ManagedGroup<'a> {
             let msg = match self.configuration().handshake_message_format {
                 HandshakeMessageFormat::Plaintext => MLSMessage::Plaintext(plaintext),
                 HandshakeMessageFormat::Ciphertext => {
-                    let ciphertext = self.group.encrypt(plaintext)?;
+                    let ciphertext = self.group.encrypt(plaintext.clone())?;
+                    self.own_handshake_messages
+                        .push((ciphertext.encode_detached()?, plaintext));
                     MLSMessage::Ciphertext(ciphertext)
                 }
             };
@@ -772,6 +1114,46 @@ impl<'a> ManagedGroup<'a> {
         Ok(out)
     }
 
+    /// Replaces the credential bundle with the pending one from
+    /// `rotate_credential()` if the own leaf carries its credential after a
+    /// Commit was applied. Otherwise the Update proposal was not committed and
+    /// the rotation is dropped. Both cases where the group can't sign with the
+    /// credential of the own leaf are reported through the `error_occured`
+    /// callback: a dropped rotation, and a committed rotation whose bundle
+    /// wasn't set again after the group was loaded.
+    fn apply_pending_credential_bundle(&mut self) {
+        let pending_credential = match self.pending_credential.take() {
+            Some(pending_credential) => pending_credential,
+            None => return,
+        };
+        let is_committed = self.group.tree().own_key_package().credential() == &pending_credential;
+        let error = match (is_committed, self.pending_credential_bundle.take()) {
+            (true, Some(credential_bundle)) => {
+                self.credential_bundle = credential_bundle;
+                return;
+            }
+            (true, None) => {
+                // The bundle can still be set with `set_pending_credential_bundle()`
+                self.pending_credential = Some(pending_credential);
+                CredentialRotationError::CredentialMismatch
+            }
+            (false, _) => CredentialRotationError::NotCommitted,
+        };
+        if let Some(error_occured) = self.managed_group_config.callbacks.error_occured {
+            error_occured(self, ManagedGroupError::CredentialRotation(error));
+        }
+    }
+
+    /// Looks up one of our own handshake messages that was sent as
+    /// `MLSCiphertext` in the current epoch
+    fn own_handshake_message(&self, ciphertext: &MLSCiphertext) -> Option<MLSPlaintext> {
+        let encoded_ciphertext = ciphertext.encode_detached().ok()?;
+        self.own_handshake_messages
+            .iter()
+            .find(|(encoding, _)| encoding == &encoded_ciphertext)
+            .map(|(_, plaintext)| plaintext.clone())
+    }
+
     /// Validate all pending proposals. The function returns `true` only if all
     /// proposals are valid.
     fn validate_proposal(
@@ -781,6 +1163,13 @@ impl<'a> ManagedGroup<'a> {
         indexed_members: &HashMap<LeafIndex, Credential>,
     ) -> bool {
         let sender = &indexed_members[sender];
+        // Enforce the role policy before asking the application
+        if self
+            .check_role_policy(proposal, sender, indexed_members)
+            .is_err()
+        {
+            return false;
+        }
         match proposal {
             // Validate add proposals
             Proposal::Add(add_proposal) => {
@@ -802,8 +1191,14 @@ impl<'a> ManagedGroup<'a> {
                     }
                 }
             }
-            // Update proposals don't have validators
-            Proposal::Update(_) => {}
+            // Update proposals must not change the identity of the member
+            Proposal::Update(update_proposal) => {
+                if update_proposal.key_package.credential().identity() != sender.identity() {
+                    return false;
+                }
+            }
+            // GroupContextExtensions proposals don't have validators
+            Proposal::GroupContextExtensions(_) => {}
         }
         true
     }
@@ -828,6 +1223,146 @@ impl<'a> ManagedGroup<'a> {
         true
     }
 
+    /// Looks up the leaf indices of the members with the given identities
+    fn member_indices(&self, identities: &[&[u8]]) -> Result<Vec<usize>, MembershipError> {
+        identities
+            .iter()
+            .map(|identity| match self.member_index(identity) {
+                Some(leaf_index) => Ok(leaf_index.as_usize()),
+                None => Err(MembershipError::UnknownIdentity),
+            })
+            .collect()
+    }
+
+    /// Selects the key packages that match the ciphersuite of the group.
+    /// Members can provide key packages for several ciphersuites (see
+    /// [`KeyPackageBundle::new_for_ciphersuites()`]), the ones for other
+    /// ciphersuites are dropped. The order of the remaining key packages is
+    /// kept.
+    ///
+    /// Returns `MembershipError::NoMatchingKeyPackage` if none of the key
+    /// packages of an identity matches the ciphersuite of the group. The
+    /// selected key packages are verified, so that no Commit is created that
+    /// other members would reject. A `KeyPackageError` is returned if one of
+    /// them is invalid, e.g. `LifetimeTooLong` if its lifetime exceeds the
+    /// maximum lifetime.
+    fn select_key_packages(
+        &self,
+        key_packages: &[KeyPackage],
+    ) -> Result<Vec<KeyPackage>, ManagedGroupError> {
+        let ciphersuite_name = self.group.ciphersuite().name();
+        let (matching, other): (Vec<&KeyPackage>, Vec<&KeyPackage>) = key_packages
+            .iter()
+            .partition(|key_package| key_package.ciphersuite_name() == ciphersuite_name);
+        if other.iter().any(|key_package| {
+            !matching.iter().any(|matching_key_package| {
+                matching_key_package.credential().identity() == key_package.credential().identity()
+            })
+        }) {
+            return Err(MembershipError::NoMatchingKeyPackage.into());
+        }
+        for key_package in matching.iter() {
+            key_package.verify_with_clock(&self.managed_group_config.clock)?;
+        }
+        Ok(matching.into_iter().cloned().collect())
+    }
+
+    /// Checks a proposal against the role policy of the group. This always
+    /// succeeds if the role policy is not enabled.
+    fn check_role_policy(
+        &self,
+        proposal: &Proposal,
+        sender: &Credential,
+        indexed_members: &HashMap<LeafIndex, Credential>,
+    ) -> Result<(), RolePolicyError> {
+        let current_roles = match self.roles() {
+            Some(roles) => roles,
+            None => return Ok(()),
+        };
+        let removed = match proposal {
+            Proposal::Remove(remove_proposal) => indexed_members
+                .get(&LeafIndex::from(remove_proposal.removed))
+                .map(|credential| credential.identity().as_slice()),
+            _ => None,
+        };
+        let members = indexed_members
+            .values()
+            .map(|credential| credential.identity().as_slice())
+            .collect::<Vec<&[u8]>>();
+        roles::authorize(
+            &current_roles,
+            sender.identity(),
+            proposal,
+            &members,
+            removed,
+        )
+    }
+
+    /// Returns a GroupContextExtensions proposal that drops the role entries of
+    /// all identities that are no longer in the group once the members with
+    /// the leaf indices in `removed` and the pending Remove proposals are
+    /// committed. Returns `None` if the role policy is not enabled, the own
+    /// client isn't an owner, a GroupContextExtensions proposal is already
+    /// pending or nothing would be dropped.
+    fn pruned_roles_proposal(&self, removed: &[usize]) -> Option<Proposal> {
+        let current_roles = self.roles()?;
+        if current_roles.role(self.credential().identity()) != Role::Owner {
+            return None;
+        }
+        let mut removed: Vec<LeafIndex> = removed.iter().map(|i| LeafIndex::from(*i)).collect();
+        for plaintext in self.pending_proposals.iter() {
+            match plaintext.content.to_proposal() {
+                Proposal::Remove(remove_proposal) => {
+                    removed.push(LeafIndex::from(remove_proposal.removed))
+                }
+                // Don't override a pending change of the extensions
+                Proposal::GroupContextExtensions(_) => return None,
+                _ => {}
+            }
+        }
+        let indexed_members = self.indexed_members();
+        let remaining = indexed_members
+            .iter()
+            .filter(|(leaf_index, _)| !removed.contains(leaf_index))
+            .map(|(_, credential)| credential.identity().as_slice())
+            .collect::<Vec<&[u8]>>();
+        let mut new_roles = current_roles.clone();
+        new_roles.retain(&remaining);
+        if new_roles == current_roles {
+            return None;
+        }
+        Some(Proposal::GroupContextExtensions(
+            GroupContextExtensionsProposal {
+                extensions: self.extensions_with_roles(new_roles),
+            },
+        ))
+    }
+
+    /// Returns the current group context extensions with the roles extension
+    /// replaced by `roles`.
+    fn extensions_with_roles(&self, roles: RolesExtension) -> Vec<Box<dyn Extension>> {
+        let mut extensions: Vec<Box<dyn Extension>> = self
+            .group
+            .context()
+            .extensions()
+            .iter()
+            .filter(|e| e.extension_type() != ExtensionType::Roles)
+            .cloned()
+            .collect();
+        extensions.push(Box::new(roles));
+        extensions
+    }
+
+    /// Checks proposals created by the own client against the role policy of
+    /// the group.
+    fn check_own_proposals(&self, proposals: &[Proposal]) -> Result<(), RolePolicyError> {
+        let indexed_members = self.indexed_members();
+        for proposal in proposals {
+            self.check_role_policy(proposal, self.credential(), &indexed_members)?;
+        }
+        Ok(())
+    }
+
     /// Send out the corresponding events for the proposals covered by the
     /// Commit
     fn send_events(
@@ -849,13 +1384,14 @@ impl<'a> ManagedGroup<'a> {
         for proposal_or_ref in proposals {
             match proposal_or_ref {
                 ProposalOrRef::Proposal(proposal) => {
-                    self.send_proposal_event(proposal, sender, indexed_members);
+                    self.send_proposal_event(proposal, sender, sender, indexed_members);
                 }
                 ProposalOrRef::Reference(proposal_reference) => {
                     if let Some(queued_proposal) = pending_proposals_queue.get(proposal_reference) {
                         self.send_proposal_event(
                             queued_proposal.proposal(),
                             sender,
+                            &queued_proposal.sender().sender,
                             indexed_members,
                         );
                     }
@@ -864,11 +1400,14 @@ impl<'a> ManagedGroup<'a> {
         }
     }
 
-    /// Send out the corresponding events for the pending proposal list.
+    /// Send out the corresponding events for the pending proposal list. The
+    /// `proposer` is the sender of the proposal, while the `sender` is the
+    /// sender of the Commit.
     fn send_proposal_event(
         &self,
         proposal: &Proposal,
         sender: &LeafIndex,
+        proposer: &LeafIndex,
         indexed_members: &HashMap<LeafIndex, Credential>,
     ) {
         let sender_credential = &indexed_members[sender];
@@ -887,7 +1426,12 @@ impl<'a> ManagedGroup<'a> {
             // Update proposals
             Proposal::Update(update_proposal) => {
                 if let Some(member_updated) = self.managed_group_config.callbacks.member_updated {
-                    member_updated(&self, &self.aad, update_proposal.key_package.credential())
+                    member_updated(
+                        &self,
+                        &self.aad,
+                        &indexed_members[proposer],
+                        update_proposal.key_package.credential(),
+                    )
                 }
             }
             // Remove proposals
@@ -902,6 +1446,30 @@ impl<'a> ManagedGroup<'a> {
                     member_removed(&self, &self.aad, &removal)
                 }
             }
+            // GroupContextExtensions proposals don't have events
+            Proposal::GroupContextExtensions(_) => {}
+        }
+    }
+
+    /// Send out the events for users that joined or left the group, given the
+    /// list of users before the Commit was applied
+    fn send_user_events(
+        &self,
+        previous_users: &[Vec<u8>],
+        sender: &LeafIndex,
+        indexed_members: &HashMap<LeafIndex, Credential>,
+    ) {
+        let sender_credential = &indexed_members[sender];
+        let users = self.users();
+        if let Some(user_added) = self.managed_group_config.callbacks.user_added {
+            for user_id in users.iter().filter(|u| !previous_users.contains(u)) {
+                user_added(&self, &self.aad, sender_credential, user_id)
+            }
+        }
+        if let Some(user_removed) = self.managed_group_config.callbacks.user_removed {
+            for user_id in previous_users.iter().filter(|u| !users.contains(u)) {
+                user_removed(&self, &self.aad, sender_credential, user_id)
+            }
         }
     }
 
@@ -923,21 +1491,62 @@ impl<'a> ManagedGroup<'a> {
 
     /// Return a list (LeafIndex, Credential)
     fn indexed_members(&self) -> HashMap<LeafIndex, Credential> {
-        let mut indexed_members = HashMap::new();
+        self.indexed_key_packages()
+            .into_iter()
+            .map(|(leaf_index, key_package)| (leaf_index, key_package.credential().clone()))
+            .collect()
+    }
+
+    /// Returns the key packages of all non-blank leaves together with their
+    /// leaf index, ordered by leaf index
+    fn indexed_key_packages(&self) -> Vec<(LeafIndex, KeyPackage)> {
+        let mut key_packages = vec![];
         let tree = self.group.tree();
-        let leaf_count = self.group.tree().leaf_count();
+        let leaf_count = tree.leaf_count();
         for index in 0..leaf_count.as_usize() {
             let leaf_index = LeafIndex::from(index);
-            let leaf = &tree.nodes[leaf_index];
-            if let Some(leaf_node) = leaf.key_package() {
-                indexed_members.insert(leaf_index, leaf_node.credential().clone());
+            if let Some(key_package) = tree.nodes[leaf_index].key_package() {
+                key_packages.push((leaf_index, key_package.clone()));
             }
         }
-        indexed_members
+        key_packages
     }
 }
 
 /// Unified message type
+///
+/// All messages are encoded with a common header, so that every message that
+/// is sent over the wire can be decoded with `MLSMessage::decode()`.
+///
+/// ```text
+/// enum {
+///     reserved(0),
+///     mls_plaintext(1),
+///     mls_ciphertext(2),
+///     mls_welcome(3),
+///     mls_group_info(4),
+///     mls_key_package(5),
+///     (255)
+/// } WireFormat;
+///
+/// struct {
+///     ProtocolVersion version = mls10;
+///     WireFormat wire_format;
+///     select (MLSMessage.wire_format) {
+///         case mls_plaintext:
+///             MLSPlaintext plaintext;
+///         case mls_ciphertext:
+///             MLSCiphertext ciphertext;
+///         case mls_welcome:
+///             Welcome welcome;
+///         case mls_group_info:
+///             GroupInfo group_info;
+///         case mls_key_package:
+///             KeyPackage key_package;
+///     }
+/// } MLSMessage;
+/// ```
+#[allow(clippy::large_enum_variant)]
 #[derive(PartialEq, Debug, Clone)]
 pub enum MLSMessage {
     /// An OpenMLS `MLSPlaintext`.
@@ -945,6 +1554,42 @@ pub enum MLSMessage {
 
     /// An OpenMLS `MLSCiphertext`.
     Ciphertext(MLSCiphertext),
+
+    /// An OpenMLS `Welcome`.
+    Welcome(Welcome),
+
+    /// An OpenMLS `GroupInfo`.
+    GroupInfo(GroupInfo),
+
+    /// An OpenMLS `KeyPackage`.
+    KeyPackage(KeyPackage),
+}
+
+/// The wire format of an `MLSMessage`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum WireFormat {
+    Plaintext = 1,
+    Ciphertext = 2,
+    Welcome = 3,
+    GroupInfo = 4,
+    KeyPackage = 5,
+}
+
+impl Codec for WireFormat {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        (*self as u8).encode(buffer)
+    }
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        match u8::decode(cursor)? {
+            1 => Ok(WireFormat::Plaintext),
+            2 => Ok(WireFormat::Ciphertext),
+            3 => Ok(WireFormat::Welcome),
+            4 => Ok(WireFormat::GroupInfo),
+            5 => Ok(WireFormat::KeyPackage),
+            _ => Err(CodecError::DecodingError),
+        }
+    }
 }
 
 impl From<MLSPlaintext> for MLSMessage {
@@ -959,20 +1604,55 @@ impl From<MLSCiphertext> for MLSMessage {
     }
 }
 
+impl From<Welcome> for MLSMessage {
+    fn from(welcome: Welcome) -> Self {
+        MLSMessage::Welcome(welcome)
+    }
+}
+
+impl From<GroupInfo> for MLSMessage {
+    fn from(group_info: GroupInfo) -> Self {
+        MLSMessage::GroupInfo(group_info)
+    }
+}
+
+impl From<KeyPackage> for MLSMessage {
+    fn from(key_package: KeyPackage) -> Self {
+        MLSMessage::KeyPackage(key_package)
+    }
+}
+
 impl MLSMessage {
-    /// Get the group ID as plain byte vector.
-    pub fn group_id(&self) -> Vec<u8> {
+    /// Get the wire format of the message.
+    pub fn wire_format(&self) -> WireFormat {
         match self {
-            MLSMessage::Ciphertext(m) => m.group_id.as_slice(),
-            MLSMessage::Plaintext(m) => m.group_id().as_slice(),
+            MLSMessage::Plaintext(_) => WireFormat::Plaintext,
+            MLSMessage::Ciphertext(_) => WireFormat::Ciphertext,
+            MLSMessage::Welcome(_) => WireFormat::Welcome,
+            MLSMessage::GroupInfo(_) => WireFormat::GroupInfo,
+            MLSMessage::KeyPackage(_) => WireFormat::KeyPackage,
         }
     }
 
-    /// Get the epoch as plain u64.
-    pub fn epoch(&self) -> u64 {
+    /// Get the group ID as plain byte vector, or `None` if the message isn't
+    /// bound to a group (`Welcome` and `KeyPackage`).
+    pub fn group_id(&self) -> Option<Vec<u8>> {
+        match self {
+            MLSMessage::Ciphertext(m) => Some(m.group_id.as_slice()),
+            MLSMessage::Plaintext(m) => Some(m.group_id().as_slice()),
+            MLSMessage::GroupInfo(m) => Some(m.group_id().as_slice()),
+            MLSMessage::Welcome(_) | MLSMessage::KeyPackage(_) => None,
+        }
+    }
+
+    /// Get the epoch as plain u64, or `None` if the message isn't bound to a
+    /// group (`Welcome` and `KeyPackage`).
+    pub fn epoch(&self) -> Option<u64> {
         match self {
-            MLSMessage::Ciphertext(m) => m.epoch.0,
-            MLSMessage::Plaintext(m) => m.epoch().0,
+            MLSMessage::Ciphertext(m) => Some(m.epoch.0),
+            MLSMessage::Plaintext(m) => Some(m.epoch().0),
+            MLSMessage::GroupInfo(m) => Some(m.epoch().0),
+            MLSMessage::Welcome(_) | MLSMessage::KeyPackage(_) => None,
         }
     }
 
@@ -981,6 +1661,36 @@ impl MLSMessage {
         match self {
             MLSMessage::Ciphertext(m) => m.is_handshake_message(),
             MLSMessage::Plaintext(m) => m.is_handshake_message(),
+            _ => false,
         }
     }
 }
+
+impl Codec for MLSMessage {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        ProtocolVersion::default().encode(buffer)?;
+        self.wire_format().encode(buffer)?;
+        match self {
+            MLSMessage::Plaintext(m) => m.encode(buffer),
+            MLSMessage::Ciphertext(m) => m.encode(buffer),
+            MLSMessage::Welcome(m) => m.encode(buffer),
+            MLSMessage::GroupInfo(m) => m.encode(buffer),
+            MLSMessage::KeyPackage(m) => m.encode(buffer),
+        }
+    }
+
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        // Fails if the protocol version isn't supported
+        ProtocolVersion::decode(cursor)?;
+        let message = match WireFormat::decode(cursor)? {
+            WireFormat::Plaintext => MLSMessage::Plaintext(MLSPlaintext::decode(cursor)?),
+            WireFormat::Ciphertext => MLSMessage::Ciphertext(MLSCiphertext::decode(cursor)?),
+            WireFormat::Welcome => MLSMessage::Welcome(Welcome::decode(cursor)?),
+            WireFormat::GroupInfo => MLSMessage::GroupInfo(GroupInfo::decode(cursor)?),
+            WireFormat::KeyPackage => MLSMessage::KeyPackage(KeyPackage::decode(cursor)?),
+        };
+        Ok(message)
+    }
+}
+
+impl TlsSerialization for MLSMessage {}
@@ -0,0 +1,112 @@
+//! # Role policy
+//!
+//! The role policy is an optional, built-in policy of the `ManagedGroup`. It
+//! is enabled when the group context contains a [`RolesExtension`], which
+//! assigns a [`Role`] to members based on the identity of their credential.
+//! The policy is enforced on incoming proposals and commits in addition to the
+//! validator callbacks, as well as on local operations.
+//!
+//! | Proposal               | Owner | Admin        | Member    |
+//! |------------------------|-------|--------------|-----------|
+//! | Add                    | yes   | members only | no        |
+//! | Update                 | yes   | yes          | yes       |
+//! | Remove                 | yes   | members only | self only |
+//! | GroupContextExtensions | yes   | no           | no        |
+//!
+//! Members can remove themselves unless they are the last owner. Only owners
+//! can add a member whose identity already has a role other than
+//! [`Role::Member`], e.g. another device of an admin. Neither a Remove nor a
+//! GroupContextExtensions proposal must leave the group without an owner.
+//!
+//! Owners drop the role entries of identities that are no longer in the group
+//! whenever they commit a removal or change a role.
+
+use super::errors::RolePolicyError;
+use crate::extensions::*;
+use crate::messages::proposals::Proposal;
+
+/// Checks whether the member with the identity `sender` is allowed to issue
+/// `proposal` under the given `roles`. `members` are the identities of all
+/// current members, one per leaf. For Remove proposals, `removed` is the
+/// identity of the member that is removed.
+pub(crate) fn authorize(
+    roles: &RolesExtension,
+    sender: &[u8],
+    proposal: &Proposal,
+    members: &[&[u8]],
+    removed: Option<&[u8]>,
+) -> Result<(), RolePolicyError> {
+    let sender_role = roles.role(sender);
+    match proposal {
+        Proposal::Add(add_proposal) => {
+            if !sender_role.at_least(Role::Admin) {
+                return Err(RolePolicyError::InsufficientRole);
+            }
+            // The new leaf inherits the role of its identity, so only owners
+            // can add identities that have a role.
+            let added = add_proposal.key_package.credential().identity();
+            if sender_role != Role::Owner && roles.role(added) != Role::Member {
+                return Err(RolePolicyError::InsufficientRole);
+            }
+        }
+        Proposal::Update(_) => {}
+        Proposal::Remove(_) => {
+            let removed = match removed {
+                Some(removed) => removed,
+                None => return Err(RolePolicyError::UnknownMember),
+            };
+            // Members can remove themselves
+            if removed != sender {
+                let allowed = match sender_role {
+                    Role::Owner => true,
+                    Role::Admin => roles.role(removed) == Role::Member,
+                    Role::Member => false,
+                };
+                if !allowed {
+                    return Err(RolePolicyError::InsufficientRole);
+                }
+            }
+            // The remaining members must still include an owner
+            let mut remaining = members.to_vec();
+            if let Some(position) = remaining.iter().position(|member| *member == removed) {
+                remaining.remove(position);
+            }
+            if !remaining.is_empty()
+                && !remaining
+                    .iter()
+                    .any(|member| roles.role(member) == Role::Owner)
+            {
+                return Err(RolePolicyError::NoOwnerLeft);
+            }
+        }
+        Proposal::GroupContextExtensions(group_context_extensions_proposal) => {
+            authorize_extensions(roles, sender, &group_context_extensions_proposal.extensions)?
+        }
+    }
+    Ok(())
+}
+
+/// Checks whether the member with the identity `sender` is allowed to replace
+/// the group context extensions with `extensions`.
+pub(crate) fn authorize_extensions(
+    roles: &RolesExtension,
+    sender: &[u8],
+    extensions: &[Box<dyn Extension>],
+) -> Result<(), RolePolicyError> {
+    if roles.role(sender) != Role::Owner {
+        return Err(RolePolicyError::InsufficientRole);
+    }
+    // If the roles are kept, there has to be at least one owner left.
+    // Dropping the extension altogether disables the role policy.
+    if let Some(extension) = extensions
+        .iter()
+        .find(|e| e.extension_type() == ExtensionType::Roles)
+    {
+        match extension.to_roles_extension() {
+            Ok(new_roles) if new_roles.has_owner() => {}
+            Ok(_) => return Err(RolePolicyError::NoOwnerLeft),
+            Err(_) => return Err(RolePolicyError::InvalidRoles),
+        }
+    }
+    Ok(())
+}
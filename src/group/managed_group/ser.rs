@@ -1,5 +1,7 @@
 use crate::group::managed_group::*;
 
+use std::io::{Error, ErrorKind};
+
 use serde::{
     ser::{SerializeStruct, Serializer},
     Deserialize, Serialize,
@@ -10,8 +12,12 @@ pub struct SerializedManagedGroup {
     group: MlsGroup,
     pending_proposals: Vec<MLSPlaintext>,
     own_kpbs: Vec<KeyPackageBundle>,
+    #[serde(default)]
+    own_handshake_messages: Vec<(Vec<u8>, MLSPlaintext)>,
     aad: Vec<u8>,
     active: bool,
+    #[serde(default)]
+    pending_credential: Option<Credential>,
 }
 
 impl<'a> SerializedManagedGroup {
@@ -19,18 +25,28 @@ impl<'a> SerializedManagedGroup {
         self,
         credential_bundle: &'a CredentialBundle,
         callbacks: &ManagedGroupCallbacks,
-    ) -> ManagedGroup<'a> {
+    ) -> Result<ManagedGroup<'a>, Error> {
+        self.group.check_persisted_state()?;
+        if self.group.tree().own_key_package().credential() != credential_bundle.credential() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The credential bundle doesn't match the own credential in the group.",
+            ));
+        }
         let mut managed_group = ManagedGroup {
             credential_bundle,
+            pending_credential_bundle: None,
+            pending_credential: self.pending_credential,
             managed_group_config: self.managed_group_config,
             group: self.group,
             pending_proposals: self.pending_proposals,
             own_kpbs: self.own_kpbs,
+            own_handshake_messages: self.own_handshake_messages,
             aad: self.aad,
             active: self.active,
         };
         managed_group.managed_group_config.set_callbacks(callbacks);
-        managed_group
+        Ok(managed_group)
     }
 }
 
@@ -39,13 +55,15 @@ impl<'a> Serialize for ManagedGroup<'a> {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("SerializedManagedGroup", 6)?;
+        let mut state = serializer.serialize_struct("SerializedManagedGroup", 8)?;
         state.serialize_field("managed_group_config", &self.managed_group_config)?;
         state.serialize_field("group", &self.group)?;
         state.serialize_field("pending_proposals", &self.pending_proposals)?;
         state.serialize_field("own_kpbs", &self.own_kpbs)?;
+        state.serialize_field("own_handshake_messages", &self.own_handshake_messages)?;
         state.serialize_field("aad", &self.aad)?;
         state.serialize_field("active", &self.active)?;
+        state.serialize_field("pending_credential", &self.pending_credential)?;
         state.end()
     }
 }
@@ -1,3 +1,4 @@
+use crate::clock::Clock;
 use crate::extensions::*;
 use crate::framing::*;
 use crate::group::mls_group::*;
@@ -6,6 +7,9 @@ use crate::key_packages::*;
 use crate::messages::*;
 use crate::tree::private_tree::CommitSecret;
 
+use super::past_epochs::PastEpoch;
+use std::mem;
+
 impl MlsGroup {
     pub(crate) fn apply_commit_internal(
         &mut self,
@@ -28,6 +32,16 @@ impl MlsGroup {
             _ => return Err(ApplyCommitError::WrongPlaintextContentType),
         };
 
+        // Verify that the Commit and the proposals were sent by members of
+        // the group
+        if self.verify_membership_tag(mls_plaintext).is_err()
+            || proposals_by_reference
+                .iter()
+                .any(|proposal| self.verify_membership_tag(proposal).is_err())
+        {
+            return Err(ApplyCommitError::InvalidMembershipTag);
+        }
+
         // Build a queue with all proposals from the Commit and check that we have all
         // of the proposals by reference locally
         let proposal_queue = match ProposalQueue::from_committed_proposals(
@@ -40,6 +54,56 @@ impl MlsGroup {
             Err(_) => return Err(ApplyCommitError::MissingProposal),
         };
 
+        // Verify the key packages in Add and Update proposals, including their
+        // lifetime
+        if proposal_queue
+            .filtered_by_type(ProposalType::Add)
+            .filter_map(|queued_proposal| queued_proposal.proposal().as_add())
+            .map(|add_proposal| add_proposal.key_package)
+            .chain(
+                proposal_queue
+                    .filtered_by_type(ProposalType::Update)
+                    .filter_map(|queued_proposal| queued_proposal.proposal().as_update())
+                    .map(|update_proposal| update_proposal.key_package),
+            )
+            .any(|key_package| key_package.verify_with_clock(&self.clock).is_err())
+        {
+            return Err(ApplyCommitError::ProposalKeyPackageVerificationFailure);
+        }
+
+        // Verify that Update proposals and the path of the Commit don't change
+        // the identity of the member. The credential itself may change, e.g. to
+        // rotate the signature key.
+        if proposal_queue
+            .filtered_by_type(ProposalType::Update)
+            .filter_map(|queued_proposal| {
+                queued_proposal
+                    .proposal()
+                    .as_update()
+                    .map(|update_proposal| (queued_proposal.sender().sender, update_proposal))
+            })
+            .any(|(sender, update_proposal)| {
+                !self.has_same_identity(sender, &update_proposal.key_package)
+            })
+            || commit.path.as_ref().map_or(false, |path| {
+                !self.has_same_identity(mls_plaintext.sender.sender, &path.leaf_key_package)
+            })
+        {
+            return Err(ApplyCommitError::CredentialIdentityMismatch);
+        }
+
+        // A GroupContextExtensions proposal replaces the current extensions
+        let group_context_extensions = proposal_queue
+            .group_context_extensions()
+            .unwrap_or_else(|| self.group_context.extensions.clone());
+
+        // Save the members of the current epoch in case the epoch is retained
+        let members = if self.past_epochs.is_enabled() {
+            self.members()
+        } else {
+            vec![]
+        };
+
         // Create provisional tree and apply proposals
         let mut provisional_tree = self.tree.borrow_mut();
         let apply_proposals_values =
@@ -63,7 +127,7 @@ impl MlsGroup {
         let commit_secret = if let Some(path) = commit.path.clone() {
             // Verify KeyPackage and MLSPlaintext signature
             let kp = &path.leaf_key_package;
-            if kp.verify().is_err() {
+            if kp.verify_with_clock(&self.clock).is_err() {
                 return Err(ApplyCommitError::PathKeyPackageVerificationFailure);
             }
             let serialized_context = self.group_context.encode_detached().unwrap();
@@ -118,6 +182,7 @@ impl MlsGroup {
             epoch: provisional_epoch,
             tree_hash: provisional_tree.compute_tree_hash(),
             confirmed_transcript_hash: confirmed_transcript_hash.clone(),
+            extensions: group_context_extensions,
         };
         let (provisional_epoch_secrets, provisional_init_secret, encryption_secret) =
             EpochSecrets::derive_epoch_secrets(
@@ -165,14 +230,48 @@ impl MlsGroup {
         }
 
         // Apply provisional tree and state to group
-        self.group_context = provisional_group_context;
-        self.epoch_secrets = provisional_epoch_secrets;
+        let group_context = mem::replace(&mut self.group_context, provisional_group_context);
+        let epoch_secrets = mem::replace(&mut self.epoch_secrets, provisional_epoch_secrets);
         self.interim_transcript_hash = interim_transcript_hash;
         self.init_secret = provisional_init_secret;
         // Create a secret_tree, consuming the `encryption_secret` in the
         // process.
-        self.secret_tree =
-            RefCell::new(encryption_secret.create_secret_tree(provisional_tree.leaf_count()));
+        let secret_tree = self
+            .secret_tree
+            .replace(encryption_secret.create_secret_tree(
+                provisional_tree.leaf_count(),
+                self.sender_ratchet_configuration,
+            ));
+        let replay_cache = mem::take(&mut self.replay_cache);
+        // Retain the secrets of the previous epoch to decrypt late application
+        // messages
+        if self.past_epochs.is_enabled() {
+            let (sender_data_secret, exporter_secret) = epoch_secrets.into_past_epoch_secrets();
+            let now = self.clock.now();
+            self.past_epochs.add(
+                PastEpoch::new(
+                    group_context,
+                    sender_data_secret,
+                    exporter_secret,
+                    secret_tree,
+                    replay_cache,
+                    members,
+                    now,
+                ),
+                now,
+            );
+        }
         Ok(())
     }
+
+    /// Returns `true` if the credential in the `key_package` has the same
+    /// identity as the credential of the member at `leaf_index`.
+    fn has_same_identity(&self, leaf_index: LeafIndex, key_package: &KeyPackage) -> bool {
+        match self.tree().nodes[leaf_index].key_package.as_ref() {
+            Some(current_key_package) => {
+                current_key_package.credential().identity() == key_package.credential().identity()
+            }
+            None => false,
+        }
+    }
 }
@@ -29,6 +29,11 @@ impl MlsGroup {
 
         let proposal_reference_list = proposal_queue.commit_list();
 
+        // A GroupContextExtensions proposal replaces the current extensions
+        let group_context_extensions = proposal_queue
+            .group_context_extensions()
+            .unwrap_or_else(|| self.group_context.extensions.clone());
+
         let sender_index = self.sender_index();
         // Make a copy of the current tree to apply proposals safely
         let mut provisional_tree = RatchetTree::new_from_public_tree(&self.tree());
@@ -101,6 +106,7 @@ impl MlsGroup {
             epoch: provisional_epoch,
             tree_hash: tree_hash.clone(),
             confirmed_transcript_hash: confirmed_transcript_hash.clone(),
+            extensions: group_context_extensions,
         };
         // The init- and encryption secrets are not used here. They come into
         // play when the provisional group state is applied in `apply_commit`.
@@ -118,13 +124,14 @@ impl MlsGroup {
         );
         // Create MLSPlaintext
         let content = MLSPlaintextContentType::Commit((commit, confirmation_tag.clone()));
-        let mls_plaintext = MLSPlaintext::new(
+        let mut mls_plaintext = MLSPlaintext::new(
             sender_index,
             aad,
             content,
             credential_bundle,
             &self.context(),
         );
+        self.add_membership_tag(&mut mls_plaintext);
         // Check if new members were added an create welcome message
         if !plaintext_secrets.is_empty() {
             let extensions: Vec<Box<dyn Extension>> = if self.add_ratchet_tree_extension {
@@ -140,6 +147,7 @@ impl MlsGroup {
                 provisional_group_context.epoch,
                 tree_hash,
                 confirmed_transcript_hash,
+                provisional_group_context.extensions.clone(),
                 extensions,
                 confirmation_tag.to_vec(),
                 sender_index,
@@ -3,13 +3,16 @@ use log::{debug, trace};
 mod apply_commit;
 mod create_commit;
 mod new_from_welcome;
+mod past_epochs;
 #[cfg(test)]
 mod test_mls_group;
 
 use crate::ciphersuite::*;
+use crate::clock::{Clock, SharedClock};
 use crate::codec::*;
 use crate::config::Config;
-use crate::credentials::CredentialBundle;
+use crate::credentials::{Credential, CredentialBundle};
+use crate::extensions::Extension;
 use crate::framing::*;
 use crate::group::*;
 use crate::key_packages::*;
@@ -31,6 +34,8 @@ use std::io::{Error, Read, Write};
 use std::cell::RefMut;
 
 use super::errors::ExporterError;
+use super::storage;
+use past_epochs::*;
 
 pub type CreateCommitResult =
     Result<(MLSPlaintext, Option<Welcome>, Option<KeyPackageBundle>), GroupError>;
@@ -49,6 +54,21 @@ pub struct MlsGroup {
     // Set to true if the ratchet tree extension is added to the `GroupInfo`.
     // Defaults to `false`.
     add_ratchet_tree_extension: bool,
+    // Configuration of the `SenderRatchet`s in the `SecretTree`.
+    sender_ratchet_configuration: SenderRatchetConfiguration,
+    // Padding strategy for application and handshake messages.
+    padding_policy: PaddingPolicy,
+    // Custom padding strategy that is used instead of the padding policy if
+    // it is set. The custom strategy is not persisted.
+    custom_padding: Option<SharedPadding>,
+    // Secrets of past epochs that are retained to decrypt late application
+    // messages.
+    past_epochs: PastEpochs,
+    // Senders and generations of the ciphertexts decrypted in this epoch.
+    replay_cache: ReplayCache,
+    // Clock to validate key packages and to expire past epochs. The clock is
+    // not persisted.
+    clock: SharedClock,
 }
 
 implement_persistence!(
@@ -59,7 +79,12 @@ implement_persistence!(
     secret_tree,
     tree,
     interim_transcript_hash,
-    add_ratchet_tree_extension
+    add_ratchet_tree_extension;
+    sender_ratchet_configuration,
+    past_epochs,
+    padding_policy,
+    replay_cache;
+    skip clock, custom_padding
 );
 
 /// Public `MlsGroup` functions.
@@ -69,6 +94,17 @@ impl MlsGroup {
         ciphersuite_name: CiphersuiteName,
         key_package_bundle: KeyPackageBundle,
         config: GroupConfig,
+    ) -> Result<Self, GroupError> {
+        Self::new_with_extensions(id, ciphersuite_name, key_package_bundle, config, Vec::new())
+    }
+
+    /// Create a new group with the given extensions in the group context.
+    pub fn new_with_extensions(
+        id: &[u8],
+        ciphersuite_name: CiphersuiteName,
+        key_package_bundle: KeyPackageBundle,
+        config: GroupConfig,
+        extensions: Vec<Box<dyn Extension>>,
     ) -> Result<Self, GroupError> {
         debug!("Created group {:x?}", id);
         trace!(" >>> with {:?}, {:?}", ciphersuite_name, config);
@@ -79,6 +115,7 @@ impl MlsGroup {
             ciphersuite,
             group_id,
             tree.compute_tree_hash(),
+            extensions,
         );
         let commit_secret = tree.private_tree().commit_secret();
         // Derive an initial member secret based on the commit secret.
@@ -88,7 +125,8 @@ impl MlsGroup {
             MemberSecret::from_commit_secret_and_psk(ciphersuite, commit_secret, None);
         let (epoch_secrets, init_secret, encryption_secret) =
             EpochSecrets::derive_epoch_secrets(ciphersuite, member_secret, &group_context);
-        let secret_tree = encryption_secret.create_secret_tree(LeafIndex::from(1u32));
+        let secret_tree = encryption_secret
+            .create_secret_tree(LeafIndex::from(1u32), config.sender_ratchet_configuration);
         let interim_transcript_hash = vec![];
         Ok(MlsGroup {
             ciphersuite,
@@ -99,6 +137,15 @@ impl MlsGroup {
             tree: RefCell::new(tree),
             interim_transcript_hash,
             add_ratchet_tree_extension: config.add_ratchet_tree_extension,
+            sender_ratchet_configuration: config.sender_ratchet_configuration,
+            padding_policy: config.padding_policy,
+            custom_padding: config.custom_padding,
+            past_epochs: PastEpochs::new(
+                config.additional_as_epochs,
+                config.additional_as_epochs_max_age,
+            ),
+            replay_cache: ReplayCache::default(),
+            clock: config.clock,
         })
     }
 
@@ -108,7 +155,22 @@ impl MlsGroup {
         nodes_option: Option<Vec<Option<Node>>>,
         kpb: KeyPackageBundle,
     ) -> Result<Self, GroupError> {
-        Ok(Self::new_from_welcome_internal(welcome, nodes_option, kpb)?)
+        Self::new_from_welcome_with_config(welcome, nodes_option, kpb, GroupConfig::default())
+    }
+
+    // Join a group from a welcome message with the given configuration
+    pub fn new_from_welcome_with_config(
+        welcome: Welcome,
+        nodes_option: Option<Vec<Option<Node>>>,
+        kpb: KeyPackageBundle,
+        config: GroupConfig,
+    ) -> Result<Self, GroupError> {
+        Ok(Self::new_from_welcome_internal(
+            welcome,
+            nodes_option,
+            kpb,
+            config,
+        )?)
     }
 
     // === Create handshake messages ===
@@ -118,24 +180,30 @@ impl MlsGroup {
     // struct {
     //     KeyPackage key_package;
     // } Add;
+    /// Returns a `KeyPackageError` if the `joiner_key_package` is invalid, e.g.
+    /// `LifetimeTooLong` if its lifetime exceeds the maximum lifetime. Other
+    /// members would reject a Commit with this proposal.
     pub fn create_add_proposal(
         &self,
         aad: &[u8],
         credential_bundle: &CredentialBundle,
         joiner_key_package: KeyPackage,
-    ) -> MLSPlaintext {
+    ) -> Result<MLSPlaintext, GroupError> {
+        joiner_key_package.verify_with_clock(&self.clock)?;
         let add_proposal = AddProposal {
             key_package: joiner_key_package,
         };
         let proposal = Proposal::Add(add_proposal);
         let content = MLSPlaintextContentType::Proposal(proposal);
-        MLSPlaintext::new(
+        let mut mls_plaintext = MLSPlaintext::new(
             self.sender_index(),
             aad,
             content,
             credential_bundle,
             &self.context(),
-        )
+        );
+        self.add_membership_tag(&mut mls_plaintext);
+        Ok(mls_plaintext)
     }
 
     // 11.1.2. Update
@@ -151,13 +219,15 @@ impl MlsGroup {
         let update_proposal = UpdateProposal { key_package };
         let proposal = Proposal::Update(update_proposal);
         let content = MLSPlaintextContentType::Proposal(proposal);
-        MLSPlaintext::new(
+        let mut mls_plaintext = MLSPlaintext::new(
             self.sender_index(),
             aad,
             content,
             credential_bundle,
             &self.context(),
-        )
+        );
+        self.add_membership_tag(&mut mls_plaintext);
+        mls_plaintext
     }
 
     // 11.1.3. Remove
@@ -175,13 +245,39 @@ impl MlsGroup {
         };
         let proposal = Proposal::Remove(remove_proposal);
         let content = MLSPlaintextContentType::Proposal(proposal);
-        MLSPlaintext::new(
+        let mut mls_plaintext = MLSPlaintext::new(
             self.sender_index(),
             aad,
             content,
             credential_bundle,
             &self.context(),
-        )
+        );
+        self.add_membership_tag(&mut mls_plaintext);
+        mls_plaintext
+    }
+
+    // 11.1.7. GroupContextExtensions
+    // struct {
+    //     Extension extensions<0..2^32-1>;
+    // } GroupContextExtensions;
+    pub fn create_group_context_extensions_proposal(
+        &self,
+        aad: &[u8],
+        credential_bundle: &CredentialBundle,
+        extensions: Vec<Box<dyn Extension>>,
+    ) -> MLSPlaintext {
+        let group_context_extensions_proposal = GroupContextExtensionsProposal { extensions };
+        let proposal = Proposal::GroupContextExtensions(group_context_extensions_proposal);
+        let content = MLSPlaintextContentType::Proposal(proposal);
+        let mut mls_plaintext = MLSPlaintext::new(
+            self.sender_index(),
+            aad,
+            content,
+            credential_bundle,
+            &self.context(),
+        );
+        self.add_membership_tag(&mut mls_plaintext);
+        mls_plaintext
     }
 
     // === ===
@@ -253,47 +349,156 @@ impl MlsGroup {
         ))
     }
 
+    /// Decrypt an `MLSCiphertext`. Application messages of past epochs can
+    /// be decrypted as long as the secrets of the epoch are retained (see
+    /// `GroupConfig::additional_as_epochs`).
     pub fn decrypt(
         &mut self,
         mls_ciphertext: &MLSCiphertext,
     ) -> Result<MLSPlaintext, MLSCiphertextError> {
-        let tree = self.tree();
-        let mut indexed_members = HashMap::new();
-        for i in 0..tree.leaf_count().as_usize() {
-            let leaf_index = LeafIndex::from(i);
-            let node = &tree.nodes[leaf_index];
-            if let Some(kp) = node.key_package.as_ref() {
-                indexed_members.insert(leaf_index, kp.credential());
+        let ciphersuite = self.ciphersuite;
+        if mls_ciphertext.epoch == self.group_context.epoch {
+            let tree = self.tree.borrow();
+            let mut indexed_members = HashMap::new();
+            for i in 0..tree.leaf_count().as_usize() {
+                let leaf_index = LeafIndex::from(i);
+                let node = &tree.nodes[leaf_index];
+                if let Some(kp) = node.key_package.as_ref() {
+                    indexed_members.insert(leaf_index, kp.credential());
+                }
+            }
+
+            let mut mls_plaintext = mls_ciphertext.to_plaintext(
+                ciphersuite,
+                indexed_members,
+                self.epoch_secrets.sender_data_secret(),
+                &mut self.secret_tree.borrow_mut(),
+                &mut self.replay_cache,
+                &self.group_context,
+            )?;
+            // Decrypted handshake messages are authenticated as coming from a
+            // member by the encryption. They get a membership tag so that they
+            // can be applied like handshake messages that were sent as
+            // plaintext.
+            if mls_plaintext.is_handshake_message() {
+                self.add_membership_tag(&mut mls_plaintext);
             }
+            return Ok(mls_plaintext);
         }
 
-        Ok(mls_ciphertext.to_plaintext(
-            self.ciphersuite(),
+        // Handshake messages are only accepted in the current epoch
+        if mls_ciphertext.content_type != ContentType::Application {
+            return Err(MLSCiphertextError::WrongEpoch);
+        }
+        let past_epoch = match self
+            .past_epochs
+            .get_mut(mls_ciphertext.epoch, self.clock.now())
+        {
+            Some(past_epoch) => past_epoch,
+            None => return Err(MLSCiphertextError::WrongEpoch),
+        };
+        let (group_context, sender_data_secret, secret_tree, replay_cache, members) =
+            past_epoch.secrets_mut();
+        let indexed_members = members
+            .iter()
+            .map(|(leaf_index, credential)| (*leaf_index, credential))
+            .collect();
+        mls_ciphertext.to_plaintext(
+            ciphersuite,
             indexed_members,
-            &self.epoch_secrets,
-            &mut self.secret_tree.borrow_mut(),
-            &self.group_context,
+            sender_data_secret,
+            secret_tree,
+            replay_cache,
+            group_context,
+        )
+    }
+
+    /// Decrypt only the sender data of an `MLSCiphertext` of the current or
+    /// a retained past epoch. In contrast to `decrypt()`, no ratchet is
+    /// advanced and no secret is consumed, so the ciphertext can still be
+    /// decrypted afterwards.
+    pub fn peek_sender_data(
+        &self,
+        mls_ciphertext: &MLSCiphertext,
+    ) -> Result<SenderData, MLSCiphertextError> {
+        let sender_data_secret = if mls_ciphertext.epoch == self.group_context.epoch {
+            self.epoch_secrets.sender_data_secret()
+        } else {
+            match self.past_epochs.get(mls_ciphertext.epoch, self.clock.now()) {
+                Some(past_epoch) => past_epoch.sender_data_secret(),
+                None => return Err(MLSCiphertextError::WrongEpoch),
+            }
+        };
+        let sender_data = mls_ciphertext.sender_data(self.ciphersuite, sender_data_secret)?;
+        Ok(SenderData::new(&sender_data, mls_ciphertext.content_type))
+    }
+
+    /// Verify the membership tag of an `MLSPlaintext` that was received as
+    /// plaintext. The tag is computed with the membership key of the current
+    /// epoch.
+    pub fn verify_membership_tag(
+        &self,
+        mls_plaintext: &MLSPlaintext,
+    ) -> Result<(), MLSPlaintextError> {
+        let serialized_context = self.group_context.encode_detached().unwrap();
+        mls_plaintext.verify_membership_tag(
+            self.ciphersuite,
+            serialized_context,
+            self.epoch_secrets.membership_key(),
+        )
+    }
+
+    /// Exports a secret of `key_length` bytes from the current epoch with
+    /// the given `label` and `context`.
+    ///
+    /// Returns an `ExporterError` if the `key_length` is `0` or too large for
+    /// the ciphersuite.
+    pub fn export_secret(
+        &self,
+        label: &str,
+        context: &[u8],
+        key_length: usize,
+    ) -> Result<Vec<u8>, GroupError> {
+        Ok(self.epoch_secrets.exporter_secret.derive_exported_secret(
+            self.ciphersuite(),
+            label,
+            context,
+            key_length,
         )?)
     }
 
-    // Exporter
-    pub fn export_secret(&self, label: &str, key_length: usize) -> Result<Vec<u8>, GroupError> {
-        // TODO: This should throw an error. Generally, keys length should be
-        // checked. (see #228).
-        if key_length > u16::MAX.into() {
-            return Err(ExporterError::KeyLengthTooLong.into());
+    /// Exports a secret like `export_secret()`, but from the given `epoch`.
+    /// Apart from the current epoch, secrets can be exported from retained
+    /// past epochs (see `GroupConfig::additional_as_epochs`).
+    pub fn export_secret_for_epoch(
+        &self,
+        epoch: GroupEpoch,
+        label: &str,
+        context: &[u8],
+        key_length: usize,
+    ) -> Result<Vec<u8>, GroupError> {
+        if epoch == self.group_context.epoch {
+            return self.export_secret(label, context, key_length);
         }
-        Ok(self.epoch_secrets.exporter_secret.derive_exported_secret(
+        let past_epoch = match self.past_epochs.get(epoch, self.clock.now()) {
+            Some(past_epoch) => past_epoch,
+            None => return Err(ExporterError::UnknownEpoch.into()),
+        };
+        Ok(past_epoch.exporter_secret().derive_exported_secret(
             self.ciphersuite(),
             label,
-            &self.context(),
+            context,
             key_length,
-        ))
+        )?)
     }
 
-    /// Loads the state from persisted state
+    /// Loads the state from persisted state. States persisted by a version
+    /// that didn't store the membership key are rejected with
+    /// `StorageError::StateTooOld`.
     pub fn load<R: Read>(reader: R) -> Result<MlsGroup, Error> {
-        serde_json::from_reader(reader).map_err(|e| e.into())
+        let mls_group: MlsGroup = serde_json::from_reader(reader)?;
+        mls_group.check_persisted_state()?;
+        Ok(mls_group)
     }
 
     /// Persists the state
@@ -302,6 +507,50 @@ impl MlsGroup {
         writer.write_all(&serialized_mls_group.into_bytes())
     }
 
+    /// Loads the state from persisted state that was sealed with
+    /// [`save_encrypted()`](`MlsGroup::save_encrypted()`). The state is
+    /// authenticated with the `storage_key` before it is deserialized.
+    ///
+    /// The sealed state must belong to the group with the given `group_id`,
+    /// otherwise `StorageError::GroupIdMismatch` is returned. To detect a
+    /// rollback to an older state, the application can pass the last epoch it
+    /// knows of as `min_epoch`; older states are rejected with
+    /// `StorageError::EpochRollback`.
+    pub fn load_encrypted<R: Read>(
+        mut reader: R,
+        storage_key: &StorageKey,
+        group_id: &GroupId,
+        min_epoch: u64,
+    ) -> Result<MlsGroup, Error> {
+        let mut sealed_mls_group = vec![];
+        reader.read_to_end(&mut sealed_mls_group)?;
+        let serialized_mls_group = storage::open(
+            storage_key,
+            group_id,
+            GroupEpoch(min_epoch),
+            &sealed_mls_group,
+        )?;
+        let mls_group: MlsGroup = serde_json::from_slice(&serialized_mls_group)?;
+        mls_group.check_persisted_state()?;
+        Ok(mls_group)
+    }
+
+    /// Persists the state sealed with the `storage_key`. The group ID and the
+    /// epoch are authenticated along with the state.
+    pub fn save_encrypted<W: Write>(
+        &self,
+        writer: &mut W,
+        storage_key: &StorageKey,
+    ) -> Result<(), Error> {
+        let serialized_mls_group = serde_json::to_vec(self)?;
+        writer.write_all(&storage::seal(
+            storage_key,
+            self.group_id(),
+            self.context().epoch,
+            &serialized_mls_group,
+        )?)
+    }
+
     /// Returns the ratchet tree
     pub fn tree(&self) -> Ref<RatchetTree> {
         self.tree.borrow()
@@ -321,10 +570,46 @@ impl MlsGroup {
     pub fn group_id(&self) -> &GroupId {
         &self.group_context.group_id
     }
+
+    /// Applies the `config` to the group. The sender ratchet configuration
+    /// also applies to the current epoch. Retained past epochs that exceed
+    /// the new limits are deleted.
+    pub fn set_config(&mut self, config: GroupConfig) {
+        self.add_ratchet_tree_extension = config.add_ratchet_tree_extension;
+        self.sender_ratchet_configuration = config.sender_ratchet_configuration;
+        self.secret_tree
+            .borrow_mut()
+            .set_configuration(config.sender_ratchet_configuration);
+        self.padding_policy = config.padding_policy;
+        self.custom_padding = config.custom_padding;
+        self.clock = config.clock;
+        self.past_epochs.set_limits(
+            config.additional_as_epochs,
+            config.additional_as_epochs_max_age,
+            self.clock.now(),
+        );
+    }
+
+    /// Set the clock that is used to validate key packages and to expire past
+    /// epochs. The clock isn't persisted and has to be set again after the
+    /// group was loaded.
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
 }
 
 // Private and crate functions
 impl MlsGroup {
+    /// Rejects states that were persisted by a version that didn't store all
+    /// epoch secrets yet. The missing secrets can't be derived again since the
+    /// epoch secret isn't persisted.
+    pub(crate) fn check_persisted_state(&self) -> Result<(), StorageError> {
+        if !self.epoch_secrets.has_membership_key() {
+            return Err(StorageError::StateTooOld);
+        }
+        Ok(())
+    }
+
     fn sender_index(&self) -> LeafIndex {
         self.tree.borrow().own_node_index().into()
     }
@@ -333,6 +618,40 @@ impl MlsGroup {
         &self.epoch_secrets
     }
 
+    /// Add a membership tag to an `MLSPlaintext` of the current epoch.
+    pub(crate) fn add_membership_tag(&self, mls_plaintext: &mut MLSPlaintext) {
+        let serialized_context = self.group_context.encode_detached().unwrap();
+        mls_plaintext
+            .set_membership_tag(
+                self.ciphersuite,
+                serialized_context,
+                self.epoch_secrets.membership_key(),
+            )
+            .unwrap();
+    }
+
+    /// Returns the number of padding bytes for a ciphertext of the given
+    /// unpadded length, using the custom padding strategy if it is set.
+    pub(crate) fn padding_length(&self, length: usize) -> usize {
+        match &self.custom_padding {
+            Some(custom_padding) => custom_padding.padding_length(length),
+            None => self.padding_policy.padding_length(length),
+        }
+    }
+
+    /// Returns the members of the current epoch with their leaf index.
+    fn members(&self) -> Vec<(LeafIndex, Credential)> {
+        let tree = self.tree();
+        let mut members = vec![];
+        for i in 0..tree.leaf_count().as_usize() {
+            let leaf_index = LeafIndex::from(i);
+            if let Some(kp) = tree.nodes[leaf_index].key_package.as_ref() {
+                members.push((leaf_index, kp.credential().clone()));
+            }
+        }
+        members
+    }
+
     #[cfg(test)]
     pub(crate) fn secret_tree_mut(&self) -> RefMut<SecretTree> {
         self.secret_tree.borrow_mut()
@@ -3,17 +3,21 @@ use log::{debug, error};
 use crate::ciphersuite::{signable::*, *};
 use crate::codec::*;
 use crate::extensions::ExtensionType;
+use crate::framing::ReplayCache;
 use crate::group::{mls_group::*, *};
 use crate::key_packages::*;
 use crate::messages::*;
 use crate::schedule::*;
 use crate::tree::{index::*, node::*, treemath, *};
 
+use super::past_epochs::PastEpochs;
+
 impl MlsGroup {
     pub(crate) fn new_from_welcome_internal(
         welcome: Welcome,
         nodes_option: Option<Vec<Option<Node>>>,
         key_package_bundle: KeyPackageBundle,
+        config: GroupConfig,
     ) -> Result<Self, WelcomeError> {
         let ciphersuite = welcome.ciphersuite();
 
@@ -148,10 +152,12 @@ impl MlsGroup {
             epoch: group_info.epoch(),
             tree_hash: tree.compute_tree_hash(),
             confirmed_transcript_hash: group_info.confirmed_transcript_hash().to_vec(),
+            extensions: group_info.group_context_extensions().to_vec(),
         };
         let (epoch_secrets, init_secret, encryption_secret) =
             EpochSecrets::derive_epoch_secrets(&ciphersuite, member_secret, &group_context);
-        let secret_tree = encryption_secret.create_secret_tree(tree.leaf_count());
+        let secret_tree = encryption_secret
+            .create_secret_tree(tree.leaf_count(), config.sender_ratchet_configuration);
 
         let confirmation_tag = ConfirmationTag::new(
             &ciphersuite,
@@ -177,6 +183,15 @@ impl MlsGroup {
                 tree: RefCell::new(tree),
                 interim_transcript_hash,
                 add_ratchet_tree_extension: enable_ratchet_tree_extension,
+                sender_ratchet_configuration: config.sender_ratchet_configuration,
+                padding_policy: config.padding_policy,
+                custom_padding: config.custom_padding,
+                past_epochs: PastEpochs::new(
+                    config.additional_as_epochs,
+                    config.additional_as_epochs_max_age,
+                ),
+                replay_cache: ReplayCache::default(),
+                clock: config.clock,
             })
         }
     }
@@ -0,0 +1,180 @@
+//! # Past epochs
+//!
+//! Application messages that were sent right before a Commit arrive after the
+//! Commit was applied. To still be able to decrypt them, the `SecretTree` and
+//! the `SenderDataSecret` of a bounded number of past epochs can be retained,
+//! together with the group context and the members of the epoch. The
+//! `ExporterSecret` is retained as well to export secrets of past epochs.
+//!
+//! Past epochs expire when more than the configured number of epochs is
+//! retained or when they were retained longer than the configured maximum age.
+//! Only application messages are decrypted with the secrets of past epochs.
+//! The `ReplayCache` of an epoch is retained as well, so that replays of
+//! application messages are also detected in past epochs.
+
+use crate::credentials::Credential;
+use crate::framing::ReplayCache;
+use crate::group::{GroupContext, GroupEpoch};
+use crate::schedule::{ExporterSecret, SenderDataSecret};
+use crate::tree::{index::LeafIndex, secret_tree::SecretTree};
+
+use serde::{Deserialize, Serialize};
+
+/// The secrets and the state of a past epoch that are needed to decrypt
+/// application messages.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub(crate) struct PastEpoch {
+    // Time when the epoch ended in seconds since the UNIX epoch
+    retained_at: u64,
+    group_context: GroupContext,
+    sender_data_secret: SenderDataSecret,
+    exporter_secret: ExporterSecret,
+    secret_tree: SecretTree,
+    replay_cache: ReplayCache,
+    members: Vec<(LeafIndex, Credential)>,
+}
+
+impl PastEpoch {
+    pub(crate) fn new(
+        group_context: GroupContext,
+        sender_data_secret: SenderDataSecret,
+        exporter_secret: ExporterSecret,
+        secret_tree: SecretTree,
+        replay_cache: ReplayCache,
+        members: Vec<(LeafIndex, Credential)>,
+        retained_at: u64,
+    ) -> Self {
+        Self {
+            retained_at,
+            group_context,
+            sender_data_secret,
+            exporter_secret,
+            secret_tree,
+            replay_cache,
+            members,
+        }
+    }
+
+    pub(crate) fn epoch(&self) -> GroupEpoch {
+        self.group_context.epoch
+    }
+
+    pub(crate) fn sender_data_secret(&self) -> &SenderDataSecret {
+        &self.sender_data_secret
+    }
+
+    pub(crate) fn exporter_secret(&self) -> &ExporterSecret {
+        &self.exporter_secret
+    }
+
+    /// Returns `true` if the epoch was retained longer than `max_age` seconds.
+    /// A `max_age` of `0` means no limit.
+    fn is_expired(&self, max_age: u64, now: u64) -> bool {
+        max_age != 0 && now.saturating_sub(self.retained_at) > max_age
+    }
+
+    /// Get a mutable reference to the `SecretTree` and the `ReplayCache`
+    /// together with the other values needed for decryption.
+    pub(crate) fn secrets_mut(
+        &mut self,
+    ) -> (
+        &GroupContext,
+        &SenderDataSecret,
+        &mut SecretTree,
+        &mut ReplayCache,
+        &[(LeafIndex, Credential)],
+    ) {
+        (
+            &self.group_context,
+            &self.sender_data_secret,
+            &mut self.secret_tree,
+            &mut self.replay_cache,
+            &self.members,
+        )
+    }
+}
+
+/// A bounded list of past epochs, the most recent one last.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub(crate) struct PastEpochs {
+    // Maximum number of retained epochs
+    max_epochs: u32,
+    // Maximum age of retained epochs in seconds. `0` means no limit.
+    max_age: u64,
+    epochs: Vec<PastEpoch>,
+}
+
+impl Default for PastEpochs {
+    /// No past epochs are retained by default.
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl PastEpochs {
+    pub(crate) fn new(max_epochs: u32, max_age: u64) -> Self {
+        Self {
+            max_epochs,
+            max_age,
+            epochs: vec![],
+        }
+    }
+
+    /// Changes the number and the maximum age of retained epochs. Epochs that
+    /// exceed the new limits at the time `now` are deleted.
+    pub(crate) fn set_limits(&mut self, max_epochs: u32, max_age: u64, now: u64) {
+        self.max_epochs = max_epochs;
+        self.max_age = max_age;
+        if self.epochs.len() > max_epochs as usize {
+            let surplus = self.epochs.len() - max_epochs as usize;
+            self.epochs.drain(0..surplus);
+        }
+        self.expire(now);
+    }
+
+    /// Returns `true` if past epochs are retained at all.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.max_epochs > 0
+    }
+
+    /// Retains a past epoch. The oldest epochs are deleted if there are more
+    /// than `max_epochs`. Epochs that are older than `max_age` at the time
+    /// `now` are deleted as well.
+    pub(crate) fn add(&mut self, past_epoch: PastEpoch, now: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.epochs.push(past_epoch);
+        if self.epochs.len() > self.max_epochs as usize {
+            let surplus = self.epochs.len() - self.max_epochs as usize;
+            self.epochs.drain(0..surplus);
+        }
+        self.expire(now);
+    }
+
+    /// Get a past epoch if it is still retained at the time `now`.
+    pub(crate) fn get_mut(&mut self, epoch: GroupEpoch, now: u64) -> Option<&mut PastEpoch> {
+        self.expire(now);
+        self.epochs
+            .iter_mut()
+            .find(|past_epoch| past_epoch.epoch() == epoch)
+    }
+
+    /// Get a past epoch if it is still retained, without deleting expired
+    /// epochs.
+    pub(crate) fn get(&self, epoch: GroupEpoch, now: u64) -> Option<&PastEpoch> {
+        self.epochs.iter().find(|past_epoch| {
+            past_epoch.epoch() == epoch && !past_epoch.is_expired(self.max_age, now)
+        })
+    }
+
+    /// Deletes all past epochs that are older than `max_age` at the time
+    /// `now`.
+    fn expire(&mut self, now: u64) {
+        let max_age = self.max_age;
+        self.epochs
+            .retain(|past_epoch| !past_epoch.is_expired(max_age, now));
+    }
+}
@@ -1,9 +1,13 @@
 use crate::tree::TreeError;
 use crate::{
+    framing::MLSPlaintextContentType,
     group::GroupEpoch,
-    messages::{Commit, EncryptedGroupSecrets, GroupInfo},
+    messages::{
+        proposals::{AddProposal, Proposal},
+        Commit, EncryptedGroupSecrets, GroupInfo,
+    },
     prelude::*,
-    tree::{UpdatePath, UpdatePathNode},
+    tree::{secret_tree::SecretType, UpdatePath, UpdatePathNode},
 };
 
 #[test]
@@ -44,6 +48,77 @@ fn test_mls_group_persistence() {
     assert_eq!(alice_group, alice_group_deserialized);
 }
 
+/// States that were persisted before configurable fields were added to
+/// `MlsGroup` can still be loaded and use the default values.
+#[test]
+fn test_mls_group_persistence_defaults() {
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let alice_credential_bundle =
+        CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+    let alice_key_package_bundle =
+        KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, Vec::new()).unwrap();
+    let alice_group = MlsGroup::new(
+        &[1, 2, 3, 4],
+        ciphersuite.name(),
+        alice_key_package_bundle,
+        GroupConfig::default(),
+    )
+    .unwrap();
+
+    // Remove the fields that older states don't have
+    let mut state = serde_json::to_value(&alice_group).unwrap();
+    let group_state = state.as_object_mut().unwrap();
+    group_state.remove("sender_ratchet_configuration");
+    group_state.remove("past_epochs");
+    group_state.remove("padding_policy");
+    group_state.remove("replay_cache");
+    group_state
+        .get_mut("secret_tree")
+        .and_then(|secret_tree| secret_tree.as_object_mut())
+        .unwrap()
+        .remove("configuration");
+
+    let old_state = serde_json::to_vec(&state).unwrap();
+    let alice_group_deserialized =
+        MlsGroup::load(&old_state[..]).expect("Could not load an old group state");
+    assert_eq!(alice_group, alice_group_deserialized);
+}
+
+/// States that were persisted before membership tags were introduced don't
+/// have a membership key and are rejected with a typed error.
+#[test]
+fn test_mls_group_persistence_without_membership_key() {
+    let ciphersuite = &Config::supported_ciphersuites()[0];
+    let alice_credential_bundle =
+        CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+    let alice_key_package_bundle =
+        KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, Vec::new()).unwrap();
+    let alice_group = MlsGroup::new(
+        &[1, 2, 3, 4],
+        ciphersuite.name(),
+        alice_key_package_bundle,
+        GroupConfig::default(),
+    )
+    .unwrap();
+
+    // Remove the membership key that older states don't have
+    let mut state = serde_json::to_value(&alice_group).unwrap();
+    state
+        .get_mut("epoch_secrets")
+        .and_then(|epoch_secrets| epoch_secrets.as_object_mut())
+        .unwrap()
+        .remove("membership_key");
+
+    let old_state = serde_json::to_vec(&state).unwrap();
+    let error = MlsGroup::load(&old_state[..]).expect_err("An old state was loaded");
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    let storage_error = error
+        .into_inner()
+        .and_then(|e| e.downcast::<StorageError>().ok())
+        .expect("Expected a StorageError");
+    assert_eq!(*storage_error, StorageError::StateTooOld);
+}
+
 #[test]
 fn test_failed_groupinfo_decryption() {
     for version in Config::supported_versions() {
@@ -52,6 +127,7 @@ fn test_failed_groupinfo_decryption() {
             let group_id = GroupId::random();
             let tree_hash = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
             let confirmed_transcript_hash = vec![1, 1, 1];
+            let group_context_extensions = Vec::new();
             let extensions = Vec::new();
             let confirmation_tag = vec![6, 6, 6];
             let signer_index = LeafIndex::from(8u32);
@@ -60,6 +136,7 @@ fn test_failed_groupinfo_decryption() {
                 epoch,
                 tree_hash,
                 confirmed_transcript_hash,
+                group_context_extensions,
                 extensions,
                 confirmation_tag,
                 signer_index,
@@ -111,9 +188,13 @@ fn test_failed_groupinfo_decryption() {
                 encrypted_group_info.clone(),
             );
 
-            let error =
-                MlsGroup::new_from_welcome_internal(broken_welcome, None, key_package_bundle)
-                    .expect_err("Creation of MLS group from a broken Welcome was successful.");
+            let error = MlsGroup::new_from_welcome_internal(
+                broken_welcome,
+                None,
+                key_package_bundle,
+                GroupConfig::default(),
+            )
+            .expect_err("Creation of MLS group from a broken Welcome was successful.");
 
             assert_eq!(
                 error,
@@ -158,11 +239,9 @@ fn test_update_path() {
         .unwrap();
 
         // === Alice adds Bob ===
-        let bob_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            bob_key_package.clone(),
-        );
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
         let epoch_proposals = &[&bob_add_proposal];
         let (mls_plaintext_commit, welcome_bundle_alice_bob_option, kpb_option) = group_alice
             .create_commit(
@@ -258,13 +337,14 @@ fn test_update_path() {
         let broken_commit_content =
             MLSPlaintextContentType::Commit((broken_commit, confirmation_tag.clone()));
 
-        let broken_plaintext = MLSPlaintext::new(
+        let mut broken_plaintext = MLSPlaintext::new(
             mls_plaintext_commit.sender.to_leaf_index(),
             &mls_plaintext_commit.authenticated_data,
             broken_commit_content,
             &bob_credential_bundle,
             group_bob.context(),
         );
+        group_bob.add_membership_tag(&mut broken_plaintext);
 
         assert_eq!(
             group_alice
@@ -276,3 +356,531 @@ fn test_update_path() {
         );
     }
 }
+
+/// This test makes sure that Commits and proposals without a valid membership
+/// tag are rejected.
+#[test]
+fn test_membership_tag() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        // Define credential bundles
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+
+        // Generate KeyPackages
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, Vec::new())
+                .unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, Vec::new())
+                .unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package().clone();
+
+        // === Alice creates a group and adds Bob ===
+        let mut group_alice = MlsGroup::new(
+            &[1, 2, 3, 4],
+            ciphersuite.name(),
+            alice_key_package_bundle,
+            GroupConfig::default(),
+        )
+        .unwrap();
+        let bob_add_proposal = group_alice
+            .create_add_proposal(&[], &alice_credential_bundle, bob_key_package)
+            .expect("Could not create proposal.");
+        let (mls_plaintext_commit, welcome_option, _kpb_option) = group_alice
+            .create_commit(
+                &[],
+                &alice_credential_bundle,
+                &[&bob_add_proposal],
+                &[],
+                false,
+            )
+            .unwrap();
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+            .unwrap();
+        let group_bob = MlsGroup::new_from_welcome(
+            welcome_option.unwrap(),
+            Some(group_alice.tree().public_key_tree_copy()),
+            bob_key_package_bundle,
+        )
+        .unwrap();
+
+        // === Bob updates and commits ===
+        let bob_update_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, Vec::new())
+                .unwrap();
+        let update_proposal_bob = group_bob.create_update_proposal(
+            &[],
+            &bob_credential_bundle,
+            bob_update_key_package_bundle.key_package().clone(),
+        );
+        assert!(group_alice
+            .verify_membership_tag(&update_proposal_bob)
+            .is_ok());
+        let (mls_plaintext_commit, _welcome_option, _kpb_option) = group_bob
+            .create_commit(
+                &[],
+                &bob_credential_bundle,
+                &[&update_proposal_bob],
+                &[],
+                false,
+            )
+            .unwrap();
+
+        // A Commit without membership tag is rejected
+        let mut untagged_commit = mls_plaintext_commit.clone();
+        untagged_commit.membership_tag = None;
+        assert_eq!(
+            group_alice.apply_commit(&untagged_commit, &[&update_proposal_bob], &[]),
+            Err(GroupError::ApplyCommitError(
+                ApplyCommitError::InvalidMembershipTag
+            ))
+        );
+
+        // A proposal with an invalid membership tag is rejected
+        let mut tampered_proposal = update_proposal_bob.clone();
+        tampered_proposal.membership_tag = Some(MembershipTag(vec![0u8; 32]));
+        assert_eq!(
+            group_alice.verify_membership_tag(&tampered_proposal),
+            Err(MLSPlaintextError::InvalidMembershipTag)
+        );
+        assert_eq!(
+            group_alice.apply_commit(&mls_plaintext_commit, &[&tampered_proposal], &[]),
+            Err(GroupError::ApplyCommitError(
+                ApplyCommitError::InvalidMembershipTag
+            ))
+        );
+
+        // The tagged messages are accepted
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&update_proposal_bob], &[])
+            .expect("Error applying commit with valid membership tags");
+    }
+}
+
+/// Alice creates a group and adds Bob. Returns both groups and credential
+/// bundles.
+fn setup_alice_and_bob(
+    ciphersuite: &'static Ciphersuite,
+) -> (MlsGroup, MlsGroup, CredentialBundle, CredentialBundle) {
+    // Define credential bundles
+    let alice_credential_bundle =
+        CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+    let bob_credential_bundle =
+        CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+
+    // Generate KeyPackages
+    let alice_key_package_bundle =
+        KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, Vec::new()).unwrap();
+    let bob_key_package_bundle =
+        KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, Vec::new()).unwrap();
+    let bob_key_package = bob_key_package_bundle.key_package().clone();
+
+    // === Alice creates a group and adds Bob ===
+    let mut group_alice = MlsGroup::new(
+        &[1, 2, 3, 4],
+        ciphersuite.name(),
+        alice_key_package_bundle,
+        GroupConfig::default(),
+    )
+    .unwrap();
+    let bob_add_proposal = group_alice
+        .create_add_proposal(&[], &alice_credential_bundle, bob_key_package)
+        .expect("Could not create proposal.");
+    let (mls_plaintext_commit, welcome_option, _kpb_option) = group_alice
+        .create_commit(
+            &[],
+            &alice_credential_bundle,
+            &[&bob_add_proposal],
+            &[],
+            false,
+        )
+        .unwrap();
+    group_alice
+        .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+        .unwrap();
+    let group_bob = MlsGroup::new_from_welcome(
+        welcome_option.unwrap(),
+        Some(group_alice.tree().public_key_tree_copy()),
+        bob_key_package_bundle,
+    )
+    .unwrap();
+
+    (
+        group_alice,
+        group_bob,
+        alice_credential_bundle,
+        bob_credential_bundle,
+    )
+}
+
+/// This test makes sure that a ciphertext can only be decrypted once, also
+/// after the group was saved and loaded.
+#[test]
+fn test_replay_protection() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, mut group_bob, alice_credential_bundle, _bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        // === Alice sends a message that Bob receives twice ===
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &[1, 2, 3], &alice_credential_bundle)
+            .unwrap();
+        let mls_plaintext = group_bob.decrypt(&mls_ciphertext).unwrap();
+        assert_eq!(mls_plaintext.as_application_message(), Ok(&[1, 2, 3][..]));
+        assert_eq!(
+            group_bob.decrypt(&mls_ciphertext),
+            Err(MLSCiphertextError::Replay)
+        );
+
+        // === The replay is also detected after Bob's group was persisted ===
+        let mut serialized_group_bob = vec![];
+        group_bob.save(&mut serialized_group_bob).unwrap();
+        let mut group_bob = MlsGroup::load(&serialized_group_bob[..]).unwrap();
+        assert_eq!(
+            group_bob.decrypt(&mls_ciphertext),
+            Err(MLSCiphertextError::Replay)
+        );
+
+        // === Later messages are still accepted ===
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &[4, 5, 6], &alice_credential_bundle)
+            .unwrap();
+        let mls_plaintext = group_bob.decrypt(&mls_ciphertext).unwrap();
+        assert_eq!(mls_plaintext.as_application_message(), Ok(&[4, 5, 6][..]));
+    }
+}
+
+/// This test makes sure that the sender can't decrypt its own message, since
+/// the key and nonce are deleted after encryption.
+#[test]
+fn test_own_message_secret_reuse() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, _group_bob, alice_credential_bundle, _bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &[1, 2, 3], &alice_credential_bundle)
+            .unwrap();
+        assert_eq!(
+            group_alice.decrypt(&mls_ciphertext),
+            Err(MLSCiphertextError::SecretReuse)
+        );
+    }
+}
+
+/// This test makes sure that ciphertexts with a corrupted or forged content are
+/// rejected with an error.
+#[test]
+fn test_invalid_ciphertext_content() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, mut group_bob, alice_credential_bundle, bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        // === Bob receives a message with modified authenticated data ===
+        // The authenticated data is only bound to the content, so the sender
+        // data can still be decrypted.
+        let mut mls_ciphertext = group_alice
+            .create_application_message(&[], &[1, 2, 3], &alice_credential_bundle)
+            .unwrap();
+        mls_ciphertext.authenticated_data = vec![7, 7, 7];
+        assert_eq!(
+            group_bob.decrypt(&mls_ciphertext),
+            Err(MLSCiphertextError::DecryptionError)
+        );
+
+        // === Bob receives a message from Alice that Bob signed ===
+        let mls_plaintext = MLSPlaintext::new(
+            LeafIndex::from(0u32),
+            &[],
+            MLSPlaintextContentType::Application(vec![4, 5, 6]),
+            &bob_credential_bundle,
+            group_alice.context(),
+        );
+        let (generation, (ratchet_key, ratchet_nonce)) =
+            group_alice.secret_tree_mut().secret_for_encryption(
+                ciphersuite,
+                LeafIndex::from(0u32),
+                SecretType::ApplicationSecret,
+            );
+        let mls_ciphertext = MLSCiphertext::new_from_plaintext(
+            &mls_plaintext,
+            &group_alice,
+            generation,
+            ratchet_key,
+            ratchet_nonce,
+        );
+        assert_eq!(
+            group_bob.decrypt(&mls_ciphertext),
+            Err(MLSCiphertextError::InvalidSignature)
+        );
+    }
+}
+
+/// This test makes sure that the generation of a ciphertext that couldn't be
+/// decrypted is marked as seen, since its secrets were consumed.
+#[test]
+fn test_replay_of_corrupted_ciphertext() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, mut group_bob, alice_credential_bundle, _bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        // === Bob receives a corrupted message twice ===
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &[1, 2, 3], &alice_credential_bundle)
+            .unwrap();
+        let mut corrupted_ciphertext = mls_ciphertext.clone();
+        corrupted_ciphertext.authenticated_data = vec![7, 7, 7];
+        assert_eq!(
+            group_bob.decrypt(&corrupted_ciphertext),
+            Err(MLSCiphertextError::DecryptionError)
+        );
+        assert_eq!(
+            group_bob.decrypt(&corrupted_ciphertext),
+            Err(MLSCiphertextError::Replay)
+        );
+        // The original message has the same sender and generation
+        assert_eq!(
+            group_bob.decrypt(&mls_ciphertext),
+            Err(MLSCiphertextError::Replay)
+        );
+
+        // === Later messages are still accepted ===
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &[4, 5, 6], &alice_credential_bundle)
+            .unwrap();
+        let mls_plaintext = group_bob.decrypt(&mls_ciphertext).unwrap();
+        assert_eq!(mls_plaintext.as_application_message(), Ok(&[4, 5, 6][..]));
+    }
+}
+
+/// This test makes sure that a custom padding strategy is used instead of the
+/// padding policy and that it isn't persisted.
+#[test]
+fn test_custom_padding() {
+    // Pads all ciphertexts to the same length
+    struct FixedLength(usize);
+    impl CustomPadding for FixedLength {
+        fn padding_length(&self, length: usize) -> usize {
+            self.0.saturating_sub(length)
+        }
+    }
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, mut group_bob, alice_credential_bundle, _bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+        group_alice.set_config(GroupConfig {
+            custom_padding: Some(SharedPadding::new(FixedLength(1001))),
+            ..GroupConfig::default()
+        });
+
+        // === Alice's messages are padded with the custom strategy ===
+        for message in &[&[1, 2, 3][..], &[4; 100][..]] {
+            let mls_ciphertext = group_alice
+                .create_application_message(&[], message, &alice_credential_bundle)
+                .unwrap();
+            assert_eq!(mls_ciphertext.ciphertext.len(), 1001);
+            let mls_plaintext = group_bob.decrypt(&mls_ciphertext).unwrap();
+            assert_eq!(mls_plaintext.as_application_message(), Ok(*message));
+        }
+
+        // === The custom strategy is not persisted ===
+        let mut serialized_group_alice = vec![];
+        group_alice.save(&mut serialized_group_alice).unwrap();
+        let mut group_alice = MlsGroup::load(&serialized_group_alice[..]).unwrap();
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &[1, 2, 3], &alice_credential_bundle)
+            .unwrap();
+        assert_eq!(mls_ciphertext.ciphertext.len() % 10, 0);
+    }
+}
+
+/// This test makes sure that the sender data of a ciphertext can be inspected
+/// without consuming the secrets that are needed to decrypt it.
+#[test]
+fn test_peek_sender_data() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, mut group_bob, alice_credential_bundle, _bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        // === Alice sends two messages ===
+        let first_ciphertext = group_alice
+            .create_application_message(&[], &[1, 2, 3], &alice_credential_bundle)
+            .unwrap();
+        let second_ciphertext = group_alice
+            .create_application_message(&[], &[4, 5, 6], &alice_credential_bundle)
+            .unwrap();
+
+        // === Bob peeks at both messages before decrypting them ===
+        for (generation, mls_ciphertext) in
+            [&first_ciphertext, &second_ciphertext].iter().enumerate()
+        {
+            let sender_data = group_bob.peek_sender_data(mls_ciphertext).unwrap();
+            assert_eq!(sender_data.sender(), LeafIndex::from(0u32));
+            assert_eq!(sender_data.generation(), generation as u32);
+            assert_eq!(sender_data.content_type(), ContentType::Application);
+            // Peeking twice gives the same result
+            assert_eq!(group_bob.peek_sender_data(mls_ciphertext), Ok(sender_data));
+        }
+        for mls_ciphertext in &[first_ciphertext, second_ciphertext] {
+            assert!(group_bob.decrypt(mls_ciphertext).is_ok());
+        }
+
+        // === Ciphertexts of unknown epochs are rejected ===
+        let mut mls_ciphertext = group_alice
+            .create_application_message(&[], &[7, 8, 9], &alice_credential_bundle)
+            .unwrap();
+        mls_ciphertext.epoch = GroupEpoch(42);
+        assert_eq!(
+            group_bob.peek_sender_data(&mls_ciphertext),
+            Err(MLSCiphertextError::WrongEpoch)
+        );
+    }
+}
+
+/// This test makes sure that a key package with a lifetime that exceeds the
+/// maximum lifetime can't be added, and that a Commit adding it is rejected.
+#[test]
+fn test_add_key_package_max_lifetime() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, mut group_bob, alice_credential_bundle, _bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        // Charlie's key package is valid for a century
+        let charlie_credential_bundle =
+            CredentialBundle::new("Charlie".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let charlie_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &charlie_credential_bundle,
+            vec![Box::new(LifetimeExtension::new(60 * 60 * 24 * 365 * 100))],
+        )
+        .unwrap();
+
+        // Alice can't create a proposal to add Charlie
+        assert_eq!(
+            group_alice
+                .create_add_proposal(
+                    &[],
+                    &alice_credential_bundle,
+                    charlie_key_package_bundle.key_package().clone(),
+                )
+                .err(),
+            Some(GroupError::KeyPackageError(
+                KeyPackageError::LifetimeTooLong
+            ))
+        );
+
+        // A Commit with a proposal that skipped the check is rejected
+        let mut charlie_add_proposal = MLSPlaintext::new(
+            group_alice.sender_index(),
+            &[],
+            MLSPlaintextContentType::Proposal(Proposal::Add(AddProposal {
+                key_package: charlie_key_package_bundle.key_package().clone(),
+            })),
+            &alice_credential_bundle,
+            group_alice.context(),
+        );
+        group_alice.add_membership_tag(&mut charlie_add_proposal);
+        let (mls_plaintext_commit, _welcome_option, _kpb_option) = group_alice
+            .create_commit(
+                &[],
+                &alice_credential_bundle,
+                &[&charlie_add_proposal],
+                &[],
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            group_bob.apply_commit(&mls_plaintext_commit, &[&charlie_add_proposal], &[]),
+            Err(GroupError::ApplyCommitError(
+                ApplyCommitError::ProposalKeyPackageVerificationFailure
+            ))
+        );
+        assert_eq!(
+            group_alice.apply_commit(&mls_plaintext_commit, &[&charlie_add_proposal], &[]),
+            Err(GroupError::ApplyCommitError(
+                ApplyCommitError::ProposalKeyPackageVerificationFailure
+            ))
+        );
+    }
+}
+
+/// This test makes sure that an Update proposal can rotate the credential of
+/// a member, but not change its identity.
+#[test]
+fn test_update_identity_continuity() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let (mut group_alice, group_bob, alice_credential_bundle, bob_credential_bundle) =
+            setup_alice_and_bob(ciphersuite);
+
+        // Bob tries to become Mallory
+        let mallory_credential_bundle =
+            CredentialBundle::new("Mallory".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let mallory_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &mallory_credential_bundle,
+            Vec::new(),
+        )
+        .unwrap();
+        let mallory_update_proposal = group_bob.create_update_proposal(
+            &[],
+            &bob_credential_bundle,
+            mallory_key_package_bundle.key_package().clone(),
+        );
+        let (mls_plaintext_commit, _welcome_option, _kpb_option) = group_alice
+            .create_commit(
+                &[],
+                &alice_credential_bundle,
+                &[&mallory_update_proposal],
+                &[],
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            group_alice.apply_commit(&mls_plaintext_commit, &[&mallory_update_proposal], &[]),
+            Err(GroupError::ApplyCommitError(
+                ApplyCommitError::CredentialIdentityMismatch
+            ))
+        );
+
+        // Bob rotates his credential and keeps his identity
+        let bob_new_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let bob_new_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &bob_new_credential_bundle,
+            Vec::new(),
+        )
+        .unwrap();
+        let bob_update_proposal = group_bob.create_update_proposal(
+            &[],
+            &bob_credential_bundle,
+            bob_new_key_package_bundle.key_package().clone(),
+        );
+        let (mls_plaintext_commit, _welcome_option, _kpb_option) = group_alice
+            .create_commit(
+                &[],
+                &alice_credential_bundle,
+                &[&bob_update_proposal],
+                &[],
+                false,
+            )
+            .unwrap();
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&bob_update_proposal], &[])
+            .expect("Error applying commit with credential rotation");
+        assert_eq!(
+            group_alice.tree().nodes[LeafIndex::from(1usize)]
+                .key_package
+                .as_ref()
+                .unwrap()
+                .credential(),
+            bob_new_credential_bundle.credential()
+        );
+    }
+}
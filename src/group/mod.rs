@@ -8,20 +8,26 @@
 pub mod errors;
 mod managed_group;
 mod mls_group;
+mod storage;
 
 use crate::ciphersuite::*;
+use crate::clock::SharedClock;
 use crate::codec::*;
+use crate::extensions::*;
+use crate::framing::{PaddingPolicy, SharedPadding};
 use crate::tree::*;
 use crate::utils::*;
 
 pub(crate) use serde::{Deserialize, Serialize};
 
 pub use codec::*;
+pub use errors::StorageError;
 pub(crate) use errors::{
     ApplyCommitError, CreateCommitError, ExporterError, GroupError, WelcomeError,
 };
 pub use managed_group::*;
 pub use mls_group::*;
+pub use storage::{StorageKey, STORAGE_KEY_LENGTH};
 
 #[derive(Hash, Eq, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct GroupId {
@@ -81,6 +87,9 @@ pub struct GroupContext {
     pub epoch: GroupEpoch,
     pub tree_hash: Vec<u8>,
     pub confirmed_transcript_hash: Vec<u8>,
+    // States persisted before group context extensions were stored have none
+    #[serde(default)]
+    pub extensions: Vec<Box<dyn Extension>>,
 }
 
 impl GroupContext {
@@ -89,18 +98,33 @@ impl GroupContext {
         ciphersuite: &Ciphersuite,
         group_id: GroupId,
         tree_hash: Vec<u8>,
+        extensions: Vec<Box<dyn Extension>>,
     ) -> Self {
         GroupContext {
             group_id,
             epoch: GroupEpoch(0),
             tree_hash,
             confirmed_transcript_hash: zero(ciphersuite.hash_length()),
+            extensions,
         }
     }
 
     pub fn serialize(&self) -> Vec<u8> {
         self.encode_detached().unwrap()
     }
+
+    /// Get the extensions of this group context.
+    pub fn extensions(&self) -> &[Box<dyn Extension>] {
+        &self.extensions
+    }
+
+    /// Get a reference to the extension of the given type if it is present.
+    pub fn extension_with_type(&self, extension_type: ExtensionType) -> Option<&dyn Extension> {
+        self.extensions
+            .iter()
+            .find(|e| e.extension_type() == extension_type)
+            .map(|e| &**e)
+    }
 }
 
 impl Codec for GroupContext {
@@ -109,6 +133,13 @@ impl Codec for GroupContext {
         self.epoch.encode(buffer)?;
         encode_vec(VecSize::VecU8, buffer, &self.tree_hash)?;
         encode_vec(VecSize::VecU8, buffer, &self.confirmed_transcript_hash)?;
+        // Get extensions encoded. We need to build a Vec::<ExtensionStruct> first.
+        let encoded_extensions: Vec<ExtensionStruct> = self
+            .extensions
+            .iter()
+            .map(|e| e.to_extension_struct())
+            .collect();
+        encode_vec(VecSize::VecU16, buffer, &encoded_extensions)?;
         Ok(())
     }
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
@@ -116,29 +147,47 @@ impl Codec for GroupContext {
         let epoch = GroupEpoch::decode(cursor)?;
         let tree_hash = decode_vec(VecSize::VecU8, cursor)?;
         let confirmed_transcript_hash = decode_vec(VecSize::VecU8, cursor)?;
+        let extensions = extensions_vec_from_cursor(cursor)?;
         Ok(GroupContext {
             group_id,
             epoch,
             tree_hash,
             confirmed_transcript_hash,
+            extensions,
         })
     }
 }
 
 /// Configuration for an MLS group.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct GroupConfig {
     /// Flag whether to send the ratchet tree along with the `GroupInfo` or not.
     /// Defaults to false.
     pub add_ratchet_tree_extension: bool,
-    pub padding_block_size: u32,
+    /// The padding strategy for application and handshake messages.
+    /// Defaults to padding to a multiple of 10 bytes.
+    pub padding_policy: PaddingPolicy,
+    /// Custom padding strategy that is used instead of the `padding_policy`.
+    /// It isn't persisted with the group. Defaults to `None`.
+    pub custom_padding: Option<SharedPadding>,
+    /// Number of past epochs for which the secrets are retained to decrypt
+    /// late application messages. Defaults to 0.
     pub additional_as_epochs: u32,
+    /// Maximum age in seconds of retained past epochs. `0` means that past
+    /// epochs only expire by count. Defaults to 0.
+    pub additional_as_epochs_max_age: u64,
+    /// Out-of-order tolerance and maximum forward distance of the
+    /// `SenderRatchet`s.
+    pub sender_ratchet_configuration: SenderRatchetConfiguration,
+    /// The clock used to validate the lifetime of key packages and to expire
+    /// retained past epochs. Defaults to the `SystemClock`.
+    pub clock: SharedClock,
 }
 
 impl GroupConfig {
-    /// Get the padding block size used in this config.
-    pub fn padding_block_size(&self) -> u32 {
-        self.padding_block_size
+    /// Get the padding policy used in this config.
+    pub fn padding_policy(&self) -> &PaddingPolicy {
+        &self.padding_policy
     }
 }
 
@@ -146,25 +195,46 @@ impl Default for GroupConfig {
     fn default() -> Self {
         Self {
             add_ratchet_tree_extension: false,
-            padding_block_size: 10,
+            padding_policy: PaddingPolicy::default(),
+            custom_padding: None,
             additional_as_epochs: 0,
+            additional_as_epochs_max_age: 0,
+            sender_ratchet_configuration: SenderRatchetConfiguration::default(),
+            clock: SharedClock::default(),
         }
     }
 }
 
 impl Codec for GroupConfig {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
-        self.padding_block_size.encode(buffer)?;
+        self.padding_policy.encode(buffer)?;
         self.additional_as_epochs.encode(buffer)?;
+        self.additional_as_epochs_max_age.encode(buffer)?;
+        self.sender_ratchet_configuration
+            .out_of_order_tolerance()
+            .encode(buffer)?;
+        self.sender_ratchet_configuration
+            .maximum_forward_distance()
+            .encode(buffer)?;
         Ok(())
     }
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
-        let padding_block_size = u32::decode(cursor)?;
+        let padding_policy = PaddingPolicy::decode(cursor)?;
         let additional_as_epochs = u32::decode(cursor)?;
+        let additional_as_epochs_max_age = u64::decode(cursor)?;
+        let out_of_order_tolerance = u32::decode(cursor)?;
+        let maximum_forward_distance = u32::decode(cursor)?;
         Ok(GroupConfig {
             add_ratchet_tree_extension: false,
-            padding_block_size,
+            padding_policy,
+            custom_padding: None,
             additional_as_epochs,
+            additional_as_epochs_max_age,
+            sender_ratchet_configuration: SenderRatchetConfiguration::new(
+                out_of_order_tolerance,
+                maximum_forward_distance,
+            ),
+            clock: SharedClock::default(),
         })
     }
 }
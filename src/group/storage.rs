@@ -0,0 +1,179 @@
+//! # Encrypted storage
+//!
+//! The state of an `MlsGroup` or `ManagedGroup` contains secret key material,
+//! such as the epoch secrets, the secret tree and private keys of the ratchet
+//! tree. Instead of persisting it in the clear with `save()`, it can be sealed
+//! with an application-provided [`StorageKey`] with `save_encrypted()`.
+//! `load_encrypted()` authenticates the sealed state before deserializing it.
+//!
+//! The sealed state has the following format:
+//!
+//! ```text
+//! struct {
+//!     opaque magic[4] = "OMLS";
+//!     uint16 version;
+//!     opaque group_id<0..255>;
+//!     uint64 epoch;
+//!     opaque nonce[12];
+//!     opaque ciphertext[rest];
+//! } SealedState;
+//! ```
+//!
+//! The ciphertext is the serialized state encrypted with ChaCha20Poly1305 under
+//! the storage key and the nonce. The header (magic, version, group ID, epoch
+//! and nonce) is used as additional authenticated data. ChaCha20Poly1305 is
+//! used independently of the ciphersuite of the group, since it doesn't require
+//! hardware support.
+//!
+//! Since the group ID and the epoch are authenticated, `load_encrypted()` can
+//! reject a sealed state of another group (`StorageError::GroupIdMismatch`)
+//! and a state that is older than the last epoch known to the application
+//! (`StorageError::EpochRollback`). Sealed states of version 1 didn't bind the
+//! group ID and epoch and are rejected with `StorageError::UnsupportedVersion`.
+
+use evercrypt::prelude::AeadMode;
+use zeroize::Zeroize;
+
+use super::errors::StorageError;
+use crate::ciphersuite::{AeadKey, AeadNonce, NONCE_BYTES};
+use crate::codec::{Codec, Cursor};
+use crate::group::{GroupEpoch, GroupId};
+use crate::utils::randombytes;
+
+/// The magic bytes at the beginning of the sealed state.
+const STORAGE_MAGIC: [u8; 4] = *b"OMLS";
+/// The current version of the sealed state format.
+const STORAGE_VERSION: u16 = 2;
+/// The length of a storage key in bytes.
+pub const STORAGE_KEY_LENGTH: usize = 32;
+
+/// A symmetric key used to seal the persisted state of a group.
+///
+/// The key has to be provided by the application, e.g. from the key store of
+/// the platform, and must be kept secret. The key value is zeroized when the
+/// `StorageKey` is dropped.
+#[derive(PartialEq)]
+pub struct StorageKey {
+    value: Vec<u8>,
+}
+
+impl StorageKey {
+    /// Create a `StorageKey` from a byte slice. Returns
+    /// `StorageError::InvalidKeyLength` if the slice is not
+    /// `STORAGE_KEY_LENGTH` bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, StorageError> {
+        if bytes.len() != STORAGE_KEY_LENGTH {
+            return Err(StorageError::InvalidKeyLength);
+        }
+        Ok(StorageKey {
+            value: bytes.to_vec(),
+        })
+    }
+
+    /// Generate a new random `StorageKey`.
+    pub fn random() -> Self {
+        StorageKey {
+            value: randombytes(STORAGE_KEY_LENGTH),
+        }
+    }
+
+    /// Get a slice to the key value, e.g. to store it in the key store of the
+    /// platform.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+
+    fn aead_key(&self) -> AeadKey {
+        AeadKey::from_slice(AeadMode::Chacha20Poly1305, &self.value)
+    }
+}
+
+impl Drop for StorageKey {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl std::fmt::Debug for StorageKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StorageKey")
+    }
+}
+
+/// Seal the serialized state of the group with the given `group_id` and
+/// `epoch` with the storage key.
+pub(crate) fn seal(
+    storage_key: &StorageKey,
+    group_id: &GroupId,
+    epoch: GroupEpoch,
+    state: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    let nonce = AeadNonce::from_random();
+    let mut sealed_state = STORAGE_MAGIC.to_vec();
+    STORAGE_VERSION
+        .encode(&mut sealed_state)
+        .map_err(|_| StorageError::EncryptionError)?;
+    group_id
+        .encode(&mut sealed_state)
+        .map_err(|_| StorageError::EncryptionError)?;
+    epoch
+        .encode(&mut sealed_state)
+        .map_err(|_| StorageError::EncryptionError)?;
+    sealed_state.extend_from_slice(nonce.as_slice());
+    let ciphertext = storage_key
+        .aead_key()
+        .aead_seal(state, &sealed_state, &nonce)
+        .map_err(|_| StorageError::EncryptionError)?;
+    sealed_state.extend_from_slice(&ciphertext);
+    Ok(sealed_state)
+}
+
+/// Authenticate and decrypt a sealed state with the storage key. The sealed
+/// state must belong to the group with the given `group_id` and its epoch must
+/// not be older than `min_epoch`. The returned bytes can then be deserialized.
+pub(crate) fn open(
+    storage_key: &StorageKey,
+    group_id: &GroupId,
+    min_epoch: GroupEpoch,
+    sealed_state: &[u8],
+) -> Result<Vec<u8>, StorageError> {
+    let cursor = &mut Cursor::new(sealed_state);
+    let magic = cursor
+        .consume(STORAGE_MAGIC.len())
+        .map_err(|_| StorageError::InvalidHeader)?;
+    if magic != &STORAGE_MAGIC[..] {
+        return Err(StorageError::InvalidHeader);
+    }
+    let version = u16::decode(cursor).map_err(|_| StorageError::InvalidHeader)?;
+    if version != STORAGE_VERSION {
+        return Err(StorageError::UnsupportedVersion);
+    }
+    let sealed_group_id = GroupId::decode(cursor).map_err(|_| StorageError::InvalidHeader)?;
+    let sealed_epoch = GroupEpoch::decode(cursor).map_err(|_| StorageError::InvalidHeader)?;
+    let mut nonce = [0u8; NONCE_BYTES];
+    nonce.copy_from_slice(
+        cursor
+            .consume(NONCE_BYTES)
+            .map_err(|_| StorageError::InvalidHeader)?,
+    );
+    let header_length = STORAGE_MAGIC.len() + 2 + 1 + sealed_group_id.value.len() + 8 + NONCE_BYTES;
+    let (header, ciphertext) = sealed_state.split_at(header_length);
+    let state = storage_key
+        .aead_key()
+        .aead_open(ciphertext, header, &AeadNonce::from_bytes(nonce))
+        .map_err(|_| StorageError::DecryptionError)?;
+    // The header is authenticated at this point.
+    if &sealed_group_id != group_id {
+        return Err(StorageError::GroupIdMismatch);
+    }
+    if sealed_epoch.0 < min_epoch.0 {
+        return Err(StorageError::EpochRollback);
+    }
+    Ok(state)
+}
+
+impl From<StorageError> for std::io::Error {
+    fn from(e: StorageError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
@@ -25,10 +25,15 @@ impl Codec for KeyPackage {
             signature,
         };
 
-        if kp.verify().is_err() {
+        // The lifetime is not checked against the current time here because
+        // the current time might not be known yet. It is checked when the key
+        // package is verified.
+        if kp.verify_internal(None).is_err() {
             log::error!("Error verifying a key package after decoding\n{:?}", kp);
             return Err(CodecError::DecodingError);
         }
         Ok(kp)
     }
 }
+
+impl TlsSerialization for KeyPackage {}
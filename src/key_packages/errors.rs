@@ -12,11 +12,16 @@ implement_error! {
             UnknownConfigError = "An unknown configuration error occurred.",
             MandatoryExtensionsMissing = "A mandatory extension is missing in the key package.",
             InvalidLifetimeExtension = "The lifetime extension of the key package is not valid.",
+            LifetimeTooLong = "The total lifetime of the key package exceeds the maximum lifetime.",
             InvalidSignature = "The key package signature is not valid.",
             LibraryError = "An unknown OpenMLS library error occurred.",
             DuplicateExtension = "Duplicate extensions are not allowed.",
             NoCiphersuitesSupplied = "Creating a new key package requires at least one ciphersuite.",
             CiphersuiteMismatch = "The list of ciphersuites is not consistent with the capabilities extension.",
+            UserIdMismatch = "The user ID extension doesn't match the identity of the credential.",
+            SignatureSchemeMismatch = "None of the credential bundles uses the signature scheme of the ciphersuite.",
+            CapabilitiesExtensionSupplied = "The capabilities extension is created automatically and must not be supplied.",
+            IdentityMismatch = "The credential bundles don't have the same identity.",
         }
         Complex {
             ExtensionError(ExtensionError) =
@@ -3,12 +3,13 @@ use log::error;
 use evercrypt::rand_util::*;
 
 use crate::ciphersuite::*;
+use crate::clock::{Clock, SystemClock};
 use crate::codec::*;
 use crate::config::{Config, ProtocolVersion};
 use crate::credentials::*;
 use crate::extensions::{
     CapabilitiesExtension, Extension, ExtensionError, ExtensionStruct, ExtensionType,
-    LifetimeExtension, ParentHashExtension,
+    LifetimeExtension, ParentHashExtension, UserIdExtension,
 };
 
 use serde::{
@@ -19,7 +20,9 @@ use serde::{
 
 mod codec;
 pub mod errors;
+mod store;
 pub(crate) use errors::*;
+pub use store::*;
 
 #[cfg(tests)]
 mod test_key_packages;
@@ -52,9 +55,22 @@ impl KeyPackage {
     /// Verify that this key package is valid:
     /// * verify that the signature on this key package is valid
     /// * verify that all mandatory extensions are present
-    /// * make sure that the lifetime is valid
+    /// * make sure that the lifetime is valid and doesn't exceed the maximum
+    ///   lifetime set in the `Config`
     /// Returns `Ok(())` if all checks succeed and `KeyPackageError` otherwise
     pub fn verify(&self) -> Result<(), KeyPackageError> {
+        self.verify_with_clock(&SystemClock)
+    }
+
+    /// Verify that this key package is valid like [`KeyPackage::verify()`],
+    /// but check the lifetime against the current time of the `clock`.
+    pub fn verify_with_clock(&self, clock: &dyn Clock) -> Result<(), KeyPackageError> {
+        self.verify_internal(Some(clock))
+    }
+
+    /// Verify this key package. Whether the lifetime is valid at the current
+    /// time is only checked if a `clock` is given.
+    fn verify_internal(&self, clock: Option<&dyn Clock>) -> Result<(), KeyPackageError> {
         //  First make sure that all mandatory extensions are present.
         let mut mandatory_extensions_found = MANDATORY_EXTENSIONS.to_vec();
         for extension in self.extensions.iter() {
@@ -68,9 +84,15 @@ impl KeyPackage {
             if extension.extension_type() == ExtensionType::Lifetime {
                 match extension.to_lifetime_extension() {
                     Ok(e) => {
-                        if !e.is_valid() {
-                            log::error!("Invalid lifetime extension in key package.");
-                            return Err(KeyPackageError::InvalidLifetimeExtension);
+                        if !e.has_acceptable_range() {
+                            log::error!("The lifetime of the key package is too long.");
+                            return Err(KeyPackageError::LifetimeTooLong);
+                        }
+                        if let Some(clock) = clock {
+                            if !e.is_valid_with_clock(clock) {
+                                log::error!("Invalid lifetime extension in key package.");
+                                return Err(KeyPackageError::InvalidLifetimeExtension);
+                            }
                         }
                     }
                     Err(e) => {
@@ -88,6 +110,9 @@ impl KeyPackage {
             return Err(KeyPackageError::MandatoryExtensionsMissing);
         }
 
+        // Make sure the user ID belongs to the credential.
+        self.check_user_id()?;
+
         // Verify the signature on this key package.
         if self
             .credential
@@ -117,6 +142,29 @@ impl KeyPackage {
         ))
     }
 
+    /// Get the ID of the user this key package belongs to.
+    /// This is the value of the user ID extension if present and the identity
+    /// of the credential otherwise. A user ID that doesn't match the identity
+    /// of the credential (see [`UserIdExtension`](crate::prelude::UserIdExtension))
+    /// is ignored.
+    pub fn user_id(&self) -> &[u8] {
+        match self.user_id_extension() {
+            Some(user_id_extension)
+                if user_id_extension.matches_identity(self.credential.identity()) =>
+            {
+                user_id_extension.as_slice()
+            }
+            _ => self.credential.identity().as_slice(),
+        }
+    }
+
+    /// Returns `true` if this is a last resort key package, i.e. if it has a
+    /// last resort extension, and `false` otherwise.
+    pub fn is_last_resort(&self) -> bool {
+        self.extension_with_type(ExtensionType::LastResort)
+            .is_some()
+    }
+
     /// Add (or replace) an extension to the KeyPackage.
     /// Make sure to re-sign the package before using it. It will be invalid
     /// after calling this function!
@@ -162,10 +210,31 @@ impl KeyPackage {
             extensions,
             signature: Signature::new_empty(),
         };
+        key_package.check_user_id()?;
         key_package.sign(&credential_bundle);
         Ok(key_package)
     }
 
+    /// Get the user ID extension if present.
+    fn user_id_extension(&self) -> Option<&UserIdExtension> {
+        self.extension_with_type(ExtensionType::UserId)
+            .and_then(|extension| extension.to_user_id_extension().ok())
+    }
+
+    /// Returns a `UserIdMismatch` error if the key package has a user ID
+    /// extension that doesn't match the identity of the credential.
+    fn check_user_id(&self) -> Result<(), KeyPackageError> {
+        match self.user_id_extension() {
+            Some(user_id_extension)
+                if !user_id_extension.matches_identity(self.credential.identity()) =>
+            {
+                error!("The user ID doesn't match the credential.");
+                Err(KeyPackageError::UserIdMismatch)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Compile the unsigned payload to create the signature required in the
     /// signature field.
     fn unsigned_payload(&self) -> Result<Vec<u8>, CodecError> {
@@ -223,6 +292,13 @@ impl KeyPackage {
         &self.hpke_init_key
     }
 
+    /// Set a new credential.
+    /// Make sure to re-sign the package with the corresponding credential
+    /// bundle before using it. It will be invalid after calling this function!
+    pub(crate) fn set_credential(&mut self, credential: Credential) {
+        self.credential = credential;
+    }
+
     /// Set a new HPKE init key.
     pub(crate) fn set_hpke_init_key(&mut self, hpke_init_key: HPKEPublicKey) {
         self.hpke_init_key = hpke_init_key;
@@ -258,10 +334,102 @@ impl KeyPackageBundle {
         credential_bundle: &CredentialBundle,
         extensions: Vec<Box<dyn Extension>>,
     ) -> Result<Self, KeyPackageError> {
-        debug_assert!(!ciphersuites.is_empty());
-        let ciphersuite = Config::ciphersuite(ciphersuites[0]).unwrap();
+        Self::new_with_clock(ciphersuites, credential_bundle, extensions, &SystemClock)
+    }
+
+    /// Create a new `KeyPackageBundle` like [`KeyPackageBundle::new()`], but
+    /// start the default lifetime at the current time of the `clock`.
+    ///
+    /// Returns a new `KeyPackageBundle`.
+    pub fn new_with_clock(
+        ciphersuites: &[CiphersuiteName],
+        credential_bundle: &CredentialBundle,
+        extensions: Vec<Box<dyn Extension>>,
+        clock: &dyn Clock,
+    ) -> Result<Self, KeyPackageError> {
+        let ciphersuite = match ciphersuites.first() {
+            Some(ciphersuite_name) => Config::ciphersuite(*ciphersuite_name)?,
+            None => return Err(KeyPackageError::NoCiphersuitesSupplied),
+        };
         let leaf_secret = Secret::from(get_random_vec(ciphersuite.hash_length()));
-        Self::new_from_leaf_secret(ciphersuites, credential_bundle, extensions, leaf_secret)
+        Self::new_from_leaf_secret(
+            ciphersuites,
+            credential_bundle,
+            extensions,
+            leaf_secret,
+            clock,
+        )
+    }
+
+    /// Create a set of `KeyPackageBundle`s with fresh `HPKEKeyPair`s, one for
+    /// each of the `ciphersuites`. All key packages use the same `extensions`.
+    ///
+    /// Each key package is signed with the first of the `credential_bundles`
+    /// that uses the signature scheme of its ciphersuite, so one credential
+    /// bundle per signature scheme is enough. Returns a
+    /// `SignatureSchemeMismatch` error if there is no such credential bundle
+    /// for one of the `ciphersuites`. All `credential_bundles` must have the
+    /// same identity, otherwise an `IdentityMismatch` error is returned.
+    ///
+    /// The capabilities extension of each key package lists all
+    /// `ciphersuites`, starting with the ciphersuite of the key package. It is
+    /// added automatically and must not be part of the `extensions`, otherwise
+    /// a `CapabilitiesExtensionSupplied` error is returned. Use
+    /// [`KeyPackageBundle::new()`] for a single key package with a custom
+    /// capabilities extension.
+    ///
+    /// When adding a member with
+    /// [`ManagedGroup::add_members()`](crate::prelude::ManagedGroup::add_members()),
+    /// all key packages of the set can be passed and the one matching the
+    /// group's ciphersuite is picked.
+    ///
+    /// Returns the `KeyPackageBundle`s in the order of the `ciphersuites`.
+    pub fn new_for_ciphersuites(
+        ciphersuites: &[CiphersuiteName],
+        credential_bundles: &[&CredentialBundle],
+        extensions: Vec<Box<dyn Extension>>,
+    ) -> Result<Vec<Self>, KeyPackageError> {
+        if ciphersuites.is_empty() {
+            return Err(KeyPackageError::NoCiphersuitesSupplied);
+        }
+        if extensions
+            .iter()
+            .any(|e| e.extension_type() == ExtensionType::Capabilities)
+        {
+            return Err(KeyPackageError::CapabilitiesExtensionSupplied);
+        }
+        // All key packages of the set belong to the same client
+        if let Some((first, others)) = credential_bundles.split_first() {
+            let identity = first.credential().identity();
+            if others
+                .iter()
+                .any(|credential_bundle| credential_bundle.credential().identity() != identity)
+            {
+                return Err(KeyPackageError::IdentityMismatch);
+            }
+        }
+        ciphersuites
+            .iter()
+            .map(|ciphersuite_name| {
+                let ciphersuite = Config::ciphersuite(*ciphersuite_name)?;
+                let credential_bundle = credential_bundles
+                    .iter()
+                    .find(|credential_bundle| {
+                        credential_bundle
+                            .credential()
+                            .ciphersuite()
+                            .has_same_signature_scheme(ciphersuite)
+                    })
+                    .ok_or(KeyPackageError::SignatureSchemeMismatch)?;
+                let mut ordered_ciphersuites = vec![*ciphersuite_name];
+                ordered_ciphersuites.extend(
+                    ciphersuites
+                        .iter()
+                        .filter(|&other| other != ciphersuite_name),
+                );
+                Self::new(&ordered_ciphersuites, credential_bundle, extensions.clone())
+            })
+            .collect()
     }
 
     /// Replace the init key in the `KeyPackage` with a random one and return a
@@ -295,11 +463,40 @@ impl KeyPackageBundle {
     ///
     /// Returns a new `KeyPackageBundle`.
     pub fn new_with_keypair(
+        ciphersuites: &[CiphersuiteName],
+        credential_bundle: &CredentialBundle,
+        extensions: Vec<Box<dyn Extension>>,
+        key_pair: HPKEKeyPair,
+        leaf_secret: Secret,
+    ) -> Result<Self, KeyPackageError> {
+        Self::new_with_keypair_and_clock(
+            ciphersuites,
+            credential_bundle,
+            extensions,
+            key_pair,
+            leaf_secret,
+            &SystemClock,
+        )
+    }
+
+    /// Get a reference to the `KeyPackage`.
+    pub fn key_package(&self) -> &KeyPackage {
+        &self.key_package
+    }
+}
+
+/// Private `KeyPackageBundle` functions.
+impl KeyPackageBundle {
+    /// Create a new `KeyPackageBundle` like
+    /// [`KeyPackageBundle::new_with_keypair()`]. If no lifetime extension is
+    /// given, the default lifetime starts at the current time of the `clock`.
+    fn new_with_keypair_and_clock(
         ciphersuites: &[CiphersuiteName],
         credential_bundle: &CredentialBundle,
         mut extensions: Vec<Box<dyn Extension>>,
         key_pair: HPKEKeyPair,
         leaf_secret: Secret,
+        clock: &dyn Clock,
     ) -> Result<Self, KeyPackageError> {
         if ciphersuites.is_empty() {
             let error = KeyPackageError::NoCiphersuitesSupplied;
@@ -360,7 +557,10 @@ impl KeyPackageBundle {
             .iter()
             .any(|e| e.extension_type() == ExtensionType::Lifetime)
         {
-            extensions.push(Box::new(LifetimeExtension::default()));
+            extensions.push(Box::new(LifetimeExtension::new_with_clock(
+                Config::default_key_package_lifetime(),
+                clock,
+            )));
         }
         let (private_key, public_key) = key_pair.into_keys();
         let key_package =
@@ -372,19 +572,12 @@ impl KeyPackageBundle {
         })
     }
 
-    /// Get a reference to the `KeyPackage`.
-    pub fn key_package(&self) -> &KeyPackage {
-        &self.key_package
-    }
-}
-
-/// Private `KeyPackageBundle` functions.
-impl KeyPackageBundle {
     fn new_from_leaf_secret(
         ciphersuites: &[CiphersuiteName],
         credential_bundle: &CredentialBundle,
         extensions: Vec<Box<dyn Extension>>,
         leaf_secret: Secret,
+        clock: &dyn Clock,
     ) -> Result<Self, KeyPackageError> {
         if ciphersuites.is_empty() {
             let error = KeyPackageError::NoCiphersuitesSupplied;
@@ -395,15 +588,16 @@ impl KeyPackageBundle {
             return Err(error);
         }
 
-        let ciphersuite = Config::ciphersuite(ciphersuites[0]).unwrap();
+        let ciphersuite = Config::ciphersuite(ciphersuites[0])?;
         let leaf_node_secret = Self::derive_leaf_node_secret(ciphersuite, &leaf_secret);
         let keypair = ciphersuite.derive_hpke_keypair(&leaf_node_secret);
-        Self::new_with_keypair(
+        Self::new_with_keypair_and_clock(
             ciphersuites,
             credential_bundle,
             extensions,
             keypair,
             leaf_secret,
+            clock,
         )
     }
 
@@ -439,6 +633,17 @@ impl KeyPackageBundle {
         &mut self.key_package
     }
 
+    /// Create a copy of this bundle. Bundles are not `Clone` because they
+    /// should only be used once. Only the bundles of last resort key packages
+    /// are used more than once.
+    pub(crate) fn duplicate(&self) -> Self {
+        Self::new_from_values(
+            self.key_package.clone(),
+            HPKEPrivateKey::new(self.private_key.as_slice().to_vec()),
+            self.leaf_secret.clone(),
+        )
+    }
+
     /// Get a reference to the `HPKEPrivateKey`.
     pub(crate) fn private_key(&self) -> &HPKEPrivateKey {
         &self.private_key
@@ -0,0 +1,283 @@
+//! # Key package store
+//!
+//! A client publishes a number of `KeyPackage`s that others can use to add it
+//! to groups, and has to keep the corresponding `KeyPackageBundle`s until it
+//! receives a `Welcome` message that references one of them. The
+//! [`KeyPackageStore`] keeps track of these bundles, indexed by the hash of
+//! their key package, which is the value a `Welcome` message references in its
+//! `EncryptedGroupSecrets`.
+//!
+//! Implementations only have to provide storage for the bundles. Generating
+//! and replenishing bundles, looking up the bundle for a `Welcome` and purging
+//! expired bundles is implemented on top of that. A bundle is only removed
+//! after the group was joined successfully, and bundles of last resort key
+//! packages are not removed when they are used to join a group. An expired
+//! last resort bundle is only purged once the store contains a valid one, see
+//! [`KeyPackageStore::replenish_last_resort()`]. The
+//! [`InMemoryKeyPackageStore`] is a simple implementation that keeps all
+//! bundles in memory and can be persisted.
+
+use std::collections::HashMap;
+use std::io::{Error, Read, Write};
+
+use crate::clock::Clock;
+use crate::extensions::LastResortExtension;
+use crate::messages::Welcome;
+
+use super::*;
+
+/// Storage for `KeyPackageBundle`s, indexed by the hash of their key package.
+pub trait KeyPackageStore {
+    /// Store the `key_package_bundle`. An existing bundle with the same key
+    /// package hash is replaced.
+    fn store(&mut self, key_package_bundle: KeyPackageBundle);
+
+    /// Get a reference to the bundle whose key package has the `hash`.
+    fn get(&self, hash: &[u8]) -> Option<&KeyPackageBundle>;
+
+    /// Remove the bundle whose key package has the `hash` from the store and
+    /// return it.
+    fn take(&mut self, hash: &[u8]) -> Option<KeyPackageBundle>;
+
+    /// Get the hashes of all key packages in the store.
+    fn hashes(&self) -> Vec<Vec<u8>>;
+
+    /// Get the number of bundles in the store.
+    fn len(&self) -> usize {
+        self.hashes().len()
+    }
+
+    /// Returns `true` if the store contains no bundles.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get copies of all key packages in the store, i.e. the key packages that
+    /// can be published.
+    fn key_packages(&self) -> Vec<KeyPackage> {
+        self.hashes()
+            .iter()
+            .filter_map(|hash| self.get(hash))
+            .map(|key_package_bundle| key_package_bundle.key_package().clone())
+            .collect()
+    }
+
+    /// Generate `n` fresh bundles for the given `ciphersuites`,
+    /// `credential_bundle` and `extensions` and store them.
+    /// See [`KeyPackageBundle::new()`] for details.
+    ///
+    /// Returns the new key packages, which can be published.
+    fn generate(
+        &mut self,
+        n: usize,
+        ciphersuites: &[CiphersuiteName],
+        credential_bundle: &CredentialBundle,
+        extensions: Vec<Box<dyn Extension>>,
+    ) -> Result<Vec<KeyPackage>, KeyPackageError> {
+        let mut key_packages = Vec::with_capacity(n);
+        for _ in 0..n {
+            let key_package_bundle =
+                KeyPackageBundle::new(ciphersuites, credential_bundle, extensions.clone())?;
+            key_packages.push(key_package_bundle.key_package().clone());
+            self.store(key_package_bundle);
+        }
+        Ok(key_packages)
+    }
+
+    /// Generate fresh bundles until the store contains at least `target`
+    /// bundles. Bundles of last resort key packages are not counted, see
+    /// [`KeyPackageStore::replenish_last_resort()`] for those. See
+    /// [`KeyPackageStore::generate()`] for details.
+    ///
+    /// Returns the new key packages, which can be published.
+    fn replenish(
+        &mut self,
+        target: usize,
+        ciphersuites: &[CiphersuiteName],
+        credential_bundle: &CredentialBundle,
+        extensions: Vec<Box<dyn Extension>>,
+    ) -> Result<Vec<KeyPackage>, KeyPackageError> {
+        let count = self
+            .hashes()
+            .iter()
+            .filter_map(|hash| self.get(hash))
+            .filter(|key_package_bundle| !key_package_bundle.key_package().is_last_resort())
+            .count();
+        let n = target.saturating_sub(count);
+        self.generate(n, ciphersuites, credential_bundle, extensions)
+    }
+
+    /// Generate a fresh bundle of a last resort key package for the given
+    /// `ciphersuites`, `credential_bundle` and `extensions` if the store
+    /// doesn't contain one that is valid at the current time of the `clock`.
+    /// The `LastResortExtension` is added to the `extensions`.
+    ///
+    /// Returns the new key package, which can be published, or `None` if the
+    /// store already contains a valid last resort key package.
+    fn replenish_last_resort(
+        &mut self,
+        ciphersuites: &[CiphersuiteName],
+        credential_bundle: &CredentialBundle,
+        mut extensions: Vec<Box<dyn Extension>>,
+        clock: &dyn Clock,
+    ) -> Result<Option<KeyPackage>, KeyPackageError> {
+        if has_valid_last_resort(self, clock) {
+            return Ok(None);
+        }
+        extensions.push(Box::new(LastResortExtension::new()));
+        let key_package_bundle =
+            KeyPackageBundle::new_with_clock(ciphersuites, credential_bundle, extensions, clock)?;
+        let key_package = key_package_bundle.key_package().clone();
+        self.store(key_package_bundle);
+        Ok(Some(key_package))
+    }
+
+    /// Get a copy of the bundle whose key package is referenced by the
+    /// `EncryptedGroupSecrets` of the `welcome` message. The bundle can be used
+    /// to join the group with
+    /// [`MlsGroup::new_from_welcome()`](`crate::group::MlsGroup::new_from_welcome()`).
+    ///
+    /// The bundle stays in the store, so that joining can be retried if it
+    /// fails. Once the group was joined, the bundle has to be removed with
+    /// [`KeyPackageStore::remove_for_welcome()`].
+    ///
+    /// Returns `None` if the `welcome` doesn't reference any key package in
+    /// the store.
+    fn bundle_for_welcome(&self, welcome: &Welcome) -> Option<KeyPackageBundle> {
+        let hash = referenced_hash(self, welcome)?;
+        self.get(hash)
+            .map(|key_package_bundle| key_package_bundle.duplicate())
+    }
+
+    /// Remove the bundle whose key package is referenced by the
+    /// `EncryptedGroupSecrets` of the `welcome` message from the store after
+    /// the group was joined. The bundle of a last resort key package is kept
+    /// in the store.
+    ///
+    /// Returns `true` if a bundle was removed.
+    fn remove_for_welcome(&mut self, welcome: &Welcome) -> bool {
+        let hash = match referenced_hash(self, welcome) {
+            Some(hash) => hash,
+            None => return false,
+        };
+        match self.get(hash) {
+            Some(key_package_bundle) if !key_package_bundle.key_package().is_last_resort() => {
+                self.take(hash).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove all bundles from the store whose key package isn't valid at the
+    /// current time of the `clock`, e.g. because its lifetime expired.
+    ///
+    /// Bundles of last resort key packages are only removed if the store
+    /// contains a valid last resort key package, so that a `Welcome` for the
+    /// last published one can still be processed. Use
+    /// [`KeyPackageStore::replenish_last_resort()`] to replace an expired last
+    /// resort key package.
+    ///
+    /// Returns the number of removed bundles.
+    fn purge_expired(&mut self, clock: &dyn Clock) -> usize {
+        let keep_last_resort = !has_valid_last_resort(self, clock);
+        let expired: Vec<Vec<u8>> = self
+            .hashes()
+            .into_iter()
+            .filter(|hash| match self.get(hash) {
+                Some(key_package_bundle) => {
+                    !(keep_last_resort && key_package_bundle.key_package().is_last_resort())
+                        && key_package_bundle
+                            .key_package()
+                            .verify_with_clock(clock)
+                            .is_err()
+                }
+                None => false,
+            })
+            .collect();
+        for hash in expired.iter() {
+            let _ = self.take(hash);
+        }
+        expired.len()
+    }
+}
+
+/// Get the first key package hash in the `EncryptedGroupSecrets` of the
+/// `welcome` message for which the `store` has a bundle.
+fn referenced_hash<'a, S: KeyPackageStore + ?Sized>(
+    store: &S,
+    welcome: &'a Welcome,
+) -> Option<&'a [u8]> {
+    welcome
+        .secrets()
+        .iter()
+        .map(|egs| egs.key_package_hash.as_slice())
+        .find(|hash| store.get(hash).is_some())
+}
+
+/// Returns `true` if the `store` contains a bundle of a last resort key
+/// package that is valid at the current time of the `clock`.
+fn has_valid_last_resort<S: KeyPackageStore + ?Sized>(store: &S, clock: &dyn Clock) -> bool {
+    store
+        .hashes()
+        .iter()
+        .filter_map(|hash| store.get(hash))
+        .map(|key_package_bundle| key_package_bundle.key_package())
+        .any(|key_package| {
+            key_package.is_last_resort() && key_package.verify_with_clock(clock).is_ok()
+        })
+}
+
+/// A `KeyPackageStore` that keeps all bundles in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryKeyPackageStore {
+    key_package_bundles: HashMap<Vec<u8>, KeyPackageBundle>,
+}
+
+impl InMemoryKeyPackageStore {
+    /// Create a new, empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the store from persisted state
+    pub fn load<R: Read>(reader: R) -> Result<InMemoryKeyPackageStore, Error> {
+        let key_package_bundles: Vec<KeyPackageBundle> = serde_json::from_reader(reader)?;
+        let mut store = Self::new();
+        for key_package_bundle in key_package_bundles {
+            store.store(key_package_bundle);
+        }
+        Ok(store)
+    }
+
+    /// Persists the store. Note that the persisted state contains the private
+    /// keys of the bundles and has to be protected accordingly.
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let key_package_bundles: Vec<&KeyPackageBundle> =
+            self.key_package_bundles.values().collect();
+        let serialized_store = serde_json::to_string_pretty(&key_package_bundles)?;
+        writer.write_all(&serialized_store.into_bytes())
+    }
+}
+
+impl KeyPackageStore for InMemoryKeyPackageStore {
+    fn store(&mut self, key_package_bundle: KeyPackageBundle) {
+        self.key_package_bundles
+            .insert(key_package_bundle.key_package().hash(), key_package_bundle);
+    }
+
+    fn get(&self, hash: &[u8]) -> Option<&KeyPackageBundle> {
+        self.key_package_bundles.get(hash)
+    }
+
+    fn take(&mut self, hash: &[u8]) -> Option<KeyPackageBundle> {
+        self.key_package_bundles.remove(hash)
+    }
+
+    fn hashes(&self) -> Vec<Vec<u8>> {
+        self.key_package_bundles.keys().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.key_package_bundles.len()
+    }
+}
@@ -28,6 +28,7 @@ mod utils;
 pub mod error;
 
 mod ciphersuite;
+mod clock;
 mod codec;
 pub mod config;
 mod credentials;
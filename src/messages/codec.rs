@@ -4,6 +4,7 @@ use super::*;
 use crate::key_packages::KeyPackage;
 
 use std::convert::TryFrom;
+use std::io::Write;
 
 impl Codec for GroupInfo {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
@@ -11,8 +12,32 @@ impl Codec for GroupInfo {
         self.signature.encode(buffer)?;
         Ok(())
     }
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        let group_id = GroupId::decode(cursor)?;
+        let epoch = GroupEpoch::decode(cursor)?;
+        let tree_hash = decode_vec(VecSize::VecU8, cursor)?;
+        let confirmed_transcript_hash = decode_vec(VecSize::VecU8, cursor)?;
+        let group_context_extensions = extensions_vec_from_cursor(cursor)?;
+        let extensions = extensions_vec_from_cursor(cursor)?;
+        let confirmation_tag = decode_vec(VecSize::VecU8, cursor)?;
+        let signer_index = LeafIndex::from(u32::decode(cursor)?);
+        let signature = Signature::decode(cursor)?;
+        Ok(GroupInfo {
+            group_id,
+            epoch,
+            tree_hash,
+            confirmed_transcript_hash,
+            group_context_extensions,
+            extensions,
+            confirmation_tag,
+            signer_index,
+            signature,
+        })
+    }
 }
 
+impl TlsSerialization for GroupInfo {}
+
 impl Codec for Commit {
     fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
         encode_vec(VecSize::VecU32, buffer, &self.proposals)?;
@@ -69,15 +94,46 @@ impl Codec for Welcome {
         self.version.encode(buffer)?;
         self.cipher_suite.name().encode(buffer)?;
         encode_vec(VecSize::VecU32, buffer, &self.secrets)?;
-        encode_vec(VecSize::VecU32, buffer, &self.encrypted_group_info)?;
+        encode_bytes(VecSize::VecU32, buffer, &self.encrypted_group_info)?;
         Ok(())
     }
     fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        Ok(Welcome::decode_borrowed(cursor)?.into_owned())
+    }
+}
+
+impl TlsSerialization for Welcome {}
+
+impl StreamingCodec for Welcome {
+    fn encoded_len(&self) -> Result<usize, CodecError> {
+        let mut secrets = vec![];
+        encode_vec(VecSize::VecU32, &mut secrets, &self.secrets)?;
+        // The protocol version is a u8 and the ciphersuite a u16
+        Ok(1 + 2
+            + secrets.len()
+            + encoded_bytes_len(VecSize::VecU32, self.encrypted_group_info.len()))
+    }
+
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), CodecError> {
+        let mut header = vec![];
+        self.version.encode(&mut header)?;
+        self.cipher_suite.name().encode(&mut header)?;
+        encode_vec(VecSize::VecU32, &mut header, &self.secrets)?;
+        writer
+            .write_all(&header)
+            .map_err(|_| CodecError::EncodingError)?;
+        write_bytes(VecSize::VecU32, writer, &self.encrypted_group_info)
+    }
+}
+
+impl Welcome {
+    /// Decodes a `Welcome` without copying the encrypted group info.
+    pub fn decode_borrowed<'a>(cursor: &mut Cursor<'a>) -> Result<WelcomeRef<'a>, CodecError> {
         let version = ProtocolVersion::decode(cursor)?;
         let cipher_suite = CiphersuiteName::decode(cursor)?;
         let secrets = decode_vec(VecSize::VecU32, cursor)?;
-        let encrypted_group_info = decode_vec(VecSize::VecU32, cursor)?;
-        Ok(Welcome {
+        let encrypted_group_info = decode_bytes(VecSize::VecU32, cursor)?;
+        Ok(WelcomeRef {
             version,
             cipher_suite: Config::ciphersuite(cipher_suite)?,
             secrets,
@@ -153,6 +209,10 @@ impl Codec for Proposal {
                 ProposalType::Remove.encode(buffer)?;
                 remove.encode(buffer)?;
             }
+            Proposal::GroupContextExtensions(group_context_extensions) => {
+                ProposalType::GroupContextExtensions.encode(buffer)?;
+                group_context_extensions.encode(buffer)?;
+            }
         }
         Ok(())
     }
@@ -162,6 +222,9 @@ impl Codec for Proposal {
             ProposalType::Add => Ok(Proposal::Add(AddProposal::decode(cursor)?)),
             ProposalType::Update => Ok(Proposal::Update(UpdateProposal::decode(cursor)?)),
             ProposalType::Remove => Ok(Proposal::Remove(RemoveProposal::decode(cursor)?)),
+            ProposalType::GroupContextExtensions => Ok(Proposal::GroupContextExtensions(
+                GroupContextExtensionsProposal::decode(cursor)?,
+            )),
             _ => Err(CodecError::DecodingError),
         }
     }
@@ -210,3 +273,20 @@ impl Codec for RemoveProposal {
         Ok(RemoveProposal { removed })
     }
 }
+
+impl Codec for GroupContextExtensionsProposal {
+    fn encode(&self, buffer: &mut Vec<u8>) -> Result<(), CodecError> {
+        // Get extensions encoded. We need to build a Vec::<ExtensionStruct> first.
+        let encoded_extensions: Vec<ExtensionStruct> = self
+            .extensions
+            .iter()
+            .map(|e| e.to_extension_struct())
+            .collect();
+        encode_vec(VecSize::VecU16, buffer, &encoded_extensions)?;
+        Ok(())
+    }
+    fn decode(cursor: &mut Cursor) -> Result<Self, CodecError> {
+        let extensions = extensions_vec_from_cursor(cursor)?;
+        Ok(GroupContextExtensionsProposal { extensions })
+    }
+}
@@ -94,6 +94,39 @@ impl Welcome {
     }
 }
 
+/// A borrowed view of an encoded `Welcome`, created with
+/// [`Welcome::decode_borrowed()`]. The encrypted group info, which contains
+/// the ratchet tree if it is sent along, points into the encoded message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WelcomeRef<'a> {
+    pub(crate) version: ProtocolVersion,
+    pub(crate) cipher_suite: &'static Ciphersuite,
+    pub(crate) secrets: Vec<EncryptedGroupSecrets>,
+    pub(crate) encrypted_group_info: &'a [u8],
+}
+
+impl<'a> WelcomeRef<'a> {
+    /// Get a reference to the encrypted group secrets in this Welcome message.
+    pub fn secrets(&self) -> &[EncryptedGroupSecrets] {
+        &self.secrets
+    }
+
+    /// Get a reference to the encrypted group info.
+    pub fn encrypted_group_info(&self) -> &'a [u8] {
+        self.encrypted_group_info
+    }
+
+    /// Copy the borrowed values into a `Welcome`.
+    pub fn into_owned(self) -> Welcome {
+        Welcome::new(
+            self.version,
+            self.cipher_suite,
+            self.secrets,
+            self.encrypted_group_info.to_vec(),
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Commit {
     pub(crate) proposals: Vec<ProposalOrRef>,
@@ -150,17 +183,20 @@ impl ConfirmationTag {
 ///   uint64 epoch;
 ///   opaque tree_hash<0..255>;
 ///   opaque confirmed_transcript_hash<0..255>;
+///   Extension group_context_extensions<0..2^32-1>;
 ///   Extension extensions<0..2^32-1>;
 ///   MAC confirmation_tag;
 ///   uint32 signer_index;
 ///   opaque signature<0..2^16-1>;
 /// } GroupInfo;
 /// ```
-pub(crate) struct GroupInfo {
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupInfo {
     group_id: GroupId,
     epoch: GroupEpoch,
     tree_hash: Vec<u8>,
     confirmed_transcript_hash: Vec<u8>,
+    group_context_extensions: Vec<Box<dyn Extension>>,
     extensions: Vec<Box<dyn Extension>>,
     confirmation_tag: Vec<u8>,
     signer_index: LeafIndex,
@@ -173,6 +209,7 @@ impl GroupInfo {
         epoch: GroupEpoch,
         tree_hash: Vec<u8>,
         confirmed_transcript_hash: Vec<u8>,
+        group_context_extensions: Vec<Box<dyn Extension>>,
         extensions: Vec<Box<dyn Extension>>,
         confirmation_tag: Vec<u8>,
         signer_index: LeafIndex,
@@ -182,6 +219,7 @@ impl GroupInfo {
             epoch,
             tree_hash,
             confirmed_transcript_hash,
+            group_context_extensions,
             extensions,
             confirmation_tag,
             signer_index,
@@ -210,12 +248,12 @@ impl GroupInfo {
     }
 
     /// Get the group ID.
-    pub(crate) fn group_id(&self) -> &GroupId {
+    pub fn group_id(&self) -> &GroupId {
         &self.group_id
     }
 
     /// Get the epoch.
-    pub(crate) fn epoch(&self) -> GroupEpoch {
+    pub fn epoch(&self) -> GroupEpoch {
         self.epoch
     }
 
@@ -229,6 +267,11 @@ impl GroupInfo {
         &self.confirmation_tag
     }
 
+    /// Get the group context extensions.
+    pub(crate) fn group_context_extensions(&self) -> &[Box<dyn Extension>] {
+        &self.group_context_extensions
+    }
+
     /// Get the extensions.
     pub(crate) fn extensions(&self) -> &[Box<dyn Extension>] {
         &self.extensions
@@ -242,25 +285,7 @@ impl GroupInfo {
 
 impl GroupInfo {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
-        let mut cursor = Cursor::new(bytes);
-        let group_id = GroupId::decode(&mut cursor)?;
-        let epoch = GroupEpoch::decode(&mut cursor)?;
-        let tree_hash = decode_vec(VecSize::VecU8, &mut cursor)?;
-        let confirmed_transcript_hash = decode_vec(VecSize::VecU8, &mut cursor)?;
-        let extensions = extensions_vec_from_cursor(&mut cursor)?;
-        let confirmation_tag = decode_vec(VecSize::VecU8, &mut cursor)?;
-        let signer_index = LeafIndex::from(u32::decode(&mut cursor)?);
-        let signature = Signature::decode(&mut cursor)?;
-        Ok(GroupInfo {
-            group_id,
-            epoch,
-            tree_hash,
-            confirmed_transcript_hash,
-            extensions,
-            confirmation_tag,
-            signer_index,
-            signature,
-        })
+        GroupInfo::decode(&mut Cursor::new(bytes))
     }
 }
 
@@ -272,6 +297,12 @@ impl Signable for GroupInfo {
         encode_vec(VecSize::VecU8, buffer, &self.tree_hash)?;
         encode_vec(VecSize::VecU8, buffer, &self.confirmed_transcript_hash)?;
         // Get extensions encoded. We need to build a Vec::<ExtensionStruct> first.
+        let encoded_group_context_extensions: Vec<ExtensionStruct> = self
+            .group_context_extensions
+            .iter()
+            .map(|e| e.to_extension_struct())
+            .collect();
+        encode_vec(VecSize::VecU16, buffer, &encoded_group_context_extensions)?;
         let encoded_extensions: Vec<ExtensionStruct> = self
             .extensions
             .iter()
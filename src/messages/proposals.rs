@@ -1,5 +1,6 @@
 use crate::ciphersuite::*;
 use crate::codec::*;
+use crate::extensions::Extension;
 use crate::framing::{sender::*, *};
 use crate::key_packages::*;
 use crate::tree::index::*;
@@ -17,6 +18,7 @@ pub enum ProposalType {
     Add = 1,
     Update = 2,
     Remove = 3,
+    GroupContextExtensions = 8,
     Default = 255,
 }
 
@@ -27,6 +29,7 @@ impl From<u8> for ProposalType {
             1 => ProposalType::Add,
             2 => ProposalType::Update,
             3 => ProposalType::Remove,
+            8 => ProposalType::GroupContextExtensions,
             _ => ProposalType::Default,
         }
     }
@@ -93,6 +96,7 @@ pub enum Proposal {
     Add(AddProposal),
     Update(UpdateProposal),
     Remove(RemoveProposal),
+    GroupContextExtensions(GroupContextExtensionsProposal),
 }
 
 impl Proposal {
@@ -101,6 +105,7 @@ impl Proposal {
             Proposal::Add(ref _a) => ProposalType::Add,
             Proposal::Update(ref _u) => ProposalType::Update,
             Proposal::Remove(ref _r) => ProposalType::Remove,
+            Proposal::GroupContextExtensions(ref _g) => ProposalType::GroupContextExtensions,
         }
     }
     pub(crate) fn is_type(&self, proposal_type: ProposalType) -> bool {
@@ -124,6 +129,14 @@ impl Proposal {
             _ => None,
         }
     }
+    pub(crate) fn as_group_context_extensions(&self) -> Option<GroupContextExtensionsProposal> {
+        match self {
+            Proposal::GroupContextExtensions(group_context_extensions_proposal) => {
+                Some(group_context_extensions_proposal.clone())
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Reference to a Proposal. This can be used in Commit messages to reference
@@ -286,6 +299,7 @@ impl<'a> ProposalQueue<'a> {
     /// - Check for invalid indexes and drop proposal
     /// - Check for presence of Removes and delete Updates
     /// - Only keep the last Update
+    /// - Only keep the last GroupContextExtensions proposal
     ///
     /// Return a `ProposalQueue` a bool that indicates whether Updates for the
     /// own node were included
@@ -309,6 +323,7 @@ impl<'a> ProposalQueue<'a> {
             tree_size.as_usize()
         ];
         let mut adds: HashSet<ProposalReference> = HashSet::new();
+        let mut group_context_extensions: Option<ProposalReference> = None;
         let mut valid_proposals: HashSet<ProposalReference> = HashSet::new();
         let mut proposal_pool: HashMap<ProposalReference, QueuedProposal> = HashMap::new();
         let mut contains_own_updates = false;
@@ -362,6 +377,11 @@ impl<'a> ProposalQueue<'a> {
                     let proposal_reference = queued_proposal.proposal_reference();
                     proposal_pool.insert(proposal_reference, queued_proposal);
                 }
+                ProposalType::GroupContextExtensions => {
+                    // Later proposals replace earlier ones
+                    group_context_extensions = Some(queued_proposal.proposal_reference());
+                    proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
+                }
                 _ => {}
             }
         }
@@ -381,7 +401,11 @@ impl<'a> ProposalQueue<'a> {
         }
         // Only retain `adds` and `valid_proposals`
         let mut proposal_queue = ProposalQueue::default();
-        for proposal_reference in adds.iter().chain(valid_proposals.iter()) {
+        for proposal_reference in adds
+            .iter()
+            .chain(valid_proposals.iter())
+            .chain(group_context_extensions.iter())
+        {
             proposal_queue.add(match proposal_pool.get(proposal_reference) {
                 Some(queued_proposal) => queued_proposal.clone(),
                 None => return Err(ProposalQueueError::ProposalNotFound),
@@ -435,6 +459,14 @@ impl<'a> ProposalQueue<'a> {
             })
             .collect::<Vec<ProposalOrRef>>()
     }
+    /// Returns the extensions of the last GroupContextExtensions proposal in
+    /// the queue, or `None` if there is no such proposal.
+    pub(crate) fn group_context_extensions(&self) -> Option<Vec<Box<dyn Extension>>> {
+        self.filtered_by_type(ProposalType::GroupContextExtensions)
+            .last()
+            .and_then(|queued_proposal| queued_proposal.proposal.as_group_context_extensions())
+            .map(|proposal| proposal.extensions)
+    }
     /// Returns an iterator over a list of `QueuedProposal` filtered by proposal
     /// type
     pub(crate) fn filtered_by_type(
@@ -466,3 +498,18 @@ pub struct UpdateProposal {
 pub struct RemoveProposal {
     pub removed: u32,
 }
+
+/// GroupContextExtensions proposal
+///
+/// The extensions in this proposal replace the extensions of the group context
+/// when the proposal is committed.
+///
+/// ```text
+/// struct {
+///   Extension extensions<0..2^32-1>;
+/// } GroupContextExtensions;
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GroupContextExtensionsProposal {
+    pub extensions: Vec<Box<dyn Extension>>,
+}
@@ -54,6 +54,7 @@ fn proposal_queue_functions() {
             epoch: GroupEpoch(0),
             tree_hash: vec![],
             confirmed_transcript_hash: vec![],
+            extensions: vec![],
         };
 
         // Let's create some proposals
@@ -160,6 +161,7 @@ fn proposal_queue_order() {
             epoch: GroupEpoch(0),
             tree_hash: vec![],
             confirmed_transcript_hash: vec![],
+            extensions: vec![],
         };
 
         // Let's create some proposals
@@ -4,16 +4,33 @@
 pub use crate::group::GroupConfig;
 pub use crate::group::MlsGroup;
 pub use crate::group::{
-    HandshakeMessageFormat, InvalidMessageError, MLSMessage, ManagedGroup, ManagedGroupCallbacks,
-    ManagedGroupConfig, ManagedGroupError, Removal, UpdatePolicy,
+    CommitBuilder, CredentialRotationError, HandshakeMessageFormat, InvalidMessageError,
+    MLSMessage, ManagedGroup, ManagedGroupCallbacks, ManagedGroupConfig, ManagedGroupError,
+    MembershipError, Removal, RolePolicyError, StorageKey, UnsupportedError, UpdatePolicy,
+    WireFormat, STORAGE_KEY_LENGTH,
 };
 // Errors
-pub use crate::group::errors::{ApplyCommitError, CreateCommitError, GroupError, WelcomeError};
+pub use crate::config::errors::ConfigError;
+pub use crate::error::ErrorCode;
+pub use crate::extensions::errors::{
+    CapabilitiesExtensionError, ExtensionError, InvalidExtensionError, KeyPackageIdError,
+    LastResortError, LifetimeExtensionError, ParentHashError, RatchetTreeError, RolesError,
+    UserIdError,
+};
+pub use crate::group::errors::{
+    ApplyCommitError, CreateCommitError, ExporterError, GroupError, StorageError, WelcomeError,
+};
+pub use crate::key_packages::errors::KeyPackageError;
+pub use crate::tree::errors::TreeError;
 
 // Indexes
 pub use crate::tree::index::LeafIndex;
 
+// Sender ratchets
+pub use crate::tree::SenderRatchetConfiguration;
+
 pub use crate::ciphersuite::*;
+pub use crate::clock::*;
 pub use crate::codec::*;
 pub use crate::config::*;
 pub use crate::credentials::*;
@@ -22,8 +39,8 @@ pub use crate::framing::{errors::*, sender::Sender, *};
 pub use crate::group::GroupId;
 pub use crate::key_packages::*;
 pub use crate::messages::{
-    proposals::{AddProposal, RemoveProposal, UpdateProposal},
-    Welcome,
+    proposals::{AddProposal, GroupContextExtensionsProposal, RemoveProposal, UpdateProposal},
+    GroupInfo, Welcome, WelcomeRef,
 };
 pub use crate::utils::*;
 
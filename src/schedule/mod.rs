@@ -49,6 +49,7 @@ use crate::tree::index::LeafIndex;
 use crate::tree::index::NodeIndex;
 use crate::tree::private_tree::CommitSecret;
 use crate::tree::secret_tree::SecretTree;
+use crate::tree::sender_ratchet::SenderRatchetConfiguration;
 use crate::tree::treemath;
 use crate::tree::RatchetTree;
 
@@ -295,6 +296,7 @@ impl EpochSecret {
 }
 
 /// The `EncryptionSecret` is used to create a `SecretTree`.
+#[cfg_attr(test, derive(Clone))]
 pub struct EncryptionSecret {
     secret: Secret,
 }
@@ -310,8 +312,12 @@ impl EncryptionSecret {
     /// Create a `SecretTree` from the `encryption_secret` contained in the
     /// `EpochSecrets`. The `encryption_secret` is replaced with `None` in the
     /// process, allowing us to achieve FS.
-    pub fn create_secret_tree(self, treesize: LeafIndex) -> SecretTree {
-        SecretTree::new(self, treesize)
+    pub fn create_secret_tree(
+        self,
+        treesize: LeafIndex,
+        configuration: SenderRatchetConfiguration,
+    ) -> SecretTree {
+        SecretTree::new(self, treesize, configuration)
     }
 
     pub(crate) fn consume_secret(self) -> Secret {
@@ -378,6 +384,10 @@ pub(crate) struct EpochSecrets {
     sender_data_secret: SenderDataSecret,
     pub(crate) exporter_secret: ExporterSecret,
     confirmation_key: Secret,
+    // States persisted before membership tags were introduced don't have a
+    // membership key. They are rejected when they are loaded.
+    #[serde(default)]
+    membership_key: Option<Secret>,
 }
 
 impl EpochSecrets {
@@ -386,10 +396,30 @@ impl EpochSecrets {
         &self.sender_data_secret
     }
 
+    /// Consume the `EpochSecrets` and keep only the sender_data and the
+    /// exporter secret, e.g. to retain them for a past epoch.
+    pub(crate) fn into_past_epoch_secrets(self) -> (SenderDataSecret, ExporterSecret) {
+        (self.sender_data_secret, self.exporter_secret)
+    }
+
     /// Get the confirmation key.
     pub(crate) fn confirmation_key(&self) -> &Secret {
         &self.confirmation_key
     }
+
+    /// Get the membership key.
+    pub(crate) fn membership_key(&self) -> &Secret {
+        self.membership_key
+            .as_ref()
+            .expect("Library error. States without a membership key can't be loaded.")
+    }
+
+    /// Returns `false` if the secrets were persisted by a version that didn't
+    /// derive the membership key yet.
+    pub(crate) fn has_membership_key(&self) -> bool {
+        self.membership_key.is_some()
+    }
+
     /// Derive `EpochSecrets`, as well as an `EncryptionSecret` and an
     /// `InitSecret` from a `MemberSecret` and a given `GroupContext`. This
     /// method is only used when initially creating a new `MlsGroup` state.
@@ -404,11 +434,13 @@ impl EpochSecrets {
         let encryption_secret = EncryptionSecret::from_epoch_secret(ciphersuite, &epoch_secret);
         let exporter_secret = ExporterSecret::from_epoch_secret(ciphersuite, &epoch_secret);
         let confirmation_key = epoch_secret.secret.derive_secret(ciphersuite, "confirm");
+        let membership_key = epoch_secret.secret.derive_secret(ciphersuite, "membership");
         let init_secret = InitSecret::from_epoch_secret(ciphersuite, &epoch_secret);
         let epoch_secrets = EpochSecrets {
             sender_data_secret,
             exporter_secret,
             confirmation_key,
+            membership_key: Some(membership_key),
         };
         (epoch_secrets, init_secret, encryption_secret)
     }
@@ -23,6 +23,7 @@ use index::*;
 use node::*;
 use private_tree::{PathSecrets, PrivateTree};
 pub use secret_tree::SecretTypeError;
+pub use sender_ratchet::SenderRatchetConfiguration;
 
 use self::private_tree::CommitSecret;
 pub(crate) use serde::{
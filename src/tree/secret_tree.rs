@@ -12,6 +12,7 @@ pub enum SecretTreeError {
     TooDistantInThePast,
     TooDistantInTheFuture,
     IndexOutOfBounds,
+    SecretReuseError,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -87,14 +88,22 @@ pub struct SecretTree {
     handshake_sender_ratchets: Vec<Option<SenderRatchet>>,
     application_sender_ratchets: Vec<Option<SenderRatchet>>,
     size: LeafIndex,
+    // Persisted states of older versions use the default configuration.
+    #[serde(default)]
+    configuration: SenderRatchetConfiguration,
 }
 
 impl SecretTree {
     /// Creates a new SecretTree based on an `encryption_secret` and group size
     /// `size`. The inner nodes of the tree and the SenderRatchets only get
     /// initialized when secrets are requested either through `secret()`
-    /// or `next_secret()`.
-    pub fn new(encryption_secret: EncryptionSecret, size: LeafIndex) -> Self {
+    /// or `next_secret()`. The `SenderRatchet`s are configured with
+    /// `configuration`.
+    pub fn new(
+        encryption_secret: EncryptionSecret,
+        size: LeafIndex,
+        configuration: SenderRatchetConfiguration,
+    ) -> Self {
         let root = root(size);
         let num_indices = NodeIndex::from(size).as_usize() - 1;
         let mut nodes = vec![None; num_indices];
@@ -107,6 +116,7 @@ impl SecretTree {
             handshake_sender_ratchets: vec![None; size.as_usize()],
             application_sender_ratchets: vec![None; size.as_usize()],
             size,
+            configuration,
         }
     }
 
@@ -191,6 +201,17 @@ impl SecretTree {
         Ok(())
     }
 
+    /// Set the configuration of the `SenderRatchet`s. Unused secrets outside
+    /// of a smaller window are deleted with the next use of a ratchet.
+    pub(crate) fn set_configuration(&mut self, configuration: SenderRatchetConfiguration) {
+        self.configuration = configuration;
+    }
+
+    /// Get the configuration of the `SenderRatchet`s.
+    pub(crate) fn configuration(&self) -> &SenderRatchetConfiguration {
+        &self.configuration
+    }
+
     /// Return RatchetSecrets for a given index and generation. This should be
     /// called when decrypting an MLSCiphertext received fromanother member.
     /// Returns an error if index or genartion are out of bound.
@@ -208,8 +229,9 @@ impl SecretTree {
         if self.ratchet_opt(index, secret_type).is_none() {
             self.initialize_sender_ratchets(ciphersuite, index)?;
         }
+        let configuration = self.configuration;
         let sender_ratchet = self.ratchet_mut(index, secret_type);
-        sender_ratchet.secret_for_decryption(ciphersuite, generation)
+        sender_ratchet.secret_for_decryption(ciphersuite, generation, &configuration)
     }
 
     /// Return the next RatchetSecrets that should be used for encryption and
@@ -224,8 +246,9 @@ impl SecretTree {
             self.initialize_sender_ratchets(ciphersuite, index)
                 .expect("Index out of bounds");
         }
+        let configuration = self.configuration;
         let sender_ratchet = self.ratchet_mut(index, secret_type);
-        sender_ratchet.secret_for_encryption(ciphersuite)
+        sender_ratchet.secret_for_encryption(ciphersuite, &configuration)
     }
 
     /// Returns a mutable reference to a specific SenderRatchet. The
@@ -8,11 +8,73 @@ const MAXIMUM_FORWARD_DISTANCE: u32 = 1000;
 
 pub type RatchetSecrets = (AeadKey, AeadNonce);
 
+/// Configuration of the `SenderRatchet`s of a group.
+///
+/// The out-of-order tolerance is the number of past generations for which
+/// unused secrets are kept, i.e. how many messages can arrive out of order. The
+/// maximum forward distance is the number of generations that can be skipped,
+/// i.e. how many messages can be lost.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SenderRatchetConfiguration {
+    out_of_order_tolerance: u32,
+    maximum_forward_distance: u32,
+}
+
+impl SenderRatchetConfiguration {
+    /// Create a new configuration. The out-of-order tolerance must be at least
+    /// 1.
+    pub fn new(out_of_order_tolerance: u32, maximum_forward_distance: u32) -> Self {
+        Self {
+            out_of_order_tolerance: out_of_order_tolerance.max(1),
+            maximum_forward_distance,
+        }
+    }
+    /// Get the out-of-order tolerance.
+    pub fn out_of_order_tolerance(&self) -> u32 {
+        self.out_of_order_tolerance
+    }
+    /// Get the maximum forward distance.
+    pub fn maximum_forward_distance(&self) -> u32 {
+        self.maximum_forward_distance
+    }
+}
+
+impl Default for SenderRatchetConfiguration {
+    fn default() -> Self {
+        Self::new(OUT_OF_ORDER_TOLERANCE, MAXIMUM_FORWARD_DISTANCE)
+    }
+}
+
+/// The key and nonce of a generation that was skipped and not used yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+struct UnusedSecrets {
+    generation: u32,
+    key: Secret,
+    nonce: Secret,
+}
+
+/// A `SenderRatchet` derives the keys and nonces of the messages of one
+/// sender. In line with the deletion schedule of the MLS specification, a
+/// ratchet secret is deleted as soon as the key and nonce of its generation
+/// and the next ratchet secret were derived from it. Only the key and nonce of
+/// generations within the out-of-order tolerance that were skipped, or used
+/// for encryption by the own client, are kept until they are used for
+/// decryption.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct SenderRatchet {
     index: LeafIndex,
+    // The generation of `secret`. All older generations were either used,
+    // skipped or are part of `unused_secrets`.
     generation: u32,
+    #[serde(default)]
+    secret: Secret,
+    #[serde(default)]
+    unused_secrets: Vec<UnusedSecrets>,
+    // Ratchet secrets of a state that was persisted before the secrets were
+    // deleted after use. They are converted by `migrate_past_secrets()`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     past_secrets: Vec<Secret>,
 }
 
@@ -22,81 +84,136 @@ impl SenderRatchet {
         Self {
             index,
             generation: 0,
-            past_secrets: vec![secret.clone()],
+            secret: secret.clone(),
+            unused_secrets: vec![],
+            past_secrets: vec![],
         }
     }
     /// Gets a secret from the SenderRatchet. Returns an error if the generation
-    /// is out of bound.
+    /// is out of bound or if the secret of the generation was already used.
+    /// The secret is deleted from the SenderRatchet.
     pub(crate) fn secret_for_decryption(
         &mut self,
         ciphersuite: &Ciphersuite,
         generation: u32,
+        configuration: &SenderRatchetConfiguration,
     ) -> Result<RatchetSecrets, SecretTreeError> {
+        self.migrate_past_secrets(ciphersuite);
         // If generation is too distant in the future
-        if generation > (self.generation + MAXIMUM_FORWARD_DISTANCE) {
-            return Err(SecretTreeError::TooDistantInTheFuture);
-        }
-        // If generation id too distant in the past
-        if generation < self.generation && (self.generation - generation) >= OUT_OF_ORDER_TOLERANCE
+        if generation as u64
+            > (self.current_generation() as u64 + configuration.maximum_forward_distance as u64)
         {
-            return Err(SecretTreeError::TooDistantInThePast);
+            return Err(SecretTreeError::TooDistantInTheFuture);
         }
-        // If generation is within the window
-        if generation <= self.generation {
-            let window_index =
-                (self.past_secrets.len() as u32 - (self.generation - generation) - 1) as usize;
-            let secret = self.past_secrets.get(window_index).unwrap().clone();
-            let ratchet_secrets = self.derive_key_nonce(ciphersuite, &secret, generation);
-            Ok(ratchet_secrets)
-        // If generation is in the future
-        } else {
-            for _ in 0..(generation - self.generation) {
-                if self.past_secrets.len() == OUT_OF_ORDER_TOLERANCE as usize {
-                    self.past_secrets.remove(0);
+        // If generation is in the past
+        if generation < self.generation {
+            // If generation is too distant in the past
+            if (self.current_generation() - generation) >= configuration.out_of_order_tolerance {
+                return Err(SecretTreeError::TooDistantInThePast);
+            }
+            // The secrets of a generation within the window can only be used
+            // once
+            return match self
+                .unused_secrets
+                .iter()
+                .position(|unused_secrets| unused_secrets.generation == generation)
+            {
+                Some(position) => {
+                    let unused_secrets = self.unused_secrets.remove(position);
+                    Ok((
+                        AeadKey::from_secret(ciphersuite, unused_secrets.key),
+                        AeadNonce::from_secret(unused_secrets.nonce),
+                    ))
                 }
-                let new_secret =
-                    self.ratchet_secret(ciphersuite, self.past_secrets.last().unwrap());
-                self.past_secrets.push(new_secret);
+                None => Err(SecretTreeError::SecretReuseError),
+            };
+        }
+        // If generation is the current one or in the future, keep the skipped
+        // secrets that are within the window
+        let window_start = (generation + 1).saturating_sub(configuration.out_of_order_tolerance);
+        while self.generation < generation {
+            if self.generation >= window_start {
+                let (key, nonce) =
+                    self.derive_key_nonce(ciphersuite, &self.secret, self.generation);
+                self.unused_secrets.push(UnusedSecrets {
+                    generation: self.generation,
+                    key,
+                    nonce,
+                });
             }
-            let secret = self.past_secrets.last().unwrap();
-            let ratchet_secrets = self.derive_key_nonce(ciphersuite, &secret, generation);
-            self.generation = generation;
-            Ok(ratchet_secrets)
+            self.ratchet_forward(ciphersuite);
         }
+        let (key, nonce) = self.derive_key_nonce(ciphersuite, &self.secret, generation);
+        self.ratchet_forward(ciphersuite);
+        self.delete_old_secrets(configuration);
+        Ok((
+            AeadKey::from_secret(ciphersuite, key),
+            AeadNonce::from_secret(nonce),
+        ))
     }
-    /// Gets a secret from the SenderRatchet and ratchets forward
-    pub fn secret_for_encryption(&mut self, ciphersuite: &Ciphersuite) -> (u32, RatchetSecrets) {
-        let current_path_secret = match self.past_secrets.last() {
-            Some(secret) => secret.clone(),
-            None => {
-                panic!("Library error. PastSecrets should never be depleted in SenderRatchet.")
-            }
-        };
-        let next_path_secret = self.ratchet_secret(ciphersuite, &current_path_secret);
+    /// Gets a secret from the SenderRatchet and ratchets forward. The key and
+    /// nonce are not kept, so the sender can't decrypt its own message.
+    pub fn secret_for_encryption(
+        &mut self,
+        ciphersuite: &Ciphersuite,
+        configuration: &SenderRatchetConfiguration,
+    ) -> (u32, RatchetSecrets) {
+        self.migrate_past_secrets(ciphersuite);
         let generation = self.generation;
-        // Check if we have too many secrets in `past_secrets`
-        if self.past_secrets.len() >= OUT_OF_ORDER_TOLERANCE as usize {
-            //Drain older secrets
-            let surplus = self.past_secrets.len() - OUT_OF_ORDER_TOLERANCE as usize + 1;
-            self.past_secrets.drain(0..surplus);
-        }
-        self.past_secrets.push(next_path_secret);
-        self.generation += 1;
+        let (key, nonce) = self.derive_key_nonce(ciphersuite, &self.secret, generation);
+        self.ratchet_forward(ciphersuite);
+        self.delete_old_secrets(configuration);
         (
             generation,
-            self.derive_key_nonce(ciphersuite, &current_path_secret, generation),
+            (
+                AeadKey::from_secret(ciphersuite, key),
+                AeadNonce::from_secret(nonce),
+            ),
         )
     }
-    /// Computes the new secret
-    fn ratchet_secret(&self, ciphersuite: &Ciphersuite, secret: &Secret) -> Secret {
-        derive_tree_secret(
+    /// The last generation for which a key and nonce were derived, or 0 if
+    /// none was derived yet
+    fn current_generation(&self) -> u32 {
+        self.generation.saturating_sub(1)
+    }
+    /// Deletes the unused secrets that are no longer within the window
+    fn delete_old_secrets(&mut self, configuration: &SenderRatchetConfiguration) {
+        let current_generation = self.current_generation();
+        self.unused_secrets.retain(|unused_secrets| {
+            current_generation - unused_secrets.generation < configuration.out_of_order_tolerance
+        });
+    }
+    /// Converts the ratchet secrets of a persisted state of an older version.
+    /// The last past secret is the secret of the current generation, the key
+    /// and nonce of the previous generations are kept as unused secrets.
+    fn migrate_past_secrets(&mut self, ciphersuite: &Ciphersuite) {
+        let mut past_secrets = std::mem::take(&mut self.past_secrets);
+        let secret = match past_secrets.pop() {
+            Some(secret) => secret,
+            None => return,
+        };
+        let first_generation = self.generation.saturating_sub(past_secrets.len() as u32);
+        for (generation, past_secret) in (first_generation..).zip(past_secrets.iter()) {
+            let (key, nonce) = self.derive_key_nonce(ciphersuite, past_secret, generation);
+            self.unused_secrets.push(UnusedSecrets {
+                generation,
+                key,
+                nonce,
+            });
+        }
+        self.secret = secret;
+    }
+    /// Replaces the current secret with the secret of the next generation
+    fn ratchet_forward(&mut self, ciphersuite: &Ciphersuite) {
+        self.secret = derive_tree_secret(
             ciphersuite,
-            secret,
+            &self.secret,
             "secret",
             self.index.into(),
             self.generation,
             ciphersuite.hash_length(),
-        )
+        );
+        self.generation += 1;
     }
     /// Derives a key & nonce from a secret
     fn derive_key_nonce(
@@ -104,7 +221,7 @@ impl SenderRatchet {
         ciphersuite: &Ciphersuite,
         secret: &Secret,
         generation: u32,
-    ) -> RatchetSecrets {
+    ) -> (Secret, Secret) {
         let nonce = derive_tree_secret(
             &ciphersuite,
             secret,
@@ -121,10 +238,7 @@ impl SenderRatchet {
             generation,
             ciphersuite.aead_key_length(),
         );
-        (
-            AeadKey::from_secret(ciphersuite, key),
-            AeadNonce::from_secret(nonce),
-        )
+        (key, nonce)
     }
     /// Gets the current generation
     #[cfg(test)]
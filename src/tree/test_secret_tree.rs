@@ -4,11 +4,15 @@ use crate::schedule::EncryptionSecret;
 #[test]
 fn test_boundaries() {
     use crate::config::*;
-    use crate::tree::{index::*, secret_tree::*};
+    use crate::tree::{index::*, secret_tree::*, SenderRatchetConfiguration};
 
     for ciphersuite in Config::supported_ciphersuites() {
         let encryption_secret = EncryptionSecret::from_random(32);
-        let mut secret_tree = SecretTree::new(encryption_secret, LeafIndex::from(2u32));
+        let mut secret_tree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(2u32),
+            SenderRatchetConfiguration::default(),
+        );
         let secret_type = SecretType::ApplicationSecret;
         assert!(secret_tree
             .secret_for_decryption(&ciphersuite, LeafIndex::from(0u32), secret_type, 0)
@@ -48,7 +52,11 @@ fn test_boundaries() {
             Err(SecretTreeError::IndexOutOfBounds)
         );
         let encryption_secret = EncryptionSecret::from_random(32);
-        let mut largetree = SecretTree::new(encryption_secret, LeafIndex::from(100_000u32));
+        let mut largetree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(100_000u32),
+            SenderRatchetConfiguration::default(),
+        );
         assert!(largetree
             .secret_for_decryption(&ciphersuite, LeafIndex::from(0u32), secret_type, 0)
             .is_ok());
@@ -70,6 +78,157 @@ fn test_boundaries() {
     }
 }
 
+// This tests the boundaries of the generations with a custom configuration
+#[test]
+fn test_configured_boundaries() {
+    use crate::config::*;
+    use crate::tree::{index::*, secret_tree::*, SenderRatchetConfiguration};
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let encryption_secret = EncryptionSecret::from_random(32);
+        let mut secret_tree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(2u32),
+            SenderRatchetConfiguration::new(20, 50),
+        );
+        let secret_type = SecretType::ApplicationSecret;
+        assert!(secret_tree
+            .secret_for_decryption(&ciphersuite, LeafIndex::from(0u32), secret_type, 50)
+            .is_ok());
+        assert_eq!(
+            secret_tree.secret_for_decryption(
+                &ciphersuite,
+                LeafIndex::from(0u32),
+                secret_type,
+                101
+            ),
+            Err(SecretTreeError::TooDistantInTheFuture)
+        );
+        // More than the default of 5 messages can arrive out of order
+        assert!(secret_tree
+            .secret_for_decryption(&ciphersuite, LeafIndex::from(0u32), secret_type, 31)
+            .is_ok());
+        assert_eq!(
+            secret_tree.secret_for_decryption(&ciphersuite, LeafIndex::from(0u32), secret_type, 30),
+            Err(SecretTreeError::TooDistantInThePast)
+        );
+    }
+}
+
+// This tests that the secrets of a generation can only be used once
+#[test]
+fn test_secret_reuse() {
+    use crate::config::*;
+    use crate::tree::{index::*, secret_tree::*, SenderRatchetConfiguration};
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let encryption_secret = EncryptionSecret::from_random(32);
+        let mut secret_tree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(2u32),
+            SenderRatchetConfiguration::default(),
+        );
+        let secret_type = SecretType::HandshakeSecret;
+        let sender = LeafIndex::from(0u32);
+        assert!(secret_tree
+            .secret_for_decryption(&ciphersuite, sender, secret_type, 0)
+            .is_ok());
+        assert_eq!(
+            secret_tree.secret_for_decryption(&ciphersuite, sender, secret_type, 0),
+            Err(SecretTreeError::SecretReuseError)
+        );
+        // Skipped generations can be used once
+        assert!(secret_tree
+            .secret_for_decryption(&ciphersuite, sender, secret_type, 3)
+            .is_ok());
+        assert!(secret_tree
+            .secret_for_decryption(&ciphersuite, sender, secret_type, 2)
+            .is_ok());
+        assert_eq!(
+            secret_tree.secret_for_decryption(&ciphersuite, sender, secret_type, 2),
+            Err(SecretTreeError::SecretReuseError)
+        );
+        assert_eq!(
+            secret_tree.secret_for_decryption(&ciphersuite, sender, secret_type, 3),
+            Err(SecretTreeError::SecretReuseError)
+        );
+        assert!(secret_tree
+            .secret_for_decryption(&ciphersuite, sender, secret_type, 1)
+            .is_ok());
+    }
+}
+
+// This tests that the sender doesn't keep the secrets of its own messages
+#[test]
+fn test_own_messages() {
+    use crate::config::*;
+    use crate::tree::{index::*, secret_tree::*, SenderRatchetConfiguration};
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let encryption_secret = EncryptionSecret::from_random(32);
+        let mut secret_tree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(2u32),
+            SenderRatchetConfiguration::new(2, 1000),
+        );
+        let secret_type = SecretType::HandshakeSecret;
+        let sender = LeafIndex::from(0u32);
+        for _ in 0..3 {
+            secret_tree.secret_for_encryption(&ciphersuite, sender, secret_type);
+        }
+        // Generation 0 is no longer within the out-of-order tolerance
+        assert_eq!(
+            secret_tree.secret_for_decryption(&ciphersuite, sender, secret_type, 0),
+            Err(SecretTreeError::TooDistantInThePast)
+        );
+        // The secrets of the other generations were deleted after encryption
+        for generation in [2u32, 1].iter() {
+            assert_eq!(
+                secret_tree.secret_for_decryption(&ciphersuite, sender, secret_type, *generation),
+                Err(SecretTreeError::SecretReuseError)
+            );
+        }
+    }
+}
+
+// This tests that the secrets derived for decryption match the secrets used for
+// encryption, also when generations are skipped
+#[test]
+fn test_skipped_generations() {
+    use crate::config::*;
+    use crate::tree::{
+        index::*, secret_tree::*, sender_ratchet::RatchetSecrets, SenderRatchetConfiguration,
+    };
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let encryption_secret = EncryptionSecret::from_random(32);
+        let encryption_secret_copy = encryption_secret.clone();
+        let mut sender_tree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(2u32),
+            SenderRatchetConfiguration::default(),
+        );
+        let mut receiver_tree = SecretTree::new(
+            encryption_secret_copy,
+            LeafIndex::from(2u32),
+            SenderRatchetConfiguration::default(),
+        );
+        let secret_type = SecretType::ApplicationSecret;
+        let sender = LeafIndex::from(1u32);
+        let sent_secrets = (0..4)
+            .map(|_| sender_tree.secret_for_encryption(&ciphersuite, sender, secret_type))
+            .collect::<Vec<(u32, RatchetSecrets)>>();
+        for generation in [3usize, 1, 2, 0].iter() {
+            let (key, nonce) = receiver_tree
+                .secret_for_decryption(&ciphersuite, sender, secret_type, *generation as u32)
+                .expect("Could not derive secrets");
+            assert_eq!(sent_secrets[*generation].0, *generation as u32);
+            assert_eq!((sent_secrets[*generation].1).0.as_slice(), key.as_slice());
+            assert_eq!((sent_secrets[*generation].1).1.as_slice(), nonce.as_slice());
+        }
+    }
+}
+
 // This tests if the generation gets incremented correctly and that the returned
 // values are unique.
 #[test]
@@ -84,7 +243,11 @@ fn increment_generation() {
     for ciphersuite in Config::supported_ciphersuites() {
         let mut unique_values: HashMap<Vec<u8>, bool> = HashMap::new();
         let encryption_secret = EncryptionSecret::from_random(32);
-        let mut secret_tree = SecretTree::new(encryption_secret, LeafIndex::from(SIZE as u32));
+        let mut secret_tree = SecretTree::new(
+            encryption_secret,
+            LeafIndex::from(SIZE as u32),
+            SenderRatchetConfiguration::default(),
+        );
         for i in 0..SIZE {
             assert_eq!(
                 secret_tree.generation(LeafIndex::from(i as u32), SecretType::HandshakeSecret),
@@ -127,3 +290,41 @@ fn increment_generation() {
         }
     }
 }
+
+// This tests that a `SenderRatchet` that was persisted before the secrets were
+// deleted after use can still be loaded
+#[test]
+fn test_legacy_sender_ratchet() {
+    use crate::ciphersuite::Secret;
+    use crate::config::*;
+    use crate::tree::{index::*, sender_ratchet::SenderRatchet, SenderRatchetConfiguration};
+
+    for ciphersuite in Config::supported_ciphersuites() {
+        let configuration = SenderRatchetConfiguration::default();
+        let secret = Secret::random(ciphersuite.hash_length());
+        let mut sender_ratchet = SenderRatchet::new(LeafIndex::from(1u32), &secret);
+
+        // A fresh ratchet in the old format only has the initial secret
+        let mut state = serde_json::to_value(&sender_ratchet).unwrap();
+        let ratchet_state = state.as_object_mut().unwrap();
+        let initial_secret = ratchet_state.remove("secret").unwrap();
+        ratchet_state.remove("unused_secrets");
+        ratchet_state.insert(
+            "past_secrets".to_string(),
+            serde_json::Value::Array(vec![initial_secret]),
+        );
+        let mut legacy_ratchet: SenderRatchet = serde_json::from_value(state).unwrap();
+
+        for generation in [2u32, 0, 1].iter() {
+            let (key, nonce) = sender_ratchet
+                .secret_for_decryption(&ciphersuite, *generation, &configuration)
+                .unwrap();
+            let (legacy_key, legacy_nonce) = legacy_ratchet
+                .secret_for_decryption(&ciphersuite, *generation, &configuration)
+                .unwrap();
+            assert_eq!(key.as_slice(), legacy_key.as_slice());
+            assert_eq!(nonce.as_slice(), legacy_nonce.as_slice());
+        }
+        assert_eq!(sender_ratchet, legacy_ratchet);
+    }
+}
@@ -40,19 +40,31 @@ macro_rules! count {
 
 /// The struct must contain a field `ciphersuite: &'static Ciphersuite`, which
 /// is not added to the macro invocation.
+///
+/// Fields that were added to the struct after its state could already be
+/// persisted are listed after a `;`. They must implement `Default` and are set
+/// to their default value if they are missing in the persisted state, so that
+/// older states can still be loaded.
+///
+/// Fields that are not persisted at all, e.g. because they hold a handle that
+/// has to be provided again by the application, are listed after a second `;`
+/// and `skip`. They are set to their default value when the state is loaded.
 
 macro_rules! implement_persistence {
-    ($name:ident, $( $fields:ident ),*) => {
+    ($name:ident, $( $fields:ident ),* $( ; $( $default_fields:ident ),* )? $( ; skip $( $skipped_fields:ident ),* )? ) => {
         impl<'de> Deserialize<'de> for $name {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
             where
                 D: Deserializer<'de>,
             {
-                const NUM_FIELDS: usize = count!($($fields)*) + 1;
+                const NUM_FIELDS: usize = count!($($fields)* $($($default_fields)*)?) + 1;
                 static FIELDS: [&'static str; NUM_FIELDS] = [
                 $(
                     stringify!($fields),
                 )*
+                $($(
+                    stringify!($default_fields),
+                )*)?
                     "ciphersuite",
                 ];
 
@@ -63,6 +75,9 @@ macro_rules! implement_persistence {
                     $(
                         $fields,
                     )*
+                    $($(
+                        $default_fields,
+                    )*)?
                     ciphersuite,
                 };
 
@@ -86,6 +101,12 @@ macro_rules! implement_persistence {
                                 .ok_or_else(|| de::Error::invalid_length(ctr, &self))?;
                             ctr += 1;
                         )*
+                        $($(
+                            let $default_fields = seq
+                                .next_element()?
+                                .ok_or_else(|| de::Error::invalid_length(ctr, &self))?;
+                            ctr += 1;
+                        )*)?
                         let ciphersuite = seq
                             .next_element()?
                             .ok_or_else(|| de::Error::invalid_length(ctr, &self))?;
@@ -97,6 +118,12 @@ macro_rules! implement_persistence {
                             $(
                                 $fields,
                             )*
+                            $($(
+                                $default_fields,
+                            )*)?
+                            $($(
+                                $skipped_fields: Default::default(),
+                            )*)?
                         })
                     }
 
@@ -107,6 +134,9 @@ macro_rules! implement_persistence {
                         $(
                             let mut $fields = None;
                         )*
+                        $($(
+                            let mut $default_fields = None;
+                        )*)?
                         let mut ciphersuite = None;
                         while let Some(key) = map.next_key()? {
                             match key {
@@ -124,6 +154,14 @@ macro_rules! implement_persistence {
                                         $fields = Some(map.next_value()?);
                                     }
                                 )*
+                                $($(
+                                    Field::$default_fields => {
+                                        if $default_fields.is_some() {
+                                            return Err(de::Error::duplicate_field(stringify!($default_fields)));
+                                        }
+                                        $default_fields = Some(map.next_value()?);
+                                    }
+                                )*)?
                             }
                         }
                         let ciphersuite =
@@ -131,6 +169,9 @@ macro_rules! implement_persistence {
                         $(
                             let $fields = $fields.ok_or_else(|| de::Error::missing_field(stringify!($fields)))?;
                         )*
+                        $($(
+                            let $default_fields = $default_fields.unwrap_or_default();
+                        )*)?
                         Ok($name {
                             ciphersuite: match Config::ciphersuite(ciphersuite) {
                                 Ok(c) => c,
@@ -139,6 +180,12 @@ macro_rules! implement_persistence {
                             $(
                                 $fields,
                             )*
+                            $($(
+                                $default_fields,
+                            )*)?
+                            $($(
+                                $skipped_fields: Default::default(),
+                            )*)?
                         })
                     }
                 }
@@ -159,10 +206,16 @@ macro_rules! implement_persistence {
                 $(
                     fields.push(stringify!($fields));
                 )*
+                $($(
+                    fields.push(stringify!($default_fields));
+                )*)?
                 let mut state = serializer.serialize_struct(stringify!($name), fields.len()+1)?;
                 $(
                     state.serialize_field(stringify!($fields), &self.$fields)?;
                 )*
+                $($(
+                    state.serialize_field(stringify!($default_fields), &self.$default_fields)?;
+                )*)?
                 state.serialize_field("ciphersuite", &self.ciphersuite.name())?;
                 state.end()
             }
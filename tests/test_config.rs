@@ -38,7 +38,8 @@ fn default_extensions() {
         vec![
             ExtensionType::Capabilities,
             ExtensionType::Lifetime,
-            ExtensionType::KeyID
+            ExtensionType::KeyID,
+            ExtensionType::LastResort
         ],
         supported_extensions
     );
@@ -0,0 +1,177 @@
+use openmls::prelude::*;
+
+const ED25519_SUITE: CiphersuiteName =
+    CiphersuiteName::MLS10_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+const P256_SUITE: CiphersuiteName = CiphersuiteName::MLS10_128_DHKEMP256_AES128GCM_SHA256_P256;
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn basic_credential(
+    identity: &[u8],
+    ciphersuite_name: CiphersuiteName,
+    public_key: &str,
+) -> Credential {
+    Credential::from(MLSCredentialType::Basic(BasicCredential {
+        identity: identity.to_vec(),
+        ciphersuite: Config::ciphersuite(ciphersuite_name).unwrap(),
+        public_key: SignaturePublicKey::new(hex_to_bytes(public_key), ciphersuite_name).unwrap(),
+    }))
+}
+
+/// Import a raw Ed25519 private key (RFC 8032, test 1) and derive the public
+/// key.
+#[test]
+fn credential_bundle_from_private_key() {
+    let private_key =
+        hex_to_bytes("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60");
+    let credential_bundle = CredentialBundle::from_private_key(
+        b"Alice".to_vec(),
+        CredentialType::Basic,
+        ED25519_SUITE,
+        &private_key,
+    )
+    .unwrap();
+    assert_eq!(
+        credential_bundle.credential(),
+        &basic_credential(
+            b"Alice",
+            ED25519_SUITE,
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        )
+    );
+    assert_eq!(credential_bundle.export_private_key(), private_key);
+
+    // Keys of the wrong length are rejected.
+    assert!(CredentialBundle::from_private_key(
+        b"Alice".to_vec(),
+        CredentialType::Basic,
+        ED25519_SUITE,
+        &private_key[1..],
+    )
+    .is_err());
+
+    // Raw keys round trip for all supported ciphersuites.
+    for ciphersuite_name in Config::supported_ciphersuite_names() {
+        let credential_bundle =
+            CredentialBundle::new(b"Bob".to_vec(), CredentialType::Basic, ciphersuite_name)
+                .unwrap();
+        let imported = CredentialBundle::from_private_key(
+            b"Bob".to_vec(),
+            CredentialType::Basic,
+            ciphersuite_name,
+            &credential_bundle.export_private_key(),
+        )
+        .unwrap();
+        assert_eq!(imported.credential(), credential_bundle.credential());
+    }
+}
+
+/// Import the Ed25519 PKCS#8 example of RFC 8410, section 10.3.
+#[test]
+fn credential_bundle_from_pkcs8() {
+    let pkcs8 = hex_to_bytes(
+        "302e020100300506032b657004220420\
+         d4ee72dbf913584ad5b6d8f1f769f8ad3afe7c28cbf1d4fbe097a88f44755842",
+    );
+    let credential_bundle = CredentialBundle::from_pkcs8(
+        b"Alice".to_vec(),
+        CredentialType::Basic,
+        ED25519_SUITE,
+        &pkcs8,
+    )
+    .unwrap();
+    assert_eq!(
+        credential_bundle.export_private_key(),
+        hex_to_bytes("d4ee72dbf913584ad5b6d8f1f769f8ad3afe7c28cbf1d4fbe097a88f44755842")
+    );
+    assert_eq!(credential_bundle.export_pkcs8(), pkcs8);
+
+    // An Ed25519 key can't be imported for a P-256 ciphersuite.
+    assert!(CredentialBundle::from_pkcs8(
+        b"Alice".to_vec(),
+        CredentialType::Basic,
+        P256_SUITE,
+        &pkcs8
+    )
+    .is_err());
+
+    // Trailing bytes are rejected.
+    let mut trailing = pkcs8.clone();
+    trailing.push(0);
+    assert!(CredentialBundle::from_pkcs8(
+        b"Alice".to_vec(),
+        CredentialType::Basic,
+        ED25519_SUITE,
+        &trailing
+    )
+    .is_err());
+
+    // PKCS#8 round trips for all supported ciphersuites.
+    for ciphersuite_name in Config::supported_ciphersuite_names() {
+        let credential_bundle =
+            CredentialBundle::new(b"Bob".to_vec(), CredentialType::Basic, ciphersuite_name)
+                .unwrap();
+        let imported = CredentialBundle::from_pkcs8(
+            b"Bob".to_vec(),
+            CredentialType::Basic,
+            ciphersuite_name,
+            &credential_bundle.export_pkcs8(),
+        )
+        .unwrap();
+        assert_eq!(imported.credential(), credential_bundle.credential());
+        assert_eq!(
+            imported.export_private_key(),
+            credential_bundle.export_private_key()
+        );
+    }
+}
+
+/// Serialize and deserialize credential bundles.
+#[test]
+fn credential_bundle_serialization() {
+    for ciphersuite_name in Config::supported_ciphersuite_names() {
+        let credential_bundle =
+            CredentialBundle::new(b"Alice".to_vec(), CredentialType::Basic, ciphersuite_name)
+                .unwrap();
+        let serialized = credential_bundle.to_bytes().unwrap();
+        let deserialized = CredentialBundle::from_bytes(&serialized).unwrap();
+        assert_eq!(deserialized.credential(), credential_bundle.credential());
+        assert_eq!(
+            deserialized.export_private_key(),
+            credential_bundle.export_private_key()
+        );
+
+        // Unknown versions are rejected.
+        let mut unknown_version = serialized.clone();
+        unknown_version[0] = 2;
+        assert_eq!(
+            CredentialBundle::from_bytes(&unknown_version).err(),
+            Some(CredentialError::UnsupportedVersion)
+        );
+
+        // Trailing bytes are rejected.
+        let mut trailing = serialized.clone();
+        trailing.push(0);
+        assert!(CredentialBundle::from_bytes(&trailing).is_err());
+
+        // The private key has to match the credential.
+        let other_credential_bundle =
+            CredentialBundle::new(b"Alice".to_vec(), CredentialType::Basic, ciphersuite_name)
+                .unwrap();
+        let other_serialized = other_credential_bundle.to_bytes().unwrap();
+        let private_key_length = credential_bundle.export_private_key().len();
+        let mut mismatch = serialized.clone();
+        let start = mismatch.len() - private_key_length;
+        mismatch[start..]
+            .copy_from_slice(&other_serialized[other_serialized.len() - private_key_length..]);
+        assert_eq!(
+            CredentialBundle::from_bytes(&mismatch).err(),
+            Some(CredentialError::KeyMismatch)
+        );
+    }
+}
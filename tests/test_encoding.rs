@@ -28,8 +28,12 @@ fn create_encoding_test_setup() -> TestSetup {
             ciphersuite: ciphersuite_name,
             config: GroupConfig {
                 add_ratchet_tree_extension: true,
-                padding_block_size: 10,
+                padding_policy: PaddingPolicy::BlockSize(10),
+                custom_padding: None,
                 additional_as_epochs: 0,
+                additional_as_epochs_max_age: 0,
+                sender_ratchet_configuration: SenderRatchetConfiguration::default(),
+                clock: SharedClock::default(),
             },
             members: vec![alice_config.clone(), bob_config.clone()],
         };
@@ -151,11 +155,13 @@ fn test_add_proposal_encoding() {
         .unwrap();
 
         // Adds
-        let add = group_state.create_add_proposal(
-            &[],
-            credential_bundle,
-            key_package_bundle.key_package().clone(),
-        );
+        let add = group_state
+            .create_add_proposal(
+                &[],
+                credential_bundle,
+                key_package_bundle.key_package().clone(),
+            )
+            .expect("Could not create proposal.");
         let add_encoded = add.encode_detached().unwrap();
         let add_decoded = match MLSPlaintext::decode(&mut Cursor::new(&add_encoded)) {
             Ok(a) => a,
@@ -237,11 +243,9 @@ fn test_commit_encoding() {
             .unwrap()
             .pop()
             .unwrap();
-        let add = group_state.create_add_proposal(
-            &[],
-            alice_credential_bundle,
-            charlie_key_package.clone(),
-        );
+        let add = group_state
+            .create_add_proposal(&[], alice_credential_bundle, charlie_key_package.clone())
+            .expect("Could not create proposal.");
 
         // Alice removes Bob
         let remove =
@@ -283,8 +287,9 @@ fn test_welcome_message_encoding() {
             .unwrap()
             .pop()
             .unwrap();
-        let add =
-            group_state.create_add_proposal(&[], credential_bundle, charlie_key_package.clone());
+        let add = group_state
+            .create_add_proposal(&[], credential_bundle, charlie_key_package.clone())
+            .expect("Could not create proposal.");
 
         let proposals = &[&add];
         let (_commit, welcome_option, _key_package_bundle_option) = group_state
@@ -314,3 +319,243 @@ fn test_welcome_message_encoding() {
         assert!(MlsGroup::new_from_welcome(welcome, None, charlie_key_package_bundle).is_ok());
     }
 }
+
+#[test]
+/// This tests the streaming encoding and the borrowing decoding of
+/// MLSCiphertext, MLSPlaintext and Welcome messages.
+fn test_streaming_encoding() {
+    let test_setup = create_encoding_test_setup();
+    let test_clients = test_setup.clients.borrow();
+    let alice = test_clients.get("alice").unwrap().borrow();
+
+    for group_state in alice.group_states.borrow_mut().values_mut() {
+        let credential_bundle = alice
+            .credential_bundles
+            .get(&group_state.ciphersuite().name())
+            .unwrap();
+        let message = randombytes(random_usize() % 1000);
+        let aad = randombytes(random_usize() % 1000);
+
+        // MLSCiphertext
+        let ciphertext = group_state
+            .create_application_message(&aad, &message, &credential_bundle)
+            .unwrap();
+        let ciphertext_encoded = ciphertext.encode_detached().unwrap();
+        assert_eq!(ciphertext.encoded_len().unwrap(), ciphertext_encoded.len());
+        assert_eq!(ciphertext.encode_exact().unwrap(), ciphertext_encoded);
+        let mut ciphertext_written = vec![];
+        ciphertext.encode_to(&mut ciphertext_written).unwrap();
+        assert_eq!(ciphertext_written, ciphertext_encoded);
+        let ciphertext_borrowed =
+            MLSCiphertext::decode_borrowed(&mut Cursor::new(&ciphertext_encoded)).unwrap();
+        assert_eq!(ciphertext_borrowed.ciphertext(), &ciphertext.ciphertext[..]);
+        assert_eq!(
+            ciphertext_borrowed.authenticated_data(),
+            &ciphertext.authenticated_data[..]
+        );
+        assert_eq!(ciphertext_borrowed.into_owned(), ciphertext);
+
+        // MLSPlaintext with application data and with a proposal
+        let application_plaintext = MLSPlaintext::new(
+            LeafIndex::from(0u32),
+            &aad,
+            MLSPlaintextContentType::Application(message.clone()),
+            credential_bundle,
+            group_state.context(),
+        );
+        let charlie_key_package = test_setup
+            ._key_store
+            .borrow_mut()
+            .get_mut(&("charlie", group_state.ciphersuite().name()))
+            .unwrap()
+            .pop()
+            .unwrap();
+        let add_plaintext = group_state
+            .create_add_proposal(&aad, credential_bundle, charlie_key_package)
+            .expect("Could not create proposal.");
+        for plaintext in &[application_plaintext, add_plaintext.clone()] {
+            let plaintext_encoded = plaintext.encode_detached().unwrap();
+            assert_eq!(plaintext.encoded_len().unwrap(), plaintext_encoded.len());
+            assert_eq!(plaintext.encode_exact().unwrap(), plaintext_encoded);
+            let plaintext_borrowed =
+                MLSPlaintext::decode_borrowed(&mut Cursor::new(&plaintext_encoded)).unwrap();
+            assert_eq!(plaintext_borrowed.authenticated_data(), &aad[..]);
+            if !plaintext.is_handshake_message() {
+                assert_eq!(
+                    plaintext_borrowed.as_application_message(),
+                    Ok(&message[..])
+                );
+            }
+            assert_eq!(&plaintext_borrowed.into_owned(), plaintext);
+        }
+
+        // Welcome
+        let (_commit, welcome_option, _key_package_bundle_option) = group_state
+            .create_commit(&[], credential_bundle, &[&add_plaintext], &[], true)
+            .unwrap();
+        let welcome = welcome_option.unwrap();
+        let welcome_encoded = welcome.encode_detached().unwrap();
+        assert_eq!(welcome.encoded_len().unwrap(), welcome_encoded.len());
+        assert_eq!(welcome.encode_exact().unwrap(), welcome_encoded);
+        let welcome_borrowed =
+            Welcome::decode_borrowed(&mut Cursor::new(&welcome_encoded)).unwrap();
+        assert_eq!(welcome_borrowed.secrets(), welcome.secrets());
+        assert_eq!(welcome_borrowed.into_owned(), welcome);
+    }
+}
+
+/// This test makes sure that all messages can be decoded from their common
+/// `MLSMessage` framing.
+#[test]
+fn test_mls_message_encoding() {
+    let test_setup = create_encoding_test_setup();
+    let test_clients = test_setup.clients.borrow();
+    let alice = test_clients.get("alice").unwrap().borrow();
+
+    for group_state in alice.group_states.borrow_mut().values_mut() {
+        let credential_bundle = alice
+            .credential_bundles
+            .get(&group_state.ciphersuite().name())
+            .unwrap();
+        let message = randombytes(random_usize() % 1000);
+        let aad = randombytes(random_usize() % 1000);
+
+        let ciphertext = group_state
+            .create_application_message(&aad, &message, &credential_bundle)
+            .unwrap();
+        let charlie_key_package = test_setup
+            ._key_store
+            .borrow_mut()
+            .get_mut(&("charlie", group_state.ciphersuite().name()))
+            .unwrap()
+            .pop()
+            .unwrap();
+        let add_plaintext = group_state
+            .create_add_proposal(&aad, credential_bundle, charlie_key_package.clone())
+            .expect("Could not create proposal.");
+        let (_commit, welcome_option, _key_package_bundle_option) = group_state
+            .create_commit(&[], credential_bundle, &[&add_plaintext], &[], true)
+            .unwrap();
+
+        let messages = vec![
+            (MLSMessage::from(ciphertext), WireFormat::Ciphertext),
+            (MLSMessage::from(add_plaintext), WireFormat::Plaintext),
+            (
+                MLSMessage::from(welcome_option.unwrap()),
+                WireFormat::Welcome,
+            ),
+            (
+                MLSMessage::from(charlie_key_package),
+                WireFormat::KeyPackage,
+            ),
+        ];
+        for (message, wire_format) in messages {
+            assert_eq!(message.wire_format(), wire_format);
+            let encoded = message.encode_detached().unwrap();
+            let decoded = MLSMessage::decode(&mut Cursor::new(&encoded)).unwrap();
+            assert_eq!(decoded, message);
+
+            // Unknown wire formats are rejected
+            let mut invalid = encoded.clone();
+            invalid[1] = 0;
+            assert!(MLSMessage::decode(&mut Cursor::new(&invalid)).is_err());
+        }
+    }
+}
+
+/// Strict import and export of the public wire structs with `to_tls_bytes`
+/// and `from_tls_bytes`.
+#[test]
+fn test_tls_bytes() {
+    let test_setup = create_encoding_test_setup();
+    let test_clients = test_setup.clients.borrow();
+    let alice = test_clients.get("alice").unwrap().borrow();
+
+    for group_state in alice.group_states.borrow_mut().values_mut() {
+        let credential_bundle = alice
+            .credential_bundles
+            .get(&group_state.ciphersuite().name())
+            .unwrap();
+        let charlie_key_package = test_setup
+            ._key_store
+            .borrow_mut()
+            .get_mut(&("charlie", group_state.ciphersuite().name()))
+            .unwrap()
+            .pop()
+            .unwrap();
+        let add = group_state
+            .create_add_proposal(&[], credential_bundle, charlie_key_package.clone())
+            .expect("Could not create proposal.");
+        let (_commit, welcome_option, _key_package_bundle_option) = group_state
+            .create_commit(&[], credential_bundle, &[&add], &[], true)
+            .unwrap();
+        let welcome = welcome_option.unwrap();
+
+        // Key packages
+        let encoded = charlie_key_package.to_tls_bytes().unwrap();
+        assert_eq!(encoded, charlie_key_package.encode_detached().unwrap());
+        assert_eq!(
+            KeyPackage::from_tls_bytes(&encoded).unwrap(),
+            charlie_key_package
+        );
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            KeyPackage::from_tls_bytes(&trailing).err(),
+            Some(CodecError::TrailingData)
+        );
+        assert!(KeyPackage::from_tls_bytes(&encoded[..encoded.len() - 1]).is_err());
+        // The signature is at the end of the encoding.
+        let mut invalid_signature = encoded.clone();
+        let last = invalid_signature.len() - 1;
+        invalid_signature[last] ^= 0xff;
+        assert!(KeyPackage::from_tls_bytes(&invalid_signature).is_err());
+        // The ciphersuite follows the protocol version.
+        let mut unsupported_ciphersuite = encoded.clone();
+        unsupported_ciphersuite[1] = 0xff;
+        unsupported_ciphersuite[2] = 0xff;
+        assert!(KeyPackage::from_tls_bytes(&unsupported_ciphersuite).is_err());
+        // Whether the lifetime is valid at the current time isn't checked when
+        // decoding.
+        let decoded = KeyPackage::from_tls_bytes(&encoded).unwrap();
+        assert_eq!(
+            decoded.verify_with_clock(&FixedClock::new(u64::MAX)),
+            Err(KeyPackageError::InvalidLifetimeExtension)
+        );
+
+        // Credentials
+        let credential = credential_bundle.credential();
+        let encoded = credential.to_tls_bytes().unwrap();
+        assert_eq!(&Credential::from_tls_bytes(&encoded).unwrap(), credential);
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            Credential::from_tls_bytes(&trailing).err(),
+            Some(CodecError::TrailingData)
+        );
+
+        // Welcome messages
+        let encoded = welcome.to_tls_bytes().unwrap();
+        assert_eq!(Welcome::from_tls_bytes(&encoded).unwrap(), welcome);
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            Welcome::from_tls_bytes(&trailing).err(),
+            Some(CodecError::TrailingData)
+        );
+        let mut unsupported_version = encoded.clone();
+        unsupported_version[0] = 0xff;
+        assert!(Welcome::from_tls_bytes(&unsupported_version).is_err());
+
+        // MLS messages
+        let message = MLSMessage::from(add);
+        let encoded = message.to_tls_bytes().unwrap();
+        assert_eq!(MLSMessage::from_tls_bytes(&encoded).unwrap(), message);
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            MLSMessage::from_tls_bytes(&trailing).err(),
+            Some(CodecError::TrailingData)
+        );
+    }
+}
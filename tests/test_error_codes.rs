@@ -0,0 +1,105 @@
+use openmls::prelude::*;
+
+/// The numeric values of the error codes must never change.
+#[test]
+fn error_code_values() {
+    assert_eq!(u16::from(ErrorCode::CodecDecodingError), 101);
+    assert_eq!(u16::from(ErrorCode::ConfigUnsupportedCiphersuite), 202);
+    assert_eq!(u16::from(ErrorCode::CredentialKeyMismatch), 402);
+    assert_eq!(u16::from(ErrorCode::KeyPackageInvalidSignature), 505);
+    assert_eq!(u16::from(ErrorCode::KeyPackageIdentityMismatch), 513);
+    assert_eq!(u16::from(ErrorCode::ExtensionLifetimeExpired), 602);
+    assert_eq!(u16::from(ErrorCode::TreeInvalidTree), 702);
+    assert_eq!(u16::from(ErrorCode::PlaintextInvalidMembershipTag), 901);
+    assert_eq!(u16::from(ErrorCode::CiphertextReplay), 1006);
+    assert_eq!(u16::from(ErrorCode::CiphertextInvalidSignature), 1009);
+    assert_eq!(u16::from(ErrorCode::CiphertextSecretReuse), 1010);
+    assert_eq!(u16::from(ErrorCode::WelcomeJoinerSecretNotFound), 1101);
+    assert_eq!(u16::from(ErrorCode::ApplyCommitEpochMismatch), 1200);
+    assert_eq!(u16::from(ErrorCode::CreateCommitCannotRemoveSelf), 1300);
+    assert_eq!(u16::from(ErrorCode::ExporterUnknownEpoch), 1402);
+    assert_eq!(u16::from(ErrorCode::StorageGroupIdMismatch), 1605);
+    assert_eq!(u16::from(ErrorCode::StorageEpochRollback), 1606);
+    assert_eq!(u16::from(ErrorCode::StorageStateTooOld), 1607);
+    assert_eq!(u16::from(ErrorCode::ManagedGroupUseAfterEviction), 2001);
+    assert_eq!(
+        u16::from(ErrorCode::ManagedGroupUnsupportedPreSharedKeys),
+        2003
+    );
+    assert_eq!(u16::from(ErrorCode::MembershipUnknownIdentity), 2100);
+    assert_eq!(u16::from(ErrorCode::RolePolicyInsufficientRole), 2201);
+    assert_eq!(
+        u16::from(ErrorCode::CredentialRotationIdentityMismatch),
+        2300
+    );
+    assert_eq!(
+        u16::from(ErrorCode::CredentialRotationCredentialMismatch),
+        2301
+    );
+    assert_eq!(u16::from(ErrorCode::CredentialRotationNotCommitted), 2302);
+}
+
+/// Wrapped errors return the code of the innermost error.
+#[test]
+fn error_code_of_wrapped_errors() {
+    assert_eq!(
+        CodecError::TrailingData.code(),
+        ErrorCode::CodecTrailingData
+    );
+    assert_eq!(
+        KeyPackageError::from(ConfigError::UnsupportedCiphersuite).code(),
+        ErrorCode::ConfigUnsupportedCiphersuite
+    );
+    assert_eq!(
+        CredentialError::from(CodecError::DecodingError).code(),
+        ErrorCode::CodecDecodingError
+    );
+    assert_eq!(
+        GroupError::from(WelcomeError::MissingRatchetTree).code(),
+        ErrorCode::WelcomeMissingRatchetTree
+    );
+    assert_eq!(
+        ManagedGroupError::from(GroupError::from(ApplyCommitError::SelfRemoved)).code(),
+        ErrorCode::ApplyCommitSelfRemoved
+    );
+    assert_eq!(
+        ManagedGroupError::from(MembershipError::NoMatchingKeyPackage).code(),
+        ErrorCode::MembershipNoMatchingKeyPackage
+    );
+    assert_eq!(
+        ManagedGroupError::LibraryError("error".into()).code(),
+        ErrorCode::ManagedGroupLibraryError
+    );
+    assert_eq!(
+        InvalidMessageError::from(ApplyCommitError::EpochMismatch).code(),
+        ErrorCode::ApplyCommitEpochMismatch
+    );
+    assert_eq!(
+        u16::from(InvalidMessageError::InvalidMembershipTag.code()),
+        2401
+    );
+    assert_eq!(
+        KeyPackageError::from(ExtensionError::from(LifetimeExtensionError::Expired)).code(),
+        ErrorCode::ExtensionLifetimeExpired
+    );
+    assert_eq!(
+        KeyPackageError::from(ExtensionError::from(CodecError::DecodingError)).code(),
+        ErrorCode::CodecDecodingError
+    );
+    assert_eq!(
+        GroupError::from(WelcomeError::from(TreeError::InvalidTree)).code(),
+        ErrorCode::TreeInvalidTree
+    );
+    assert_eq!(
+        ApplyCommitError::from(TreeError::from(ConfigError::UnsupportedCiphersuite)).code(),
+        ErrorCode::ConfigUnsupportedCiphersuite
+    );
+    assert_eq!(
+        StorageError::EpochRollback.code(),
+        ErrorCode::StorageEpochRollback
+    );
+    assert_eq!(
+        MLSPlaintextError::NotAnApplicationMessage.code(),
+        ErrorCode::PlaintextNotAnApplicationMessage
+    );
+}
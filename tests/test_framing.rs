@@ -64,3 +64,62 @@ fn padding() {
         }
     }
 }
+
+#[test]
+fn padding_power_of_two() {
+    // Create a test config for a single client supporting all possible
+    // ciphersuites.
+    let alice_config = TestClientConfig {
+        name: "alice",
+        ciphersuites: Config::supported_ciphersuite_names(),
+    };
+
+    let mut test_group_configs = Vec::new();
+
+    // Create a group config with power-of-two padding for each ciphersuite.
+    for ciphersuite_name in Config::supported_ciphersuite_names() {
+        let test_group = TestGroupConfig {
+            ciphersuite: ciphersuite_name,
+            config: GroupConfig {
+                padding_policy: PaddingPolicy::PowerOfTwo,
+                ..GroupConfig::default()
+            },
+            members: vec![alice_config.clone()],
+        };
+        test_group_configs.push(test_group);
+    }
+
+    // Create the test setup config.
+    let test_setup_config = TestSetupConfig {
+        clients: vec![alice_config],
+        groups: test_group_configs,
+    };
+
+    // Initialize the test setup according to config.
+    let test_setup = setup(test_setup_config);
+
+    let test_clients = test_setup.clients.borrow();
+    let alice = test_clients.get("alice").unwrap().borrow();
+
+    // Create a message in each group and test the padding.
+    for group_state in alice.group_states.borrow_mut().values_mut() {
+        let credential_bundle = alice
+            .credential_bundles
+            .get(&group_state.ciphersuite().name())
+            .unwrap();
+        for _ in 0..100 {
+            let message = randombytes(random_usize() % 1000);
+            let aad = randombytes(random_usize() % 1000);
+            let length = group_state
+                .create_application_message(&aad, &message, &credential_bundle)
+                .unwrap()
+                .ciphertext
+                .len();
+            assert!(
+                length.is_power_of_two(),
+                "Error: message length {} is not a power of two",
+                length
+            );
+        }
+    }
+}
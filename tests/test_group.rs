@@ -60,11 +60,9 @@ fn create_commit_optional_path() {
         // Alice proposes to add Bob with forced self-update
         // Even though there are only Add Proposals, this should generated a path field
         // on the Commit
-        let bob_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            bob_key_package.clone(),
-        );
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
         let epoch_proposals = vec![bob_add_proposal];
         let (mls_plaintext_commit, _welcome_bundle_alice_bob_option, kpb_option) = match group_alice
             .create_commit(
@@ -88,11 +86,9 @@ fn create_commit_optional_path() {
         // Since there are only Add Proposals, this does not generate a path field on
         // the Commit Creating a second proposal to add the same member should
         // not fail, only committing that proposal should fail
-        let bob_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            bob_key_package.clone(),
-        );
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
         let epoch_proposals = &[&bob_add_proposal];
         let (mls_plaintext_commit, welcome_bundle_alice_bob_option, kpb_option) = match group_alice
             .create_commit(
@@ -200,11 +196,9 @@ fn basic_group_setup() {
         .unwrap();
 
         // Alice adds Bob
-        let bob_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            bob_key_package.clone(),
-        );
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
         let _commit = match group_alice.create_commit(
             group_aad,
             &alice_credential_bundle,
@@ -279,11 +273,9 @@ fn group_operations() {
         .unwrap();
 
         // === Alice adds Bob ===
-        let bob_add_proposal = group_alice.create_add_proposal(
-            group_aad,
-            &alice_credential_bundle,
-            bob_key_package.clone(),
-        );
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
         let epoch_proposals = &[&bob_add_proposal];
         let (mls_plaintext_commit, welcome_bundle_alice_bob_option, kpb_option) = group_alice
             .create_commit(
@@ -487,8 +479,9 @@ fn group_operations() {
         .unwrap();
         let charlie_key_package = charlie_key_package_bundle.key_package().clone();
 
-        let add_charlie_proposal_bob =
-            group_bob.create_add_proposal(&[], &bob_credential_bundle, charlie_key_package);
+        let add_charlie_proposal_bob = group_bob
+            .create_add_proposal(&[], &bob_credential_bundle, charlie_key_package)
+            .expect("Could not create proposal.");
 
         let (mls_plaintext_commit, welcome_for_charlie_option, kpb_option) = match group_bob
             .create_commit(
@@ -656,14 +649,318 @@ fn group_operations() {
         }
 
         // Make sure all groups export the same key
-        let alice_exporter = group_alice.export_secret("export test", 32).unwrap();
-        let charlie_exporter = group_charlie.export_secret("export test", 32).unwrap();
+        let alice_exporter = group_alice.export_secret("export test", &[], 32).unwrap();
+        let charlie_exporter = group_charlie.export_secret("export test", &[], 32).unwrap();
         assert_eq!(alice_exporter, charlie_exporter);
 
+        // The context is bound to the exported secret
+        let alice_exporter_with_context = group_alice
+            .export_secret("export test", b"context", 32)
+            .unwrap();
+        assert_ne!(alice_exporter, alice_exporter_with_context);
+
         // Now alice tries to derive an exporter with too large of a key length.
         let exporter_length: usize = u16::MAX.into();
         let exporter_length = exporter_length + 1;
-        let alice_exporter = group_alice.export_secret("export test", exporter_length);
-        assert!(alice_exporter.is_err())
+        let alice_exporter = group_alice.export_secret("export test", &[], exporter_length);
+        assert_eq!(
+            alice_exporter,
+            Err(GroupError::ExporterError(ExporterError::KeyLengthTooLong))
+        );
+
+        // Empty keys can't be exported either.
+        let alice_exporter = group_alice.export_secret("export test", &[], 0);
+        assert_eq!(
+            alice_exporter,
+            Err(GroupError::ExporterError(ExporterError::KeyLengthZero))
+        );
+    }
+}
+
+#[test]
+/// This test checks that application messages of retained past epochs can
+/// still be decrypted and that secrets can still be exported from them
+///  - Alice creates a group that retains one past epoch and adds Bob
+///  - Alice sends a message to Bob before committing an update twice
+///  - Bob decrypts the message after the first Commit, but not after the
+///    second one
+fn past_epoch_retention() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_aad = b"Alice's test group";
+        let group_config = GroupConfig {
+            additional_as_epochs: 1,
+            ..GroupConfig::default()
+        };
+
+        // Define credential bundles
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+
+        // Generate KeyPackages
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![]).unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package();
+
+        // === Alice creates a group and adds Bob ===
+        let mut group_alice = MlsGroup::new(
+            &[1, 2, 3, 4],
+            ciphersuite.name(),
+            alice_key_package_bundle,
+            group_config.clone(),
+        )
+        .unwrap();
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
+        let (mls_plaintext_commit, welcome_option, _) = group_alice
+            .create_commit(
+                group_aad,
+                &alice_credential_bundle,
+                &[&bob_add_proposal],
+                &[],
+                false,
+            )
+            .expect("Error creating commit");
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+            .expect("error applying commit");
+        let mut group_bob = MlsGroup::new_from_welcome_with_config(
+            welcome_option.unwrap(),
+            Some(group_alice.tree().public_key_tree_copy()),
+            bob_key_package_bundle,
+            group_config,
+        )
+        .expect("Error creating group from Welcome");
+
+        // === Alice sends two messages and commits an update ===
+        let message_alice = [1, 2, 3];
+        let first_ciphertext = group_alice
+            .create_application_message(&[], &message_alice, &alice_credential_bundle)
+            .unwrap();
+        let second_ciphertext = group_alice
+            .create_application_message(&[], &message_alice, &alice_credential_bundle)
+            .unwrap();
+        let first_epoch = group_alice.context().epoch;
+        let first_exporter = group_alice.export_secret("sframe", b"context", 16).unwrap();
+        for commit_counter in 0..2 {
+            let (mls_plaintext_commit, _, kpb_option) = group_alice
+                .create_commit(group_aad, &alice_credential_bundle, &[], &[], true)
+                .expect("Error creating commit");
+            group_alice
+                .apply_commit(&mls_plaintext_commit, &[], &[kpb_option.unwrap()])
+                .expect("Error applying commit (Alice)");
+            group_bob
+                .apply_commit(&mls_plaintext_commit, &[], &[])
+                .expect("Error applying commit (Bob)");
+
+            // The message from the previous epoch can be decrypted after the
+            // first Commit, but not after the second one
+            if commit_counter == 0 {
+                let mls_plaintext_bob = group_bob
+                    .decrypt(&first_ciphertext)
+                    .expect("Error decrypting message of the past epoch");
+                assert_eq!(
+                    mls_plaintext_bob.as_application_message(),
+                    Ok(message_alice.as_ref())
+                );
+                // Secrets can still be exported from the past epoch
+                assert_eq!(
+                    group_bob.export_secret_for_epoch(first_epoch, "sframe", b"context", 16),
+                    Ok(first_exporter.clone())
+                );
+            } else {
+                assert_eq!(
+                    group_bob.decrypt(&second_ciphertext),
+                    Err(MLSCiphertextError::WrongEpoch)
+                );
+                assert_eq!(
+                    group_bob.export_secret_for_epoch(first_epoch, "sframe", b"context", 16),
+                    Err(GroupError::ExporterError(ExporterError::UnknownEpoch))
+                );
+            }
+        }
+    }
+}
+
+#[test]
+/// This test checks that the configuration of an existing group can be
+/// changed
+///  - Alice creates a group without past epoch retention and adds Bob
+///  - Bob enables the retention of one past epoch
+///  - Bob decrypts a message of the past epoch after a Commit
+///  - Bob disables the retention again, which deletes the past epoch
+fn set_config_of_existing_group() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_aad = b"Alice's test group";
+
+        // Define credential bundles
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+
+        // Generate KeyPackages
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![]).unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package();
+
+        // === Alice creates a group and adds Bob ===
+        let mut group_alice = MlsGroup::new(
+            &[1, 2, 3, 4],
+            ciphersuite.name(),
+            alice_key_package_bundle,
+            GroupConfig::default(),
+        )
+        .unwrap();
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
+        let (mls_plaintext_commit, welcome_option, _) = group_alice
+            .create_commit(
+                group_aad,
+                &alice_credential_bundle,
+                &[&bob_add_proposal],
+                &[],
+                false,
+            )
+            .expect("Error creating commit");
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+            .expect("error applying commit");
+        let mut group_bob = MlsGroup::new_from_welcome(
+            welcome_option.unwrap(),
+            Some(group_alice.tree().public_key_tree_copy()),
+            bob_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+
+        // === Bob enables the retention of one past epoch ===
+        group_bob.set_config(GroupConfig {
+            additional_as_epochs: 1,
+            ..GroupConfig::default()
+        });
+
+        // === Alice sends a message and commits an update ===
+        let message_alice = [1, 2, 3];
+        let mls_ciphertext = group_alice
+            .create_application_message(&[], &message_alice, &alice_credential_bundle)
+            .unwrap();
+        let first_epoch = group_alice.context().epoch;
+        let (mls_plaintext_commit, _, kpb_option) = group_alice
+            .create_commit(group_aad, &alice_credential_bundle, &[], &[], true)
+            .expect("Error creating commit");
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[], &[kpb_option.unwrap()])
+            .expect("Error applying commit (Alice)");
+        group_bob
+            .apply_commit(&mls_plaintext_commit, &[], &[])
+            .expect("Error applying commit (Bob)");
+
+        // === Bob decrypts the message of the past epoch ===
+        let mls_plaintext_bob = group_bob
+            .decrypt(&mls_ciphertext)
+            .expect("Error decrypting message of the past epoch");
+        assert_eq!(
+            mls_plaintext_bob.as_application_message(),
+            Ok(message_alice.as_ref())
+        );
+
+        // === Disabling the retention deletes the past epoch ===
+        group_bob.set_config(GroupConfig::default());
+        assert_eq!(
+            group_bob.export_secret_for_epoch(first_epoch, "sframe", b"context", 16),
+            Err(GroupError::ExporterError(ExporterError::UnknownEpoch))
+        );
+    }
+}
+
+#[test]
+/// This test makes sure that key packages are validated with the clock of the
+/// group:
+///  - Bob creates a key package at a fixed time in the past
+///  - A group with the system clock refuses to add Bob
+///  - A group with a clock at the same fixed time adds Bob
+fn group_clock() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_aad = b"Alice's test group";
+        let past_clock = FixedClock::new(1_000_000);
+
+        // Define credential bundles
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+
+        // Bob's key package expired a long time ago
+        let bob_key_package_bundle = KeyPackageBundle::new_with_clock(
+            &[ciphersuite.name()],
+            &bob_credential_bundle,
+            vec![],
+            &past_clock,
+        )
+        .unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package();
+
+        // Alice's group with the system clock refuses to add Bob
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let group_alice = MlsGroup::new(
+            &[1, 2, 3, 4],
+            ciphersuite.name(),
+            alice_key_package_bundle,
+            GroupConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            group_alice
+                .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+                .err(),
+            Some(GroupError::KeyPackageError(
+                KeyPackageError::InvalidLifetimeExtension
+            ))
+        );
+
+        // Alice's group with the past clock adds Bob
+        let alice_key_package_bundle = KeyPackageBundle::new_with_clock(
+            &[ciphersuite.name()],
+            &alice_credential_bundle,
+            vec![],
+            &past_clock,
+        )
+        .unwrap();
+        let mut group_alice = MlsGroup::new(
+            &[1, 2, 3, 4],
+            ciphersuite.name(),
+            alice_key_package_bundle,
+            GroupConfig {
+                clock: SharedClock::new(past_clock),
+                ..GroupConfig::default()
+            },
+        )
+        .unwrap();
+        let bob_add_proposal = group_alice
+            .create_add_proposal(group_aad, &alice_credential_bundle, bob_key_package.clone())
+            .expect("Could not create proposal.");
+        let (mls_plaintext_commit, _, _) = group_alice
+            .create_commit(
+                group_aad,
+                &alice_credential_bundle,
+                &[&bob_add_proposal],
+                &[],
+                false,
+            )
+            .expect("Error creating commit");
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+            .expect("Error applying commit");
     }
 }
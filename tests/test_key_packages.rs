@@ -114,3 +114,396 @@ key_package_generation!(
     CiphersuiteName::MLS10_256_DHKEMX448_CHACHA20POLY1305_SHA512_Ed448,
     false
 );
+
+#[test]
+fn key_package_verify_with_clock() {
+    let ciphersuite_name = Config::supported_ciphersuite_names()[0];
+    let credential_bundle =
+        CredentialBundle::new(vec![1, 2, 3], CredentialType::Basic, ciphersuite_name).unwrap();
+
+    // Create a key package with a clock that is a year ahead of the system
+    // clock, e.g. because the device clock is wrong.
+    let year = 60 * 60 * 24 * 365;
+    let mut clock = FixedClock::new(SystemClock.now() + year);
+    let lifetime_extension = Box::new(LifetimeExtension::new_with_clock(60, &clock));
+    let kpb = KeyPackageBundle::new(
+        &[ciphersuite_name],
+        &credential_bundle,
+        vec![lifetime_extension],
+    )
+    .unwrap();
+
+    // The key package is not valid yet according to the system clock, but it
+    // is according to the provided clock.
+    assert!(kpb.key_package().verify().is_err());
+    assert!(kpb.key_package().verify_with_clock(&clock).is_ok());
+
+    // Decoding doesn't check the lifetime.
+    let encoded = kpb.key_package().encode_detached().unwrap();
+    let decoded = KeyPackage::decode(&mut Cursor::new(&encoded)).unwrap();
+    assert!(decoded.verify_with_clock(&clock).is_ok());
+
+    // The key package expires after its lifetime.
+    clock.advance(60);
+    assert!(decoded.verify_with_clock(&clock).is_err());
+}
+
+#[test]
+fn key_package_max_lifetime() {
+    let ciphersuite_name = Config::supported_ciphersuite_names()[0];
+    let credential_bundle =
+        CredentialBundle::new(vec![1, 2, 3], CredentialType::Basic, ciphersuite_name).unwrap();
+
+    // A key package with the maximum lifetime is valid. Note that the lifetime
+    // margin is added to the lifetime.
+    let lifetime = Config::max_key_package_lifetime() - Config::key_package_lifetime_margin();
+    let kpb = KeyPackageBundle::new(
+        &[ciphersuite_name],
+        &credential_bundle,
+        vec![Box::new(LifetimeExtension::new(lifetime))],
+    )
+    .unwrap();
+    assert!(kpb.key_package().verify().is_ok());
+
+    // A key package with a longer lifetime is rejected, also when decoding it.
+    let kpb = KeyPackageBundle::new(
+        &[ciphersuite_name],
+        &credential_bundle,
+        vec![Box::new(LifetimeExtension::new(lifetime + 1))],
+    )
+    .unwrap();
+    assert!(kpb.key_package().verify().is_err());
+    let encoded = kpb.key_package().encode_detached().unwrap();
+    assert!(KeyPackage::decode(&mut Cursor::new(&encoded)).is_err());
+}
+
+#[test]
+fn key_package_store() {
+    let ciphersuite_name = Config::supported_ciphersuite_names()[0];
+    let alice_credential_bundle =
+        CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite_name).unwrap();
+    let bob_credential_bundle =
+        CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite_name).unwrap();
+
+    // Bob generates and publishes key packages
+    let mut bob_store = InMemoryKeyPackageStore::new();
+    let published = bob_store
+        .generate(3, &[ciphersuite_name], &bob_credential_bundle, Vec::new())
+        .unwrap();
+    assert_eq!(published.len(), 3);
+    assert_eq!(bob_store.len(), 3);
+    for key_package in published.iter() {
+        assert!(bob_store.get(&key_package.hash()).is_some());
+        assert!(bob_store.key_packages().contains(key_package));
+    }
+
+    // Replenishing only creates the missing bundles
+    assert!(bob_store
+        .replenish(2, &[ciphersuite_name], &bob_credential_bundle, Vec::new())
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        bob_store
+            .replenish(4, &[ciphersuite_name], &bob_credential_bundle, Vec::new())
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(bob_store.len(), 4);
+
+    // Bob persists the store and loads it again
+    let mut serialized_store = Vec::new();
+    bob_store.save(&mut serialized_store).unwrap();
+    let mut bob_store = InMemoryKeyPackageStore::load(serialized_store.as_slice()).unwrap();
+    assert_eq!(bob_store.len(), 4);
+    for key_package in published.iter() {
+        assert!(bob_store.get(&key_package.hash()).is_some());
+    }
+
+    // Alice adds Bob with one of the published key packages
+    let alice_key_package_bundle =
+        KeyPackageBundle::new(&[ciphersuite_name], &alice_credential_bundle, Vec::new()).unwrap();
+    let mut group_alice = MlsGroup::new(
+        &[1, 2, 3, 4],
+        ciphersuite_name,
+        alice_key_package_bundle,
+        GroupConfig::default(),
+    )
+    .unwrap();
+    let bob_add_proposal = group_alice
+        .create_add_proposal(&[], &alice_credential_bundle, published[1].clone())
+        .expect("Could not create proposal.");
+    let (mls_plaintext_commit, welcome_option, _kpb_option) = group_alice
+        .create_commit(
+            &[],
+            &alice_credential_bundle,
+            &[&bob_add_proposal],
+            &[],
+            false,
+        )
+        .unwrap();
+    group_alice
+        .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+        .unwrap();
+    let welcome = welcome_option.unwrap();
+
+    // Bob fails to join without the ratchet tree and the bundle referenced by
+    // the Welcome stays in the store
+    let bob_key_package_bundle = bob_store.bundle_for_welcome(&welcome).unwrap();
+    assert_eq!(bob_key_package_bundle.key_package(), &published[1]);
+    assert_eq!(
+        MlsGroup::new_from_welcome(welcome.clone(), None, bob_key_package_bundle).err(),
+        Some(GroupError::WelcomeError(WelcomeError::MissingRatchetTree))
+    );
+    assert_eq!(bob_store.len(), 4);
+
+    // Bob joins with the ratchet tree and the bundle is consumed
+    let bob_key_package_bundle = bob_store.bundle_for_welcome(&welcome).unwrap();
+    MlsGroup::new_from_welcome(
+        welcome.clone(),
+        Some(group_alice.tree().public_key_tree_copy()),
+        bob_key_package_bundle,
+    )
+    .expect("Error joining group with bundle from the store");
+    assert!(bob_store.remove_for_welcome(&welcome));
+    assert_eq!(bob_store.len(), 3);
+    assert!(bob_store.bundle_for_welcome(&welcome).is_none());
+
+    // Nothing is purged before the key packages expire
+    assert_eq!(bob_store.purge_expired(&SystemClock), 0);
+    let expired_clock = FixedClock::new(SystemClock.now() + Config::max_key_package_lifetime());
+    assert_eq!(bob_store.purge_expired(&expired_clock), 3);
+    assert!(bob_store.is_empty());
+}
+
+#[test]
+fn last_resort_key_package() {
+    let ciphersuite_name = Config::supported_ciphersuite_names()[0];
+    let alice_credential_bundle =
+        CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite_name).unwrap();
+    let bob_credential_bundle =
+        CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite_name).unwrap();
+
+    // Bob only has a last resort key package
+    let mut bob_store = InMemoryKeyPackageStore::new();
+    let last_resort_key_package = bob_store
+        .generate(
+            1,
+            &[ciphersuite_name],
+            &bob_credential_bundle,
+            vec![Box::new(LastResortExtension::new())],
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(last_resort_key_package.is_last_resort());
+    assert!(last_resort_key_package.verify().is_ok());
+
+    // The extension is advertised in the capabilities
+    let capabilities_extension = last_resort_key_package
+        .extensions()
+        .iter()
+        .find(|e| e.extension_type() == ExtensionType::Capabilities)
+        .unwrap()
+        .to_capabilities_extension()
+        .unwrap();
+    assert!(capabilities_extension
+        .extensions()
+        .contains(&ExtensionType::LastResort));
+
+    // Bob is added to two groups with the same key package
+    for group_id in &[[1u8, 2, 3, 4], [5u8, 6, 7, 8]] {
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite_name], &alice_credential_bundle, Vec::new())
+                .unwrap();
+        let mut group_alice = MlsGroup::new(
+            group_id,
+            ciphersuite_name,
+            alice_key_package_bundle,
+            GroupConfig::default(),
+        )
+        .unwrap();
+        let bob_add_proposal = group_alice
+            .create_add_proposal(
+                &[],
+                &alice_credential_bundle,
+                last_resort_key_package.clone(),
+            )
+            .expect("Could not create proposal.");
+        let (mls_plaintext_commit, welcome_option, _kpb_option) = group_alice
+            .create_commit(
+                &[],
+                &alice_credential_bundle,
+                &[&bob_add_proposal],
+                &[],
+                false,
+            )
+            .unwrap();
+        group_alice
+            .apply_commit(&mls_plaintext_commit, &[&bob_add_proposal], &[])
+            .unwrap();
+
+        // The bundle stays in the store
+        let welcome = welcome_option.unwrap();
+        let bob_key_package_bundle = bob_store.bundle_for_welcome(&welcome).unwrap();
+        MlsGroup::new_from_welcome(
+            welcome.clone(),
+            Some(group_alice.tree().public_key_tree_copy()),
+            bob_key_package_bundle,
+        )
+        .expect("Error joining group with last resort key package");
+        assert!(!bob_store.remove_for_welcome(&welcome));
+        assert_eq!(bob_store.len(), 1);
+    }
+
+    // The last resort key package isn't counted when replenishing
+    assert_eq!(
+        bob_store
+            .replenish(1, &[ciphersuite_name], &bob_credential_bundle, Vec::new())
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(bob_store
+        .replenish_last_resort(
+            &[ciphersuite_name],
+            &bob_credential_bundle,
+            Vec::new(),
+            &SystemClock
+        )
+        .unwrap()
+        .is_none());
+
+    // An expired last resort key package is kept until it was replaced
+    let expired_clock = FixedClock::new(SystemClock.now() + Config::max_key_package_lifetime());
+    assert_eq!(bob_store.purge_expired(&expired_clock), 1);
+    assert_eq!(bob_store.key_packages(), vec![last_resort_key_package]);
+    let new_last_resort_key_package = bob_store
+        .replenish_last_resort(
+            &[ciphersuite_name],
+            &bob_credential_bundle,
+            Vec::new(),
+            &expired_clock,
+        )
+        .unwrap()
+        .unwrap();
+    assert!(new_last_resort_key_package.is_last_resort());
+    assert_eq!(bob_store.purge_expired(&expired_clock), 1);
+    assert_eq!(bob_store.key_packages(), vec![new_last_resort_key_package]);
+}
+
+/// Create a set of key packages for all supported ciphersuites.
+#[test]
+fn key_packages_for_ciphersuites() {
+    let ciphersuite_names = Config::supported_ciphersuite_names();
+    let credential_bundles: Vec<CredentialBundle> = ciphersuite_names
+        .iter()
+        .map(|&name| CredentialBundle::new("Alice".into(), CredentialType::Basic, name).unwrap())
+        .collect();
+    let credential_bundle_refs: Vec<&CredentialBundle> = credential_bundles.iter().collect();
+    let credential_bundle = &credential_bundles[0];
+
+    let key_package_bundles =
+        KeyPackageBundle::new_for_ciphersuites(&ciphersuite_names, &credential_bundle_refs, vec![])
+            .unwrap();
+    assert_eq!(key_package_bundles.len(), ciphersuite_names.len());
+    for (key_package_bundle, &ciphersuite_name) in
+        key_package_bundles.iter().zip(ciphersuite_names.iter())
+    {
+        let key_package = key_package_bundle.key_package();
+        assert!(key_package.verify().is_ok());
+        assert_eq!(key_package.ciphersuite_name(), ciphersuite_name);
+        assert!(credential_bundles
+            .iter()
+            .any(|credential_bundle| credential_bundle.credential() == key_package.credential()));
+
+        // All ciphersuites are advertised, starting with the own one.
+        let capabilities_extension = key_package
+            .extensions()
+            .iter()
+            .find(|e| e.extension_type() == ExtensionType::Capabilities)
+            .unwrap()
+            .to_capabilities_extension()
+            .unwrap();
+        assert_eq!(capabilities_extension.ciphersuites()[0], ciphersuite_name);
+        assert_eq!(
+            capabilities_extension.ciphersuites().len(),
+            ciphersuite_names.len()
+        );
+        assert!(ciphersuite_names
+            .iter()
+            .all(|name| capabilities_extension.ciphersuites().contains(name)));
+    }
+
+    // At least one ciphersuite is required.
+    assert!(KeyPackageBundle::new_for_ciphersuites(&[], &[credential_bundle], vec![]).is_err());
+    assert!(KeyPackageBundle::new(&[], &credential_bundle, vec![]).is_err());
+
+    // The capabilities extension is created automatically.
+    let capabilities_extension = Box::new(CapabilitiesExtension::new(
+        None,
+        Some(&ciphersuite_names[..]),
+        None,
+    ));
+    assert_eq!(
+        KeyPackageBundle::new_for_ciphersuites(
+            &ciphersuite_names,
+            &credential_bundle_refs,
+            vec![capabilities_extension]
+        )
+        .err(),
+        Some(KeyPackageError::CapabilitiesExtensionSupplied)
+    );
+
+    // Key packages are only signed with a credential of the ciphersuite's
+    // signature scheme.
+    let ed25519_credential_bundle = CredentialBundle::new(
+        "Alice".into(),
+        CredentialType::Basic,
+        CiphersuiteName::MLS10_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+    )
+    .unwrap();
+    let p256_credential_bundle = CredentialBundle::new(
+        "Alice".into(),
+        CredentialType::Basic,
+        CiphersuiteName::MLS10_128_DHKEMP256_AES128GCM_SHA256_P256,
+    )
+    .unwrap();
+    let p256_ciphersuite = [CiphersuiteName::MLS10_128_DHKEMP256_AES128GCM_SHA256_P256];
+    assert_eq!(
+        KeyPackageBundle::new_for_ciphersuites(
+            &p256_ciphersuite,
+            &[&ed25519_credential_bundle],
+            vec![]
+        )
+        .err(),
+        Some(KeyPackageError::SignatureSchemeMismatch)
+    );
+    let key_package_bundles = KeyPackageBundle::new_for_ciphersuites(
+        &p256_ciphersuite,
+        &[&ed25519_credential_bundle, &p256_credential_bundle],
+        vec![],
+    )
+    .unwrap();
+    assert_eq!(
+        key_package_bundles[0].key_package().credential(),
+        p256_credential_bundle.credential()
+    );
+
+    // All credential bundles must have the same identity.
+    let bob_p256_credential_bundle = CredentialBundle::new(
+        "Bob".into(),
+        CredentialType::Basic,
+        CiphersuiteName::MLS10_128_DHKEMP256_AES128GCM_SHA256_P256,
+    )
+    .unwrap();
+    assert_eq!(
+        KeyPackageBundle::new_for_ciphersuites(
+            &p256_ciphersuite,
+            &[&ed25519_credential_bundle, &bob_p256_credential_bundle],
+            vec![]
+        )
+        .err(),
+        Some(KeyPackageError::IdentityMismatch)
+    );
+}
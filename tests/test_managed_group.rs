@@ -3,6 +3,7 @@ use openmls::prelude::*;
 use std::fs::File;
 use std::path::Path;
 use std::str;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Validator function for AddProposals
 /// `(managed_group: &ManagedGroup, sender: &Credential, added_member:
@@ -57,6 +58,30 @@ fn member_added(
         str::from_utf8(added_member.identity()).unwrap(),
     );
 }
+/// Event listener function for users that joined the group
+/// `(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, user_id:
+/// &[u8])`
+fn user_added(managed_group: &ManagedGroup, _aad: &[u8], sender: &Credential, user_id: &[u8]) {
+    println!(
+        "User joined group '{}' by '{}': '{}' added user '{}'",
+        str::from_utf8(&managed_group.group_id().as_slice()).unwrap(),
+        str::from_utf8(&managed_group.credential().identity()).unwrap(),
+        str::from_utf8(sender.identity()).unwrap(),
+        str::from_utf8(user_id).unwrap(),
+    );
+}
+/// Event listener function for users that left the group
+/// `(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential, user_id:
+/// &[u8])`
+fn user_removed(managed_group: &ManagedGroup, _aad: &[u8], sender: &Credential, user_id: &[u8]) {
+    println!(
+        "User left group '{}' by '{}': '{}' removed user '{}'",
+        str::from_utf8(&managed_group.group_id().as_slice()).unwrap(),
+        str::from_utf8(&managed_group.credential().identity()).unwrap(),
+        str::from_utf8(sender.identity()).unwrap(),
+        str::from_utf8(user_id).unwrap(),
+    );
+}
 /// Event listener function for RemoveProposals when a member was removed
 /// `(managed_group: &ManagedGroup, aad: &[u8], removal: &Removal)`
 fn member_removed(managed_group: &ManagedGroup, _aad: &[u8], removal: &Removal) {
@@ -88,14 +113,19 @@ fn member_removed(managed_group: &ManagedGroup, _aad: &[u8], removal: &Removal)
     }
 }
 /// Event listener function for UpdateProposals
-/// `(managed_group: &ManagedGroup, aad: &[u8], sender: &Credential,
-/// update_proposal: &UpdateProposal)`
-fn member_updated(managed_group: &ManagedGroup, _aad: &[u8], updated_member: &Credential) {
+/// `(managed_group: &ManagedGroup, aad: &[u8], old_credential: &Credential,
+/// new_credential: &Credential)`
+fn member_updated(
+    managed_group: &ManagedGroup,
+    _aad: &[u8],
+    _old_credential: &Credential,
+    new_credential: &Credential,
+) {
     println!(
         "UpdateProposal received in group '{}' by '{}': '{}'",
         str::from_utf8(&managed_group.group_id().as_slice()).unwrap(),
         str::from_utf8(&managed_group.credential().identity()).unwrap(),
-        str::from_utf8(updated_member.identity()).unwrap(),
+        str::from_utf8(new_credential.identity()).unwrap(),
     );
 }
 /// Event listener function for application messages
@@ -137,6 +167,12 @@ fn invalid_message_received(managed_group: &ManagedGroup, error: InvalidMessageE
         InvalidMessageError::GroupError(e) => {
             println!("An group error occurred: {:?}", e);
         }
+        InvalidMessageError::UnexpectedWireFormat => {
+            println!("A message that can't be processed by the group was received");
+        }
+        InvalidMessageError::InvalidMembershipTag => {
+            println!("A plaintext message with an invalid membership tag was received");
+        }
     }
 }
 /// Event listener function for errors that occur
@@ -233,8 +269,8 @@ fn managed_group_operations() {
 
             // Check that Alice & Bob are the members of the group
             let members = alice_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
-            assert_eq!(members[1].identity(), b"Bob");
+            assert_eq!(members[0].1.identity(), b"Alice");
+            assert_eq!(members[1].1.identity(), b"Bob");
 
             let mut bob_group = ManagedGroup::new_from_welcome(
                 &bob_credential_bundle,
@@ -274,8 +310,8 @@ fn managed_group_operations() {
 
             // Check that both groups have the same state
             assert_eq!(
-                alice_group.export_secret("", 32),
-                bob_group.export_secret("", 32)
+                alice_group.export_secret("", &[], 32),
+                bob_group.export_secret("", &[], 32)
             );
 
             // Make sure that both groups have the same public tree
@@ -309,8 +345,8 @@ fn managed_group_operations() {
 
             // Check that both groups have the same state
             assert_eq!(
-                alice_group.export_secret("", 32),
-                bob_group.export_secret("", 32)
+                alice_group.export_secret("", &[], 32),
+                bob_group.export_secret("", &[], 32)
             );
 
             // Make sure that both groups have the same public tree
@@ -362,9 +398,9 @@ fn managed_group_operations() {
 
             // Check that Alice, Bob & Charlie are the members of the group
             let members = alice_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
-            assert_eq!(members[1].identity(), b"Bob");
-            assert_eq!(members[2].identity(), b"Charlie");
+            assert_eq!(members[0].1.identity(), b"Alice");
+            assert_eq!(members[1].1.identity(), b"Bob");
+            assert_eq!(members[2].1.identity(), b"Charlie");
 
             // === Charlie sends a message to the group ===
             let message_charlie = b"Hi, I'm Charlie!";
@@ -398,12 +434,12 @@ fn managed_group_operations() {
 
             // Check that all groups have the same state
             assert_eq!(
-                alice_group.export_secret("", 32),
-                bob_group.export_secret("", 32)
+                alice_group.export_secret("", &[], 32),
+                bob_group.export_secret("", &[], 32)
             );
             assert_eq!(
-                alice_group.export_secret("", 32),
-                charlie_group.export_secret("", 32)
+                alice_group.export_secret("", &[], 32),
+                charlie_group.export_secret("", &[], 32)
             );
 
             // Make sure that all groups have the same public tree
@@ -452,8 +488,8 @@ fn managed_group_operations() {
 
             // Check that Alice & Charlie are the members of the group
             let members = alice_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
-            assert_eq!(members[1].identity(), b"Charlie");
+            assert_eq!(members[0].1.identity(), b"Alice");
+            assert_eq!(members[1].1.identity(), b"Charlie");
 
             // Check that Bob can no longer send messages
             assert!(bob_group.create_message(b"Should not go through").is_err());
@@ -504,8 +540,8 @@ fn managed_group_operations() {
 
             // Check that Alice & Bob are the members of the group
             let members = alice_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
-            assert_eq!(members[1].identity(), b"Bob");
+            assert_eq!(members[0].1.identity(), b"Alice");
+            assert_eq!(members[1].1.identity(), b"Bob");
 
             // Bob creates a new group
             let mut bob_group = ManagedGroup::new_from_welcome(
@@ -522,16 +558,16 @@ fn managed_group_operations() {
 
             // Check that Alice & Bob are the members of the group
             let members = alice_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
-            assert_eq!(members[1].identity(), b"Bob");
+            assert_eq!(members[0].1.identity(), b"Alice");
+            assert_eq!(members[1].1.identity(), b"Bob");
 
             // Make sure the group contains two members
             assert_eq!(bob_group.members().len(), 2);
 
             // Check that Alice & Bob are the members of the group
             let members = bob_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
-            assert_eq!(members[1].identity(), b"Bob");
+            assert_eq!(members[0].1.identity(), b"Alice");
+            assert_eq!(members[1].1.identity(), b"Bob");
 
             // === lice sends a message to the group ===
             let message_alice = b"Hi, I'm Alice!";
@@ -583,7 +619,7 @@ fn managed_group_operations() {
 
             // Check that Alice is the only member of the group
             let members = alice_group.members();
-            assert_eq!(members[0].identity(), b"Alice");
+            assert_eq!(members[0].1.identity(), b"Alice");
 
             // === Auto-save ===
 
@@ -612,8 +648,8 @@ fn managed_group_operations() {
             .expect("Could not create group from Welcome");
 
             assert_eq!(
-                alice_group.export_secret("before load", 32),
-                bob_group.export_secret("before load", 32)
+                alice_group.export_secret("before load", &[], 32),
+                bob_group.export_secret("before load", &[], 32)
             );
 
             // Re-load Bob's state from file
@@ -628,9 +664,1137 @@ fn managed_group_operations() {
 
             // Make sure the state is still the same
             assert_eq!(
-                alice_group.export_secret("after load", 32),
-                bob_group.export_secret("after load", 32)
+                alice_group.export_secret("after load", &[], 32),
+                bob_group.export_secret("after load", &[], 32)
+            );
+        }
+    }
+}
+
+/// This test removes members by identity
+///  - Alice adds Bob and Charlie
+///  - Unknown identities are rejected
+///  - Alice proposes to remove Bob by identity and commits
+///  - Alice removes Charlie by identity in a Commit
+#[test]
+fn managed_group_remove_members_by_identity() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let charlie_credential_bundle =
+            CredentialBundle::new("Charlie".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![])
+                .unwrap()
+                .key_package()
+                .clone();
+        let charlie_key_package =
+            KeyPackageBundle::new(&[ciphersuite.name()], &charlie_credential_bundle, vec![])
+                .unwrap()
+                .key_package()
+                .clone();
+
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            ManagedGroupCallbacks::default(),
+        );
+
+        // === Alice adds Bob and Charlie ===
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+        let (queued_messages, _welcome) = alice_group
+            .add_members(&[bob_key_package, charlie_key_package])
+            .expect("Could not add members");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert_eq!(
+            alice_group.member_index(b"Bob"),
+            Some(LeafIndex::from(1u32))
+        );
+        assert_eq!(
+            alice_group.member_index(b"Charlie"),
+            Some(LeafIndex::from(2u32))
+        );
+
+        // === Unknown identities are rejected ===
+        assert_eq!(
+            alice_group
+                .propose_remove_members_by_identity(&[&b"Bob"[..], &b"Mallory"[..]])
+                .err(),
+            Some(ManagedGroupError::Membership(
+                MembershipError::UnknownIdentity
+            ))
+        );
+        assert_eq!(
+            alice_group
+                .remove_members_by_identity(&[&b"Mallory"[..]])
+                .err(),
+            Some(ManagedGroupError::Membership(
+                MembershipError::UnknownIdentity
+            ))
+        );
+        assert!(alice_group.pending_proposals().is_empty());
+
+        // === Alice proposes to remove Bob and commits ===
+        let queued_messages = alice_group
+            .propose_remove_members_by_identity(&[&b"Bob"[..]])
+            .expect("Could not create proposal to remove Bob");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        let (queued_messages, _welcome_option) = alice_group
+            .process_pending_proposals()
+            .expect("Could not commit proposals");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        // Bob's leaf is blank, so Charlie keeps its leaf index
+        let members = alice_group.members();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[1].0, LeafIndex::from(2u32));
+        assert_eq!(members[1].1.identity(), b"Charlie");
+        assert_eq!(alice_group.member_index(b"Bob"), None);
+
+        // === Alice removes Charlie in a Commit ===
+        let (queued_messages, welcome_option) = alice_group
+            .remove_members_by_identity(&[&b"Charlie"[..]])
+            .expect("Could not remove Charlie");
+        assert!(welcome_option.is_none());
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert_eq!(alice_group.members().len(), 1);
+        assert_eq!(alice_group.member_index(b"Charlie"), None);
+    }
+}
+
+/// This test checks the built-in role policy
+///  - Alice creates a group with the role policy and becomes its owner
+///  - Alice adds Bob, who joins as a member
+///  - Bob can neither add Charlie nor change roles
+///  - Alice makes Bob an admin
+///  - Bob adds Charlie
+///  - Bob can't remove Alice
+///  - Bob can't add another device of Alice
+///  - Alice can't leave as the last owner
+///  - Alice removes Bob, which drops Bob's role
+#[test]
+fn managed_group_role_policy() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        // Define credential bundles
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let charlie_credential_bundle =
+            CredentialBundle::new("Charlie".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+
+        // Generate KeyPackages
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![]).unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package().clone();
+        let charlie_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &charlie_credential_bundle, vec![])
+                .unwrap();
+        let charlie_key_package = charlie_key_package_bundle.key_package().clone();
+
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            ManagedGroupCallbacks::default(),
+        )
+        .with_role_policy(true);
+
+        // === Alice creates a group ===
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+        assert_eq!(alice_group.role(b"Alice"), Some(Role::Owner));
+
+        // === Alice adds Bob ===
+        let (queued_messages, welcome) = alice_group
+            .add_members(&[bob_key_package])
+            .expect("Could not add Bob");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        let mut bob_group = ManagedGroup::new_from_welcome(
+            &bob_credential_bundle,
+            &managed_group_config,
+            welcome,
+            Some(alice_group.export_ratchet_tree()),
+            bob_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+
+        // Bob learns the roles from the group context
+        assert_eq!(bob_group.role(b"Alice"), Some(Role::Owner));
+        assert_eq!(bob_group.role(b"Bob"), Some(Role::Member));
+
+        // === Bob can neither add Charlie nor change roles ===
+        assert_eq!(
+            bob_group.add_members(&[charlie_key_package.clone()]).err(),
+            Some(ManagedGroupError::RolePolicy(
+                RolePolicyError::InsufficientRole
+            ))
+        );
+        assert_eq!(
+            bob_group.propose_role_change(b"Bob", Role::Owner).err(),
+            Some(ManagedGroupError::RolePolicy(
+                RolePolicyError::InsufficientRole
+            ))
+        );
+
+        // === Alice makes Bob an admin ===
+        let queued_messages = alice_group
+            .propose_role_change(b"Bob", Role::Admin)
+            .expect("Could not propose role change");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        let (queued_messages, _welcome_option) = alice_group
+            .process_pending_proposals()
+            .expect("Could not commit to proposals");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        assert_eq!(alice_group.role(b"Bob"), Some(Role::Admin));
+        assert_eq!(bob_group.role(b"Bob"), Some(Role::Admin));
+        assert_eq!(
+            alice_group.export_secret("", &[], 32),
+            bob_group.export_secret("", &[], 32)
+        );
+
+        // === Bob adds Charlie ===
+        let (queued_messages, _welcome) = bob_group
+            .add_members(&[charlie_key_package])
+            .expect("Could not add Charlie");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        assert_eq!(alice_group.members().len(), 3);
+        assert_eq!(alice_group.members(), bob_group.members());
+
+        // === Bob can't remove Alice ===
+        assert_eq!(
+            bob_group.remove_members(&[0]).err(),
+            Some(ManagedGroupError::RolePolicy(
+                RolePolicyError::InsufficientRole
+            ))
+        );
+
+        // === Bob can't add another device of Alice ===
+        let alice_key_package_bundle_2 =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        assert_eq!(
+            bob_group
+                .add_members(&[alice_key_package_bundle_2.key_package().clone()])
+                .err(),
+            Some(ManagedGroupError::RolePolicy(
+                RolePolicyError::InsufficientRole
+            ))
+        );
+
+        // === Alice can't leave as the last owner ===
+        assert_eq!(
+            alice_group.leave_group().err(),
+            Some(ManagedGroupError::RolePolicy(RolePolicyError::NoOwnerLeft))
+        );
+
+        // === Alice removes Bob, which drops Bob's role ===
+        let (queued_messages, _welcome_option) = alice_group
+            .remove_members_by_identity(&[&b"Bob"[..]])
+            .expect("Could not remove Bob");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert_eq!(alice_group.members().len(), 2);
+        assert_eq!(
+            alice_group.roles().expect("Roles are enabled").roles(),
+            vec![(&b"Alice"[..], Role::Owner)]
+        );
+    }
+}
+
+/// This test applies several operations in a single Commit
+///  - Alice creates a group and adds Bob
+///  - Alice removes Bob, adds Charlie and updates her leaf in one Commit
+#[test]
+fn managed_group_commit_builder() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        // Define credential bundles
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let charlie_credential_bundle =
+            CredentialBundle::new("Charlie".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+
+        // Generate KeyPackages
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![]).unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package().clone();
+        let charlie_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &charlie_credential_bundle, vec![])
+                .unwrap();
+        let charlie_key_package = charlie_key_package_bundle.key_package().clone();
+
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            ManagedGroupCallbacks::default(),
+        );
+
+        // === Alice creates a group and adds Bob ===
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+        let (queued_messages, welcome) = alice_group
+            .commit()
+            .add(bob_key_package)
+            .finish()
+            .expect("Could not add Bob");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert!(welcome.is_some());
+
+        let mut bob_group = ManagedGroup::new_from_welcome(
+            &bob_credential_bundle,
+            &managed_group_config,
+            welcome.unwrap(),
+            Some(alice_group.export_ratchet_tree()),
+            bob_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+
+        // Pre-shared keys are not supported yet
+        let epoch = alice_group.epoch();
+        assert_eq!(
+            alice_group
+                .commit()
+                .update(None)
+                .psk(b"external psk")
+                .finish()
+                .expect_err("Created a Commit with a pre-shared key"),
+            ManagedGroupError::Unsupported(UnsupportedError::PreSharedKeys)
+        );
+        assert_eq!(alice_group.epoch(), epoch);
+
+        // === Alice removes Bob, adds Charlie and updates in one Commit ===
+        let bob_index = alice_group.member_index(b"Bob").unwrap();
+        let (queued_messages, welcome) = alice_group
+            .commit()
+            .remove(bob_index.as_usize())
+            .add(charlie_key_package)
+            .update(None)
+            .finish()
+            .expect("Could not create Commit");
+
+        // The Commit is the only message
+        assert_eq!(queued_messages.len(), 1);
+
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert!(!bob_group.is_active());
+
+        let charlie_group = ManagedGroup::new_from_welcome(
+            &charlie_credential_bundle,
+            &managed_group_config,
+            welcome.expect("Expected a Welcome message"),
+            Some(alice_group.export_ratchet_tree()),
+            charlie_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+
+        assert_eq!(alice_group.members(), charlie_group.members());
+        assert_eq!(
+            alice_group.export_secret("", &[], 32),
+            charlie_group.export_secret("", &[], 32)
+        );
+    }
+}
+
+#[test]
+fn managed_group_multi_device_users() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        // Define credential bundles, Bob has two devices whose identities start
+        // with his user ID
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_phone_credential_bundle = CredentialBundle::new(
+            "Bob:phone".into(),
+            CredentialType::Basic,
+            ciphersuite.name(),
+        )
+        .unwrap();
+        let bob_laptop_credential_bundle = CredentialBundle::new(
+            "Bob:laptop".into(),
+            CredentialType::Basic,
+            ciphersuite.name(),
+        )
+        .unwrap();
+        let charlie_credential_bundle =
+            CredentialBundle::new("Charlie".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+
+        // Generate KeyPackages, Bob's devices carry his user ID
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_phone_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &bob_phone_credential_bundle,
+            vec![Box::new(UserIdExtension::new(b"Bob"))],
+        )
+        .unwrap();
+        let bob_laptop_key_package_bundle = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &bob_laptop_credential_bundle,
+            vec![Box::new(UserIdExtension::new(b"Bob"))],
+        )
+        .unwrap();
+        let charlie_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &charlie_credential_bundle, vec![])
+                .unwrap();
+        let bob_key_packages = vec![
+            bob_phone_key_package_bundle.key_package().clone(),
+            bob_laptop_key_package_bundle.key_package().clone(),
+        ];
+        assert_eq!(bob_key_packages[0].user_id(), b"Bob");
+        assert_eq!(
+            charlie_key_package_bundle.key_package().user_id(),
+            b"Charlie"
+        );
+
+        // Charlie can't claim Bob's user ID
+        assert_eq!(
+            KeyPackageBundle::new(
+                &[ciphersuite.name()],
+                &charlie_credential_bundle,
+                vec![Box::new(UserIdExtension::new(b"Bob"))],
+            )
+            .err(),
+            Some(KeyPackageError::UserIdMismatch)
+        );
+
+        let callbacks = ManagedGroupCallbacks::default()
+            .with_user_added(user_added)
+            .with_user_removed(user_removed);
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            callbacks,
+        );
+
+        // === Alice creates a group ===
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+
+        // Key packages of different users are rejected
+        assert_eq!(
+            alice_group.add_user(&[
+                bob_key_packages[0].clone(),
+                charlie_key_package_bundle.key_package().clone()
+            ]),
+            Err(ManagedGroupError::Membership(MembershipError::UserMismatch))
+        );
+        assert_eq!(
+            alice_group.add_user(&[]),
+            Err(ManagedGroupError::Membership(
+                MembershipError::NoKeyPackages
+            ))
+        );
+
+        // === Alice adds Bob with both devices ===
+        let (queued_messages, welcome) = alice_group
+            .add_user(&bob_key_packages)
+            .expect("Could not add Bob");
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        assert_eq!(
+            alice_group.users(),
+            vec![b"Alice".to_vec(), b"Bob".to_vec()]
+        );
+        let bob_devices = alice_group.user_devices(b"Bob");
+        assert_eq!(bob_devices.len(), 2);
+        assert_eq!(bob_devices[0].1.identity(), b"Bob:phone");
+        assert_eq!(bob_devices[1].1.identity(), b"Bob:laptop");
+
+        // Bob can't be added a second time
+        assert_eq!(
+            alice_group.add_user(&bob_key_packages).err(),
+            Some(ManagedGroupError::Membership(
+                MembershipError::UserAlreadyMember
+            ))
+        );
+
+        let mut bob_phone_group = ManagedGroup::new_from_welcome(
+            &bob_phone_credential_bundle,
+            &managed_group_config,
+            welcome,
+            Some(alice_group.export_ratchet_tree()),
+            bob_phone_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+        assert_eq!(bob_phone_group.users(), alice_group.users());
+
+        // === Alice removes Bob with all of his devices ===
+        assert_eq!(
+            alice_group.remove_user(b"Charlie"),
+            Err(ManagedGroupError::Membership(MembershipError::UnknownUser))
+        );
+        let (queued_messages, _) = alice_group
+            .remove_user(b"Bob")
+            .expect("Could not remove Bob");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_phone_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        assert!(!bob_phone_group.is_active());
+        assert_eq!(alice_group.users(), vec![b"Alice".to_vec()]);
+        assert!(alice_group.user_devices(b"Bob").is_empty());
+    }
+}
+
+/// This test checks the encrypted persistence of the group state
+///  - Alice saves her group sealed with a storage key
+///  - The sealed state doesn't contain the plain serialization
+///  - Loading fails with a wrong key and with a tampered state
+///  - Loading with the correct key restores the group
+#[test]
+fn managed_group_encrypted_persistence() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            ManagedGroupCallbacks::default(),
+        );
+
+        let alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+
+        // Keys must have the correct length
+        assert_eq!(
+            StorageKey::from_slice(&[0u8; 16]),
+            Err(StorageError::InvalidKeyLength)
+        );
+        let storage_key = StorageKey::random();
+        let storage_key =
+            StorageKey::from_slice(storage_key.as_slice()).expect("Could not create storage key");
+
+        // Save the sealed state
+        let mut sealed_state = vec![];
+        alice_group
+            .save_encrypted(&mut sealed_state, &storage_key)
+            .expect("Could not save group state");
+        let mut plain_state = vec![];
+        alice_group
+            .save(&mut plain_state)
+            .expect("Could not save group state");
+        assert!(!sealed_state
+            .windows(b"epoch_secrets".len())
+            .any(|window| window == b"epoch_secrets"));
+        assert!(plain_state
+            .windows(b"epoch_secrets".len())
+            .any(|window| window == b"epoch_secrets"));
+
+        // A wrong key is rejected
+        let error = ManagedGroup::load_encrypted(
+            sealed_state.as_slice(),
+            &StorageKey::random(),
+            alice_group.group_id(),
+            0,
+            &alice_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .err()
+        .expect("Loaded group with a wrong key");
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        // A tampered state is rejected
+        let mut tampered_state = sealed_state.clone();
+        let last = tampered_state.len() - 1;
+        tampered_state[last] ^= 0xff;
+        assert!(ManagedGroup::load_encrypted(
+            tampered_state.as_slice(),
+            &storage_key,
+            alice_group.group_id(),
+            0,
+            &alice_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .is_err());
+
+        // The state of another group is rejected
+        let error = ManagedGroup::load_encrypted(
+            sealed_state.as_slice(),
+            &storage_key,
+            &GroupId::from_slice(b"Other group"),
+            0,
+            &alice_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .err()
+        .expect("Loaded the state of another group");
+        assert_eq!(
+            error.into_inner().map(|e| e.to_string()),
+            Some(StorageError::GroupIdMismatch.to_string())
+        );
+
+        // A state that is older than the expected epoch is rejected
+        let error = ManagedGroup::load_encrypted(
+            sealed_state.as_slice(),
+            &storage_key,
+            alice_group.group_id(),
+            alice_group.epoch() + 1,
+            &alice_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .err()
+        .expect("Loaded a rolled back state");
+        assert_eq!(
+            error.into_inner().map(|e| e.to_string()),
+            Some(StorageError::EpochRollback.to_string())
+        );
+
+        // The correct key restores the group
+        let loaded_group = ManagedGroup::load_encrypted(
+            sealed_state.as_slice(),
+            &storage_key,
+            alice_group.group_id(),
+            alice_group.epoch(),
+            &alice_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .expect("Could not load group state");
+        assert_eq!(
+            alice_group.export_secret("after load", &[], 32),
+            loaded_group.export_secret("after load", &[], 32)
+        );
+    }
+}
+
+static CREDENTIAL_ROTATED: AtomicBool = AtomicBool::new(false);
+
+/// Event listener function for UpdateProposals that records a credential
+/// rotation
+fn credential_rotated(
+    _managed_group: &ManagedGroup,
+    _aad: &[u8],
+    old_credential: &Credential,
+    new_credential: &Credential,
+) {
+    assert_eq!(old_credential.identity(), new_credential.identity());
+    if old_credential != new_credential {
+        CREDENTIAL_ROTATED.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Event listener function for invalid messages that fails the test
+fn fail_on_invalid_message(_managed_group: &ManagedGroup, error: InvalidMessageError) {
+    panic!("Received an invalid message: {:?}", error);
+}
+
+/// Test credential rotation:
+///  - Bob can't rotate to a credential with a different identity
+///  - Bob rotates his credential and Alice commits the Update proposal
+///  - The `member_updated` event contains the old and the new credential
+///  - Bob signs with the new credential afterwards
+#[test]
+fn managed_group_credential_rotation() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![]).unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package().clone();
+
+        let callbacks = ManagedGroupCallbacks::new()
+            .with_member_updated(credential_rotated)
+            .with_invalid_message_received(fail_on_invalid_message);
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            callbacks,
+        );
+
+        // === Alice creates a group and adds Bob ===
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+        let (queued_messages, welcome) = alice_group.add_members(&[bob_key_package]).unwrap();
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        let mut bob_group = ManagedGroup::new_from_welcome(
+            &bob_credential_bundle,
+            &managed_group_config,
+            welcome,
+            Some(alice_group.export_ratchet_tree()),
+            bob_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+
+        // === Bob can't change his identity ===
+        let mallory_credential_bundle =
+            CredentialBundle::new("Mallory".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        assert_eq!(
+            bob_group
+                .rotate_credential(&mallory_credential_bundle)
+                .err(),
+            Some(ManagedGroupError::CredentialRotation(
+                CredentialRotationError::IdentityMismatch
+            ))
+        );
+
+        // === Bob rotates his credential and Alice commits ===
+        let bob_new_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        CREDENTIAL_ROTATED.store(false, Ordering::SeqCst);
+        let queued_messages = bob_group
+            .rotate_credential(&bob_new_credential_bundle)
+            .expect("Error rotating credential");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        // Bob still signs with the old credential until the rotation is
+        // committed
+        assert_eq!(bob_group.credential(), bob_credential_bundle.credential());
+
+        let (queued_messages, _welcome_option) = alice_group
+            .process_pending_proposals()
+            .expect("Could not commit proposals");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert!(CREDENTIAL_ROTATED.load(Ordering::SeqCst));
+
+        // Both groups have the new credential
+        assert_eq!(
+            bob_group.credential(),
+            bob_new_credential_bundle.credential()
+        );
+        assert_eq!(
+            alice_group.members()[1].1,
+            bob_new_credential_bundle.credential().clone()
+        );
+        assert_eq!(alice_group.members(), bob_group.members());
+
+        // === Bob sends a message signed with the new credential ===
+        let queued_message = bob_group
+            .create_message(b"Hi, I have a new key!")
+            .expect("Error creating application message");
+        alice_group
+            .process_messages(vec![queued_message])
+            .expect("The group is no longer active");
+    }
+}
+
+static ROTATION_NOT_COMMITTED: AtomicBool = AtomicBool::new(false);
+
+/// Event listener function for errors that records a dropped credential
+/// rotation
+fn rotation_not_committed(_managed_group: &ManagedGroup, error: ManagedGroupError) {
+    if error == ManagedGroupError::CredentialRotation(CredentialRotationError::NotCommitted) {
+        ROTATION_NOT_COMMITTED.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Test a pending credential rotation across save & load:
+///  - The pending credential is persisted, the bundle has to be set again
+///  - Loading with a credential bundle that doesn't match fails
+///  - The rotation is reported as not committed when another Commit is
+///    applied first
+#[test]
+fn managed_group_pending_credential_rotation() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+        let bob_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &bob_credential_bundle, vec![]).unwrap();
+        let bob_key_package = bob_key_package_bundle.key_package().clone();
+
+        let callbacks = ManagedGroupCallbacks::new()
+            .with_invalid_message_received(fail_on_invalid_message)
+            .with_error_occured(rotation_not_committed);
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            callbacks,
+        );
+
+        // === Alice creates a group and adds Bob ===
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+        let (queued_messages, welcome) = alice_group.add_members(&[bob_key_package]).unwrap();
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        let mut bob_group = ManagedGroup::new_from_welcome(
+            &bob_credential_bundle,
+            &managed_group_config,
+            welcome,
+            Some(alice_group.export_ratchet_tree()),
+            bob_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+
+        // === Bob rotates his credential and saves the group ===
+        let bob_new_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let queued_messages = bob_group
+            .rotate_credential(&bob_new_credential_bundle)
+            .expect("Error rotating credential");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        // Bob can't sign with the new credential before it was committed
+        assert_eq!(
+            bob_group
+                .set_credential_bundle(&bob_new_credential_bundle)
+                .err(),
+            Some(ManagedGroupError::CredentialRotation(
+                CredentialRotationError::CredentialMismatch
+            ))
+        );
+
+        let mut serialized_group = vec![];
+        bob_group
+            .save(&mut serialized_group)
+            .expect("Could not save group");
+
+        // === Loading with the wrong credential bundle fails ===
+        let error = ManagedGroup::load(
+            serialized_group.as_slice(),
+            &bob_new_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .err()
+        .expect("Loaded the group with the wrong credential bundle");
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        // === The pending credential survives save & load ===
+        let mut bob_group = ManagedGroup::load(
+            serialized_group.as_slice(),
+            &bob_credential_bundle,
+            managed_group_config.callbacks(),
+        )
+        .expect("Could not load group");
+        assert_eq!(
+            bob_group.pending_credential(),
+            Some(bob_new_credential_bundle.credential())
+        );
+        assert_eq!(
+            bob_group
+                .set_pending_credential_bundle(&alice_credential_bundle)
+                .err(),
+            Some(ManagedGroupError::CredentialRotation(
+                CredentialRotationError::CredentialMismatch
+            ))
+        );
+        bob_group
+            .set_pending_credential_bundle(&bob_new_credential_bundle)
+            .expect("Could not set the pending credential bundle");
+
+        // === Alice commits without the Update proposal ===
+        ROTATION_NOT_COMMITTED.store(false, Ordering::SeqCst);
+        let (queued_messages, _welcome_option) = alice_group
+            .self_update(None)
+            .expect("Could not update own key package");
+        alice_group
+            .process_messages(queued_messages.clone())
+            .expect("The group is no longer active");
+        bob_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+
+        // The rotation was dropped and Bob still signs with the old credential
+        assert!(ROTATION_NOT_COMMITTED.load(Ordering::SeqCst));
+        assert_eq!(bob_group.pending_credential(), None);
+        assert_eq!(bob_group.credential(), bob_credential_bundle.credential());
+        assert_eq!(alice_group.members(), bob_group.members());
+    }
+}
+
+/// Members can provide key packages for several ciphersuites and the one
+/// matching the group's ciphersuite is used.
+#[test]
+fn managed_group_multiple_ciphersuites() {
+    let ciphersuite_names = Config::supported_ciphersuite_names();
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        // Bob has a credential for each ciphersuite
+        let bob_credential_bundles: Vec<CredentialBundle> = ciphersuite_names
+            .iter()
+            .map(|&name| CredentialBundle::new("Bob".into(), CredentialType::Basic, name).unwrap())
+            .collect();
+        let bob_credential_bundle_refs: Vec<&CredentialBundle> =
+            bob_credential_bundles.iter().collect();
+        let charlie_credential_bundle =
+            CredentialBundle::new("Charlie".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+
+        // Bob has key packages for all ciphersuites
+        let mut bob_key_package_store = InMemoryKeyPackageStore::new();
+        let bob_key_packages: Vec<KeyPackage> = KeyPackageBundle::new_for_ciphersuites(
+            &ciphersuite_names,
+            &bob_credential_bundle_refs,
+            vec![],
+        )
+        .unwrap()
+        .into_iter()
+        .map(|key_package_bundle| {
+            let key_package = key_package_bundle.key_package().clone();
+            bob_key_package_store.store(key_package_bundle);
+            key_package
+        })
+        .collect();
+
+        // Charlie only has key packages for other ciphersuites
+        let charlie_key_packages: Vec<KeyPackage> = ciphersuite_names
+            .iter()
+            .filter(|&&name| name != ciphersuite.name())
+            .map(|&name| {
+                KeyPackageBundle::new(&[name], &charlie_credential_bundle, vec![])
+                    .unwrap()
+                    .key_package()
+                    .clone()
+            })
+            .collect();
+
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            ManagedGroupCallbacks::default(),
+        );
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+
+        // === Charlie can't be added ===
+        if !charlie_key_packages.is_empty() {
+            assert_eq!(
+                alice_group.add_members(&charlie_key_packages).err(),
+                Some(ManagedGroupError::Membership(
+                    MembershipError::NoMatchingKeyPackage
+                ))
             );
         }
+
+        // === Alice adds Bob with all of his key packages ===
+        let (queued_messages, welcome) = alice_group.add_members(&bob_key_packages).unwrap();
+        alice_group
+            .process_messages(queued_messages)
+            .expect("The group is no longer active");
+        assert_eq!(alice_group.members().len(), 2);
+
+        let bob_key_package_bundle = bob_key_package_store
+            .bundle_for_welcome(&welcome)
+            .expect("The Welcome doesn't reference any of Bob's key packages");
+        assert_eq!(
+            bob_key_package_bundle.key_package().ciphersuite_name(),
+            ciphersuite.name()
+        );
+        let bob_credential_bundle = bob_credential_bundles
+            .iter()
+            .find(|credential_bundle| {
+                credential_bundle.credential() == bob_key_package_bundle.key_package().credential()
+            })
+            .expect("The key package wasn't signed with one of Bob's credentials");
+        let bob_group = ManagedGroup::new_from_welcome(
+            bob_credential_bundle,
+            &managed_group_config,
+            welcome,
+            Some(alice_group.export_ratchet_tree()),
+            bob_key_package_bundle,
+        )
+        .expect("Error creating group from Welcome");
+        assert_eq!(bob_group.members().len(), 2);
+    }
+}
+
+/// This test makes sure that a managed group doesn't create proposals or
+/// Commits that add a key package with a lifetime that exceeds the maximum
+/// lifetime.
+#[test]
+fn managed_group_key_package_max_lifetime() {
+    for ciphersuite in Config::supported_ciphersuites() {
+        let group_id = GroupId::from_slice(b"Test Group");
+
+        let alice_credential_bundle =
+            CredentialBundle::new("Alice".into(), CredentialType::Basic, ciphersuite.name())
+                .unwrap();
+        let bob_credential_bundle =
+            CredentialBundle::new("Bob".into(), CredentialType::Basic, ciphersuite.name()).unwrap();
+        let alice_key_package_bundle =
+            KeyPackageBundle::new(&[ciphersuite.name()], &alice_credential_bundle, vec![]).unwrap();
+
+        // Bob's key package is valid for a century
+        let bob_key_package = KeyPackageBundle::new(
+            &[ciphersuite.name()],
+            &bob_credential_bundle,
+            vec![Box::new(LifetimeExtension::new(60 * 60 * 24 * 365 * 100))],
+        )
+        .unwrap()
+        .key_package()
+        .clone();
+
+        let managed_group_config = ManagedGroupConfig::new(
+            HandshakeMessageFormat::Plaintext,
+            UpdatePolicy::default(),
+            ManagedGroupCallbacks::default(),
+        );
+        let mut alice_group = ManagedGroup::new(
+            &alice_credential_bundle,
+            &managed_group_config,
+            group_id,
+            alice_key_package_bundle,
+        )
+        .unwrap();
+
+        // Neither a proposal nor a Commit is created
+        assert_eq!(
+            alice_group
+                .propose_add_members(&[bob_key_package.clone()])
+                .err(),
+            Some(ManagedGroupError::KeyPackage(
+                KeyPackageError::LifetimeTooLong
+            ))
+        );
+        assert_eq!(
+            alice_group.add_members(&[bob_key_package.clone()]).err(),
+            Some(ManagedGroupError::KeyPackage(
+                KeyPackageError::LifetimeTooLong
+            ))
+        );
+        assert_eq!(
+            alice_group.commit().add(bob_key_package).finish().err(),
+            Some(ManagedGroupError::KeyPackage(
+                KeyPackageError::LifetimeTooLong
+            ))
+        );
+        assert!(alice_group.pending_proposals().is_empty());
+        assert_eq!(alice_group.members().len(), 1);
     }
 }